@@ -4,6 +4,10 @@ fn main() -> Result<()> {
     tonic_prost_build::configure()
         .build_server(true)
         .build_client(true)
+        .type_attribute(
+            "telemetry.SensorData",
+            r#"#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]"#,
+        )
         .compile_protos(&["proto/drone.proto", "proto/telemetry.proto"], &["proto/"])?;
     Ok(())
 }
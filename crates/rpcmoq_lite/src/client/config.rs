@@ -1,6 +1,11 @@
 use std::time::Duration;
 
 use bon::Builder;
+use moq_lite::TrackProducer;
+
+use crate::compression::Compression;
+use crate::connection::RpcOutbound;
+use crate::error::RpcConfigError;
 
 /// Configuration for the RPC client.
 #[derive(Debug, Clone, Builder)]
@@ -22,12 +27,104 @@ pub struct RpcClientConfig {
     #[builder(default = "primary".to_string())]
     pub track_name: String,
 
+    /// Names of additional channels carried by each connection alongside the primary track,
+    /// opened with [`RpcConnection::channel`](crate::RpcConnection::channel).
+    /// Each channel is a separate pair of tracks, served by the handler registered for it with
+    /// [`RpcRouter::register_channel`](crate::RpcRouter::register_channel). Defaults to none.
+    #[builder(default)]
+    pub channels: Vec<String>,
+
     /// Timeout for waiting for server response broadcast.
     #[builder(default = Duration::from_secs(30))]
     pub timeout: Duration,
+
+    /// Timeout for waiting for each response once connected.
+    /// If not set, receiving waits for a response indefinitely.
+    pub response_timeout: Option<Duration>,
+
+    /// High-water mark of the outbound send buffer.
+    /// If set, sending waits while this many requests are buffered waiting to be written.
+    /// If not set, requests are written directly to the track and sending never waits.
+    pub send_buffer: Option<usize>,
+
+    /// Skip response frames that fail to decode instead of yielding `RpcWireError::Decode`.
+    /// Defaults to `false`.
+    #[builder(default)]
+    pub skip_decode_errors: bool,
+
+    /// Compression applied to outgoing frames and expected of incoming frames.
+    /// Both peers must be configured with the same compression. Defaults to none.
+    #[builder(default)]
+    pub compression: Compression,
+
+    /// Interval at which heartbeats are sent on each connection's request track.
+    /// If not set, no heartbeats are sent.
+    pub keepalive_interval: Option<Duration>,
 }
 
 impl RpcClientConfig {
+    /// Environment variable holding the client identifier. Required.
+    pub const ENV_CLIENT_ID: &'static str = "RPC_CLIENT_ID";
+    /// Environment variable holding the client broadcast prefix.
+    pub const ENV_CLIENT_PREFIX: &'static str = "RPC_CLIENT_PREFIX";
+    /// Environment variable holding the server response prefix.
+    pub const ENV_SERVER_PREFIX: &'static str = "RPC_SERVER_PREFIX";
+    /// Environment variable holding the handshake timeout in whole seconds.
+    pub const ENV_TIMEOUT_SECS: &'static str = "RPC_CLIENT_TIMEOUT_SECS";
+
+    /// Read a configuration from the environment.
+    ///
+    /// Reads [`ENV_CLIENT_ID`](Self::ENV_CLIENT_ID), [`ENV_CLIENT_PREFIX`](Self::ENV_CLIENT_PREFIX),
+    /// [`ENV_SERVER_PREFIX`](Self::ENV_SERVER_PREFIX) and
+    /// [`ENV_TIMEOUT_SECS`](Self::ENV_TIMEOUT_SECS). Variables which are unset or empty fall back
+    /// to the builder defaults, except for the client id which is required. A timeout which is
+    /// not a whole number of seconds is an error.
+    pub fn from_env() -> Result<Self, RpcConfigError> {
+        let var = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+        let client_id =
+            var(Self::ENV_CLIENT_ID).ok_or(RpcConfigError::MissingVar(Self::ENV_CLIENT_ID))?;
+        let timeout = var(Self::ENV_TIMEOUT_SECS)
+            .map(|value| {
+                value
+                    .parse()
+                    .map(Duration::from_secs)
+                    .map_err(|_| RpcConfigError::InvalidVar {
+                        name: Self::ENV_TIMEOUT_SECS,
+                        value,
+                    })
+            })
+            .transpose()?;
+
+        Ok(Self::builder()
+            .client_id(client_id)
+            .maybe_client_prefix(var(Self::ENV_CLIENT_PREFIX))
+            .maybe_server_prefix(var(Self::ENV_SERVER_PREFIX))
+            .maybe_timeout(timeout)
+            .build())
+    }
+
+    /// Wrap a request track in an outbound stream with the configured buffering and compression.
+    pub(crate) fn outbound(&self, track: TrackProducer) -> RpcOutbound {
+        match self.send_buffer {
+            Some(high_water) => RpcOutbound::buffered(track, high_water),
+            None => RpcOutbound::new(track),
+        }
+        .with_compression(self.compression)
+    }
+
+    /// The first channel which is configured twice or named after the primary track, whose
+    /// tracks would collide.
+    pub(crate) fn invalid_channel(&self) -> Option<&str> {
+        self.channels
+            .iter()
+            .enumerate()
+            .find(|(index, name)| {
+                **name == self.track_name || self.channels[..*index].contains(name)
+            })
+            .map(|(_, name)| name.as_str())
+    }
+
     /// Build the client broadcast path for a given gRPC path.
     pub(crate) fn client_path(&self, grpc_path: &str) -> String {
         match &self.client_prefix {
@@ -44,3 +141,120 @@ impl RpcClientConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Serializes tests which modify the process environment.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    const VARS: [&str; 4] = [
+        RpcClientConfig::ENV_CLIENT_ID,
+        RpcClientConfig::ENV_CLIENT_PREFIX,
+        RpcClientConfig::ENV_SERVER_PREFIX,
+        RpcClientConfig::ENV_TIMEOUT_SECS,
+    ];
+
+    /// Run `f` with exactly the given variables set, clearing them afterwards.
+    fn with_env<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        // SAFETY: tests touching the environment hold ENV_LOCK, so no other thread reads or
+        // writes these variables concurrently.
+        unsafe {
+            for name in VARS {
+                std::env::remove_var(name);
+            }
+            for (name, value) in vars {
+                std::env::set_var(name, value);
+            }
+        }
+        let result = f();
+        unsafe {
+            for name in VARS {
+                std::env::remove_var(name);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_from_env_reads_all_vars() {
+        let config = with_env(
+            &[
+                ("RPC_CLIENT_ID", "drone-1"),
+                ("RPC_CLIENT_PREFIX", "drone"),
+                ("RPC_SERVER_PREFIX", "server"),
+                ("RPC_CLIENT_TIMEOUT_SECS", "5"),
+            ],
+            RpcClientConfig::from_env,
+        )
+        .unwrap();
+
+        assert_eq!(config.client_id, "drone-1");
+        assert_eq!(config.client_prefix.as_deref(), Some("drone"));
+        assert_eq!(config.server_prefix.as_deref(), Some("server"));
+        assert_eq!(config.timeout, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_from_env_falls_back_to_defaults() {
+        let config = with_env(
+            &[("RPC_CLIENT_ID", "drone-1"), ("RPC_CLIENT_PREFIX", "")],
+            RpcClientConfig::from_env,
+        )
+        .unwrap();
+
+        assert_eq!(config.client_prefix, None);
+        assert_eq!(config.server_prefix, None);
+        assert_eq!(config.timeout, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_from_env_rejects_malformed_timeout() {
+        let result = with_env(
+            &[
+                ("RPC_CLIENT_ID", "drone-1"),
+                ("RPC_CLIENT_TIMEOUT_SECS", "soon"),
+            ],
+            RpcClientConfig::from_env,
+        );
+
+        assert!(matches!(
+            result,
+            Err(RpcConfigError::InvalidVar { name: "RPC_CLIENT_TIMEOUT_SECS", value }) if value == "soon"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_channel() {
+        let config = |channels: &[&str]| {
+            RpcClientConfig::builder()
+                .client_id("drone-1".to_string())
+                .channels(channels.iter().map(|name| name.to_string()).collect())
+                .build()
+        };
+
+        assert_eq!(config(&["control", "data"]).invalid_channel(), None);
+        assert_eq!(
+            config(&["control", "primary"]).invalid_channel(),
+            Some("primary")
+        );
+        assert_eq!(
+            config(&["control", "data", "control"]).invalid_channel(),
+            Some("control")
+        );
+    }
+
+    #[test]
+    fn test_from_env_requires_client_id() {
+        let result = with_env(&[], RpcClientConfig::from_env);
+
+        assert!(matches!(
+            result,
+            Err(RpcConfigError::MissingVar("RPC_CLIENT_ID"))
+        ));
+    }
+}
@@ -1,13 +1,19 @@
 use futures::{Sink, Stream};
-use moq_lite::BroadcastProducer;
+use moq_lite::{BroadcastConsumer, BroadcastProducer, TrackProducer};
 use prost::Message;
+use std::collections::HashMap;
+use std::future::Future;
 use std::marker::PhantomData;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::task::{Context, Poll};
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+use tokio::time::{Instant, Sleep};
 
-use crate::connection::{RpcInbound, RpcOutbound};
-use crate::error::{RpcSendError, RpcWireError};
+use crate::client::config::RpcClientConfig;
+use crate::connection::{Keepalive, RpcInbound, RpcOutbound};
+use crate::error::{RpcClientError, RpcSendError, RpcWireError};
+use crate::frame::Frame;
 
 /// A bidirectional RPC connection.
 ///
@@ -35,6 +41,16 @@ use crate::error::{RpcSendError, RpcWireError};
 pub struct RpcConnection<Req, Resp> {
     sender: RpcSender<Req>,
     receiver: RpcReceiver<Resp>,
+    // None for a connection reassembled by reunite
+    channels: Option<Channels>,
+}
+
+/// The channels of a connection which have not been opened yet.
+struct Channels {
+    // Request tracks created up front, so they exist before the server subscribes
+    requests: HashMap<String, TrackProducer>,
+    server_broadcast: BroadcastConsumer,
+    config: RpcClientConfig,
 }
 
 impl<Req, Resp> RpcConnection<Req, Resp> {
@@ -43,13 +59,108 @@ impl<Req, Resp> RpcConnection<Req, Resp> {
         outbound: RpcOutbound,
         inbound: RpcInbound,
         broadcast: Arc<BroadcastProducer>,
+        server_broadcast: BroadcastConsumer,
+        channels: HashMap<String, TrackProducer>,
+        config: &RpcClientConfig,
     ) -> Self {
+        let (sender, receiver) = halves(outbound, inbound, broadcast, config);
+
+        Self {
+            sender,
+            receiver,
+            channels: Some(Channels {
+                requests: channels,
+                server_broadcast,
+                config: config.clone(),
+            }),
+        }
+    }
+
+    /// Open one of the connection's [`channels`](RpcClientConfig::channels), returning its send
+    /// and receive halves.
+    ///
+    /// A channel is carried by its own pair of tracks, named after the channel, under the same
+    /// broadcasts as the connection, so its messages never mix with those of the primary track or
+    /// other channels. The channel halves keep the connection's broadcast alive like the halves
+    /// returned by [`split`](Self::split). Channels must be opened before the connection is split,
+    /// and each channel can be opened once.
+    ///
+    /// If the router has no handler registered for the channel, nothing is ever received on it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RpcClientError::UnknownChannel`] if the channel is not configured or has already
+    /// been opened.
+    pub fn channel<CReq, CResp>(
+        &mut self,
+        name: &str,
+    ) -> Result<(RpcSender<CReq>, RpcReceiver<CResp>), RpcClientError> {
+        let unknown = || RpcClientError::UnknownChannel(name.to_string());
+        let Channels {
+            requests,
+            server_broadcast,
+            config,
+        } = self.channels.as_mut().ok_or_else(unknown)?;
+
+        let track = requests.remove(name).ok_or_else(unknown)?;
+        let outbound = config.outbound(track);
+        let inbound = RpcInbound::new(server_broadcast, name).with_compression(config.compression);
+
+        Ok(halves(
+            outbound,
+            inbound,
+            Arc::clone(&self.sender._broadcast),
+            config,
+        ))
+    }
+
+    /// The time a frame, including heartbeats, was last received from the server.
+    ///
+    /// See [`RpcReceiver::last_seen`].
+    pub fn last_seen(&self) -> Instant {
+        self.receiver.last_seen()
+    }
+
+    /// Whether nothing has been received from the server for longer than `max_idle`.
+    ///
+    /// See [`RpcReceiver::is_stale`].
+    pub fn is_stale(&self, max_idle: Duration) -> bool {
+        self.receiver.is_stale(max_idle)
+    }
+
+    /// Reassemble a connection from the halves returned by [`split`](Self::split).
+    ///
+    /// The halves must come from the same connection, which is not checked.
+    pub fn reunite(sender: RpcSender<Req>, receiver: RpcReceiver<Resp>) -> Self {
+        // Any unopened channels were dropped by split
         Self {
-            sender: RpcSender::new(outbound, Arc::clone(&broadcast)),
-            receiver: RpcReceiver::new(inbound, broadcast),
+            sender,
+            receiver,
+            channels: None,
         }
     }
 
+    /// Close the connection, tearing down the client side of the RPC.
+    ///
+    /// The request track is closed, ending the server's inbound stream, and the client broadcast
+    /// is unannounced, so the server releases its session for the connection. Split halves can
+    /// be closed by first [reuniting](Self::reunite) them.
+    ///
+    /// To close only the request track, close the connection as a `Sink` instead, for example
+    /// with `SinkExt::close`. Sends after that fail with [`RpcSendError::ConnectionClosed`].
+    pub fn close(self) {
+        let Self {
+            mut sender,
+            receiver,
+            ..
+        } = self;
+        sender.request = None;
+
+        // The broadcast is shared by both halves, so is unannounced once both are dropped
+        drop(sender);
+        drop(receiver);
+    }
+
     /// Split the connection into separate send and receive halves.
     ///
     /// Both halves share ownership of the underlying broadcast, so the connection
@@ -93,25 +204,64 @@ where
     }
 }
 
+/// Build the send and receive halves of a connection, or of one of its channels.
+fn halves<Req, Resp>(
+    outbound: RpcOutbound,
+    inbound: RpcInbound,
+    broadcast: Arc<BroadcastProducer>,
+    config: &RpcClientConfig,
+) -> (RpcSender<Req>, RpcReceiver<Resp>) {
+    let keepalive = config
+        .keepalive_interval
+        .map(|interval| outbound.spawn_keepalive(interval));
+
+    (
+        RpcSender::new(outbound, keepalive, Arc::clone(&broadcast)),
+        RpcReceiver::new(inbound, broadcast, config),
+    )
+}
+
 /// The send half of an `RpcConnection`.
 ///
 /// Implements `Sink` for sending request messages to the server.
 /// Shares ownership of the underlying broadcast with `RpcReceiver`.
 pub struct RpcSender<Req> {
-    outbound: RpcOutbound,
+    // None once the sender has been closed
+    request: Option<RequestTrack>,
     // Keeps the broadcast alive; shared with RpcReceiver when split
     _broadcast: Arc<BroadcastProducer>,
     _marker: PhantomData<fn(Req)>,
 }
 
 impl<Req> RpcSender<Req> {
-    fn new(outbound: RpcOutbound, broadcast: Arc<BroadcastProducer>) -> Self {
+    fn new(
+        outbound: RpcOutbound,
+        keepalive: Option<Keepalive>,
+        broadcast: Arc<BroadcastProducer>,
+    ) -> Self {
         Self {
-            outbound,
+            request: Some(RequestTrack {
+                outbound,
+                _keepalive: keepalive,
+            }),
             _broadcast: broadcast,
             _marker: PhantomData,
         }
     }
+
+    fn outbound(&mut self) -> Result<&mut RpcOutbound, RpcSendError> {
+        self.request
+            .as_mut()
+            .map(|request| &mut request.outbound)
+            .ok_or(RpcSendError::ConnectionClosed)
+    }
+}
+
+/// The request track of an `RpcSender`, which closes once dropped.
+struct RequestTrack {
+    outbound: RpcOutbound,
+    // Sends heartbeats until dropped, if keepalive is configured
+    _keepalive: Option<Keepalive>,
 }
 
 impl<Req> Sink<Req> for RpcSender<Req>
@@ -120,13 +270,13 @@ where
 {
     type Error = RpcSendError;
 
-    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        // MoQ tracks are always ready to accept writes
-        Poll::Ready(Ok(()))
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Pending only while a bounded send buffer is full
+        self.outbound()?.poll_ready(cx)
     }
 
     fn start_send(mut self: Pin<&mut Self>, item: Req) -> Result<(), Self::Error> {
-        self.outbound.send(&item)?;
+        self.outbound()?.send(&item)?;
         Ok(())
     }
 
@@ -135,8 +285,13 @@ where
         Poll::Ready(Ok(()))
     }
 
-    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        // Closing is handled by dropping the broadcast
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        // Dropping the request track closes it, ending the server's inbound stream. The broadcast
+        // stays announced until the sender and any receiver are dropped.
+        self.request = None;
         Poll::Ready(Ok(()))
     }
 }
@@ -145,21 +300,67 @@ where
 ///
 /// Implements `Stream` for receiving response messages from the server.
 /// Shares ownership of the underlying broadcast with `RpcSender`.
+///
+/// If a response timeout is configured, `RpcWireError::Timeout` is yielded whenever no response
+/// arrives within the timeout. The stream may continue to be polled afterwards.
+///
+/// If the server's gRPC backend fails, its status is yielded as `RpcWireError::Status`, after
+/// which the stream ends.
 pub struct RpcReceiver<Resp> {
     inbound: RpcInbound,
+    response_timeout: Option<Duration>,
+    skip_decode_errors: bool,
+    // Set once the server has reported an error status, after which the stream ends
+    done: bool,
+    // The deadline for the response currently being waited on, if any
+    deadline: Option<Pin<Box<Sleep>>>,
     // Keeps the broadcast alive; shared with RpcSender when split
     _broadcast: Arc<BroadcastProducer>,
+    // Keeps the request track open, and its heartbeats going, when the RpcSender has been dropped
+    _request: Option<RequestTrack>,
     _marker: PhantomData<fn() -> Resp>,
 }
 
 impl<Resp> RpcReceiver<Resp> {
-    fn new(inbound: RpcInbound, broadcast: Arc<BroadcastProducer>) -> Self {
+    fn new(
+        inbound: RpcInbound,
+        broadcast: Arc<BroadcastProducer>,
+        config: &RpcClientConfig,
+    ) -> Self {
         Self {
             inbound,
+            response_timeout: config.response_timeout,
+            skip_decode_errors: config.skip_decode_errors,
+            done: false,
+            deadline: None,
             _broadcast: broadcast,
+            _request: None,
             _marker: PhantomData,
         }
     }
+
+    /// Keep the request track of `sender` open for as long as this receiver is alive.
+    ///
+    /// Dropping the request track cancels the server's inbound stream, so this is used when only
+    /// the receive half is handed out.
+    pub(crate) fn retain_request<Req>(mut self, sender: RpcSender<Req>) -> Self {
+        self._request = sender.request;
+        self
+    }
+
+    /// The time a frame, including heartbeats, was last received from the server.
+    ///
+    /// Frames are only received while the receiver is polled, so this is only meaningful while
+    /// waiting on responses. Enable [`keepalive_interval`](crate::RpcRouterConfig::keepalive_interval)
+    /// on the router to receive heartbeats while the server is idle.
+    pub fn last_seen(&self) -> Instant {
+        self.inbound.last_seen()
+    }
+
+    /// Whether nothing has been received from the server for longer than `max_idle`.
+    pub fn is_stale(&self, max_idle: Duration) -> bool {
+        self.inbound.is_stale(max_idle)
+    }
 }
 
 impl<Resp> Stream for RpcReceiver<Resp>
@@ -169,14 +370,54 @@ where
     type Item = Result<Resp, RpcWireError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match Pin::new(&mut self.inbound).poll_next(cx) {
-            Poll::Ready(Some(Ok(bytes))) => match Resp::decode(bytes) {
-                Ok(msg) => Poll::Ready(Some(Ok(msg))),
-                Err(_) => Poll::Ready(Some(Err(RpcWireError::Decode))),
-            },
-            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(RpcWireError::from(err)))),
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
+        loop {
+            if self.done {
+                return Poll::Ready(None);
+            }
+
+            // Heartbeats and trailers are not responses, so don't reset the response timeout
+            let polled = loop {
+                match self.inbound.poll_frame(cx) {
+                    Poll::Ready(Some(Ok(Frame::Heartbeat | Frame::Trailer(_)))) => continue,
+                    polled => break polled,
+                }
+            };
+
+            if polled.is_pending() {
+                if let Some(timeout) = self.response_timeout {
+                    let deadline = self
+                        .deadline
+                        .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+                    ready!(deadline.as_mut().poll(cx));
+
+                    self.deadline = None;
+                    return Poll::Ready(Some(Err(RpcWireError::Timeout)));
+                }
+            } else {
+                self.deadline = None;
+            }
+
+            return match polled {
+                Poll::Ready(Some(Ok(Frame::Message(bytes)))) => match Resp::decode(bytes) {
+                    Ok(msg) => Poll::Ready(Some(Ok(msg))),
+                    Err(err) if self.skip_decode_errors => {
+                        tracing::warn!(%err, "Skipping response frame that failed to decode");
+                        continue;
+                    }
+                    Err(_) => Poll::Ready(Some(Err(RpcWireError::Decode))),
+                },
+                Poll::Ready(Some(Ok(Frame::Error(payload)))) => {
+                    self.done = true;
+                    match Frame::decode_status(&payload) {
+                        Ok(status) => Poll::Ready(Some(Err(RpcWireError::Status(status)))),
+                        Err(_) => Poll::Ready(Some(Err(RpcWireError::Decode))),
+                    }
+                }
+                Poll::Ready(Some(Ok(Frame::Heartbeat | Frame::Trailer(_)))) => continue,
+                Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            };
         }
     }
 }
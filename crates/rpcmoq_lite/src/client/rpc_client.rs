@@ -1,11 +1,13 @@
+use futures::future::BoxFuture;
+use futures::{FutureExt, SinkExt, StreamExt};
 use moq_lite::{BroadcastConsumer, OriginConsumer, OriginProducer, Track};
 use prost::Message;
 use std::sync::Arc;
 use tracing::{debug, info};
 
 use crate::client::config::RpcClientConfig;
-use crate::client::connection::RpcConnection;
-use crate::connection::{RpcInbound, RpcOutbound};
+use crate::client::connection::{RpcConnection, RpcReceiver, RpcSender};
+use crate::connection::RpcInbound;
 use crate::error::RpcClientError;
 
 /// An RPC client that connects to a server over MoQ.
@@ -82,6 +84,7 @@ impl RpcClient {
     /// * Failed to create the client broadcast
     /// * Timeout waiting for server response broadcast
     /// * Server broadcast was not found
+    /// * A channel is configured twice or named after the primary track
     pub async fn connect<Req, Resp>(
         &mut self,
         grpc_path: impl Into<String>,
@@ -90,6 +93,10 @@ impl RpcClient {
         Req: Message + Default + Send + 'static,
         Resp: Message + Default + Send + 'static,
     {
+        if let Some(channel) = self.config.invalid_channel() {
+            return Err(RpcClientError::InvalidChannel(channel.to_string()));
+        }
+
         let grpc_path = grpc_path.into();
         let client_path = self.config.client_path(&grpc_path);
         let server_path = self.config.server_path(&grpc_path);
@@ -112,12 +119,21 @@ impl RpcClient {
 
         // Create the outbound track for sending requests
         let outbound_track = broadcast.create_track(Track::new(&self.config.track_name));
-        let outbound = RpcOutbound::new(outbound_track);
+        let outbound = self.config.outbound(outbound_track);
+
+        // Channel tracks must exist before the server subscribes to them
+        let channels = self
+            .config
+            .channels
+            .iter()
+            .map(|name| (name.clone(), broadcast.create_track(Track::new(name))))
+            .collect();
 
         let server_broadcast = self.wait_for_server(&server_path).await?;
 
         // Subscribe to the server's response track
-        let inbound = RpcInbound::new(&server_broadcast, &self.config.track_name);
+        let inbound = RpcInbound::new(&server_broadcast, &self.config.track_name)
+            .with_compression(self.config.compression);
 
         info!(
             client_id = %self.config.client_id,
@@ -128,7 +144,107 @@ impl RpcClient {
         // Wrap the broadcast in Arc for shared ownership when split
         let broadcast = Arc::new(broadcast);
 
-        Ok(RpcConnection::new(outbound, inbound, broadcast))
+        Ok(RpcConnection::new(
+            outbound,
+            inbound,
+            broadcast,
+            server_broadcast,
+            channels,
+            &self.config,
+        ))
+    }
+
+    /// Perform a unary call, sending exactly one request and awaiting exactly one response.
+    ///
+    /// A connection is established as in [`connect`](Self::connect) and torn down once the
+    /// response is received.
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors of [`connect`](Self::connect), returns an error if:
+    /// * The request could not be sent
+    /// * The server responded with an error
+    /// * The connection closed before a response was received, or no response was received within
+    ///   the configured timeout, both reported as [`RpcClientError::ConnectionClosed`]
+    pub async fn unary<Req, Resp>(
+        &mut self,
+        grpc_path: impl Into<String>,
+        request: Req,
+    ) -> Result<Resp, RpcClientError>
+    where
+        Req: Message + Default + Send + 'static,
+        Resp: Message + Default + Send + 'static,
+    {
+        let mut conn = self.connect::<Req, Resp>(grpc_path).await?;
+        conn.send(request).await?;
+
+        let response = tokio::time::timeout(self.config.timeout, conn.next())
+            .await
+            .map_err(|_| RpcClientError::ConnectionClosed)?
+            .ok_or(RpcClientError::ConnectionClosed)??;
+
+        Ok(response)
+    }
+
+    /// Perform a server-streaming call, sending exactly one request and returning the stream of
+    /// responses.
+    ///
+    /// The request track is kept open for as long as the returned [`RpcReceiver`] is alive.
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors of [`connect`](Self::connect), returns an error if the request
+    /// could not be sent.
+    pub async fn server_streaming<Req, Resp>(
+        &mut self,
+        grpc_path: impl Into<String>,
+        request: Req,
+    ) -> Result<RpcReceiver<Resp>, RpcClientError>
+    where
+        Req: Message + Default + Send + 'static,
+        Resp: Message + Default + Send + 'static,
+    {
+        let (mut sender, receiver) = self.connect::<Req, Resp>(grpc_path).await?.split();
+        sender.send(request).await?;
+
+        Ok(receiver.retain_request(sender))
+    }
+
+    /// Perform a client-streaming call, returning a sender for the stream of requests and a future
+    /// resolving to the single response.
+    ///
+    /// The server observes the end of the request stream once the [`RpcSender`] is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`connect`](Self::connect). The response future fails if the server
+    /// responded with an error or the connection closed before a response was received.
+    pub async fn client_streaming<Req, Resp>(
+        &mut self,
+        grpc_path: impl Into<String>,
+    ) -> Result<
+        (
+            RpcSender<Req>,
+            BoxFuture<'static, Result<Resp, RpcClientError>>,
+        ),
+        RpcClientError,
+    >
+    where
+        Req: Message + Default + Send + 'static,
+        Resp: Message + Default + Send + 'static,
+    {
+        let (sender, mut receiver) = self.connect::<Req, Resp>(grpc_path).await?.split();
+
+        let response = async move {
+            let response = receiver
+                .next()
+                .await
+                .ok_or(RpcClientError::ConnectionClosed)??;
+            Ok(response)
+        }
+        .boxed();
+
+        Ok((sender, response))
     }
 
     /// Wait for the server to announce its response broadcast.
@@ -178,3 +294,284 @@ impl RpcClient {
         &self.config
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use tokio::time::Instant;
+
+    use super::*;
+    use crate::error::{RpcSendError, RpcWireError};
+    use crate::server::RpcRouterConfig;
+    use crate::testing::Ping;
+
+    const ECHO_PATH: &str = "test.EchoService/Echo";
+    const REPEAT_PATH: &str = "test.EchoService/Repeat";
+    const LAST_PATH: &str = "test.EchoService/Last";
+    const SILENT_PATH: &str = "test.EchoService/Silent";
+    const DENIED_PATH: &str = "test.EchoService/Denied";
+
+    fn ping(text: &str) -> Ping {
+        Ping {
+            text: text.to_string(),
+        }
+    }
+
+    fn loopback() -> RpcClient {
+        loopback_with(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        )
+    }
+
+    fn loopback_with(config: RpcClientConfig) -> RpcClient {
+        loopback_with_router(config, RpcRouterConfig::builder().build())
+    }
+
+    /// Wire a client to a router through in-process origins, with test handlers registered.
+    fn loopback_with_router(config: RpcClientConfig, router_config: RpcRouterConfig) -> RpcClient {
+        let (client_sides, server_sides) = crate::testing::loopback();
+
+        let mut router = server_sides.router(router_config);
+        router
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+        // Respond to each request three times, pacing responses so each is observed
+        router
+            .register::<Ping, Ping, _, _, _>(REPEAT_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().flat_map(|ping| {
+                    futures::stream::iter(std::iter::repeat_n(ping, 3)).then(|ping| async {
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        Ok(ping)
+                    })
+                }))
+            })
+            .unwrap();
+        // Respond once with the last request after the request stream ends
+        router
+            .register::<Ping, Ping, _, _, _>(LAST_PATH, |_client_id, inbound| async move {
+                let last = inbound
+                    .into_ok_stream()
+                    .fold(None, |_, ping| async { Some(ping) })
+                    .await;
+                Ok(futures::stream::iter(last.map(Ok)))
+            })
+            .unwrap();
+        // Tag the responses of each channel with the channel name
+        for channel in ["a", "b"] {
+            router
+                .register_channel::<Ping, Ping, _, _, _>(
+                    ECHO_PATH,
+                    channel,
+                    move |_client_id, inbound| async move {
+                        Ok(inbound
+                            .into_ok_stream()
+                            .map(move |ping| Ok(self::ping(&format!("{channel}:{}", ping.text)))))
+                    },
+                )
+                .unwrap();
+        }
+        // Fail the stream with a status once the first request arrives
+        router
+            .register::<Ping, Ping, _, _, _>(DENIED_PATH, |_client_id, inbound| async move {
+                Ok(inbound
+                    .into_ok_stream()
+                    .take(1)
+                    .map(|_| Err(tonic::Status::permission_denied("not allowed"))))
+            })
+            .unwrap();
+        // Never respond to any request
+        router
+            .register::<Ping, Ping, _, _, _>(SILENT_PATH, |_client_id, _inbound| async move {
+                Ok(futures::stream::pending())
+            })
+            .unwrap();
+        tokio::spawn(router.run());
+
+        client_sides.client(config)
+    }
+
+    #[tokio::test]
+    async fn test_unary_echo() {
+        let mut client = loopback();
+
+        let request = Ping {
+            text: "hello".to_string(),
+        };
+        let response: Ping = client.unary(ECHO_PATH, request.clone()).await.unwrap();
+
+        assert_eq!(response, request);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unary_without_response() {
+        let mut client = loopback();
+
+        let start = Instant::now();
+        let result = client.unary::<Ping, Ping>(SILENT_PATH, ping("hello")).await;
+
+        assert!(matches!(result, Err(RpcClientError::ConnectionClosed)));
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_channels_do_not_cross() {
+        let mut client = loopback_with(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .channels(vec!["a".to_string(), "b".to_string()])
+                .build(),
+        );
+        let mut conn = client.connect::<Ping, Ping>(ECHO_PATH).await.unwrap();
+        let (mut a_sender, mut a_receiver) = conn.channel::<Ping, Ping>("a").unwrap();
+        let (mut b_sender, mut b_receiver) = conn.channel::<Ping, Ping>("b").unwrap();
+
+        a_sender.send(ping("first")).await.unwrap();
+        b_sender.send(ping("second")).await.unwrap();
+        conn.send(ping("third")).await.unwrap();
+
+        assert_eq!(a_receiver.next().await.unwrap().unwrap(), ping("a:first"));
+        assert_eq!(b_receiver.next().await.unwrap().unwrap(), ping("b:second"));
+        assert_eq!(conn.next().await.unwrap().unwrap(), ping("third"));
+
+        // Each channel only received its own request
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(a_receiver.next().now_or_never().is_none());
+        assert!(b_receiver.next().now_or_never().is_none());
+        assert!(conn.next().now_or_never().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_channel_must_be_configured_and_opened_once() {
+        let mut client = loopback_with(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .channels(vec!["a".to_string()])
+                .build(),
+        );
+        let mut conn = client.connect::<Ping, Ping>(ECHO_PATH).await.unwrap();
+
+        assert!(conn.channel::<Ping, Ping>("a").is_ok());
+        assert!(matches!(
+            conn.channel::<Ping, Ping>("a"),
+            Err(RpcClientError::UnknownChannel(name)) if name == "a"
+        ));
+        assert!(matches!(
+            conn.channel::<Ping, Ping>("c"),
+            Err(RpcClientError::UnknownChannel(name)) if name == "c"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_backend_status_is_forwarded() {
+        let mut client = loopback();
+
+        let mut conn = client.connect::<Ping, Ping>(DENIED_PATH).await.unwrap();
+        conn.send(ping("hello")).await.unwrap();
+
+        let Err(RpcWireError::Status(status)) = conn.next().await.unwrap() else {
+            panic!("expected the backend status");
+        };
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert_eq!(status.message(), "not allowed");
+
+        // The status ends the stream
+        assert!(conn.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_server_streaming_receives_many_responses() {
+        let mut client = loopback();
+
+        let receiver = client
+            .server_streaming::<Ping, Ping>(REPEAT_PATH, ping("hello"))
+            .await
+            .unwrap();
+
+        let responses: Vec<_> = receiver.take(3).map(Result::unwrap).collect().await;
+        assert_eq!(responses, vec![ping("hello"); 3]);
+    }
+
+    #[tokio::test]
+    async fn test_client_streaming_receives_one_response() {
+        let mut client = loopback();
+
+        let (mut sender, response) = client
+            .client_streaming::<Ping, Ping>(LAST_PATH)
+            .await
+            .unwrap();
+        for text in ["one", "two", "three"] {
+            sender.send(ping(text)).await.unwrap();
+        }
+        drop(sender);
+
+        assert_eq!(response.await.unwrap(), ping("three"));
+    }
+
+    #[tokio::test]
+    async fn test_receiver_times_out_without_response() {
+        let mut client = loopback_with(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .response_timeout(Duration::from_millis(50))
+                .build(),
+        );
+
+        let mut receiver = client
+            .server_streaming::<Ping, Ping>(SILENT_PATH, ping("hello"))
+            .await
+            .unwrap();
+
+        let start = Instant::now();
+        let result = receiver.next().await.unwrap();
+        let elapsed = start.elapsed();
+
+        assert!(matches!(result, Err(RpcWireError::Timeout)));
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_heartbeats_are_filtered() {
+        let keepalive_interval = Duration::from_millis(10);
+        let mut client = loopback_with_router(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .keepalive_interval(keepalive_interval)
+                .build(),
+            RpcRouterConfig::builder()
+                .keepalive_interval(keepalive_interval)
+                .build(),
+        );
+
+        let mut conn = client.connect::<Ping, Ping>(SILENT_PATH).await.unwrap();
+        let connected = conn.last_seen();
+
+        // Heartbeats arrive while the server is silent, but are never yielded
+        let result = tokio::time::timeout(Duration::from_millis(100), conn.next()).await;
+        assert!(result.is_err());
+        assert!(conn.last_seen() > connected);
+        assert!(!conn.is_stale(Duration::from_millis(50)));
+    }
+
+    #[tokio::test]
+    async fn test_send_after_close_fails() {
+        let mut client = loopback();
+
+        let mut conn = client.connect::<Ping, Ping>(ECHO_PATH).await.unwrap();
+        SinkExt::close(&mut conn).await.unwrap();
+
+        let result = conn.send(ping("hello")).await;
+        assert!(matches!(result, Err(RpcSendError::ConnectionClosed)));
+    }
+}
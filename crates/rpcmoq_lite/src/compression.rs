@@ -0,0 +1,147 @@
+use bytes::Bytes;
+use std::io::{Read, Write};
+
+use crate::error::RpcCodecError;
+
+/// The compression applied to the payload of each RPC frame.
+///
+/// Every frame starts with a one-byte header tagging the codec its payload was compressed with,
+/// so that a peer configured with a different codec fails with a [`RpcCodecError`] rather than
+/// attempting to decode a compressed payload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// The payload is sent as-is.
+    #[default]
+    None,
+    /// The payload is compressed with gzip.
+    Gzip,
+    /// The payload is compressed with zstd.
+    Zstd,
+}
+
+impl Compression {
+    const TAG_NONE: u8 = 0;
+    const TAG_GZIP: u8 = 1;
+    const TAG_ZSTD: u8 = 2;
+
+    fn tag(self) -> u8 {
+        match self {
+            Compression::None => Self::TAG_NONE,
+            Compression::Gzip => Self::TAG_GZIP,
+            Compression::Zstd => Self::TAG_ZSTD,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self, RpcCodecError> {
+        match tag {
+            Self::TAG_NONE => Ok(Compression::None),
+            Self::TAG_GZIP => Ok(Compression::Gzip),
+            Self::TAG_ZSTD => Ok(Compression::Zstd),
+            other => Err(RpcCodecError::UnknownCodec(other)),
+        }
+    }
+
+    /// Compress `payload` into a frame tagged with this codec.
+    pub(crate) fn encode_frame(self, payload: &[u8]) -> std::io::Result<Bytes> {
+        let mut frame = Vec::with_capacity(payload.len() + 1);
+        frame.push(self.tag());
+
+        match self {
+            Compression::None => frame.extend_from_slice(payload),
+            Compression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(frame, Default::default());
+                encoder.write_all(payload)?;
+                frame = encoder.finish()?;
+            }
+            Compression::Zstd => zstd::stream::copy_encode(payload, &mut frame, 0)?,
+        }
+
+        Ok(frame.into())
+    }
+
+    /// Decompress the payload of `frame`, which must be tagged with this codec.
+    pub(crate) fn decode_frame(self, frame: Bytes) -> Result<Bytes, RpcCodecError> {
+        let tag = *frame.first().ok_or(RpcCodecError::MissingHeader)?;
+        let found = Self::from_tag(tag)?;
+        if found != self {
+            return Err(RpcCodecError::Mismatch {
+                expected: self,
+                found,
+            });
+        }
+
+        let payload = frame.slice(1..);
+        match self {
+            Compression::None => Ok(payload),
+            Compression::Gzip => {
+                let mut decompressed = Vec::new();
+                flate2::read::GzDecoder::new(payload.as_ref())
+                    .read_to_end(&mut decompressed)
+                    .map_err(RpcCodecError::Decompress)?;
+                Ok(decompressed.into())
+            }
+            Compression::Zstd => zstd::stream::decode_all(payload.as_ref())
+                .map(Bytes::from)
+                .map_err(RpcCodecError::Decompress),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &[u8] = b"drone telemetry drone telemetry drone telemetry";
+
+    fn round_trip(compression: Compression) {
+        let frame = compression.encode_frame(PAYLOAD).unwrap();
+        assert_eq!(compression.decode_frame(frame).unwrap(), PAYLOAD);
+    }
+
+    #[test]
+    fn test_none_round_trip() {
+        round_trip(Compression::None);
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        round_trip(Compression::Gzip);
+    }
+
+    #[test]
+    fn test_zstd_round_trip() {
+        round_trip(Compression::Zstd);
+    }
+
+    #[test]
+    fn test_none_rejects_compressed_frame() {
+        let frame = Compression::Zstd.encode_frame(PAYLOAD).unwrap();
+
+        let err = Compression::None.decode_frame(frame).unwrap_err();
+
+        assert!(matches!(
+            err,
+            RpcCodecError::Mismatch {
+                expected: Compression::None,
+                found: Compression::Zstd,
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "frame is compressed with Zstd but None was expected"
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_header() {
+        assert!(matches!(
+            Compression::None.decode_frame(Bytes::new()),
+            Err(RpcCodecError::MissingHeader)
+        ));
+        assert!(matches!(
+            Compression::None.decode_frame(Bytes::from_static(&[0xff])),
+            Err(RpcCodecError::UnknownCodec(0xff))
+        ));
+    }
+}
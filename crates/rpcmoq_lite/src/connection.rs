@@ -4,14 +4,27 @@ use futures::Stream;
 use moq_lite::{BroadcastConsumer, Error as MoqError, Track, TrackConsumer, TrackProducer};
 use prost::Message;
 use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
+use tokio_util::sync::PollSender;
 
-use crate::error::RpcSendError;
+use crate::compression::Compression;
+use crate::error::{RpcSendError, RpcWireError};
+use crate::frame::Frame;
 
 /// A stream of raw bytes from a MoQ track.
 ///
-/// This wraps a `TrackConsumer` and yields frames as `Bytes`.
+/// This wraps a `TrackConsumer` and yields the decompressed payload of each message
+/// [`Frame`] as `Bytes`. Every other frame type is skipped, including heartbeats sent by
+/// [`RpcOutbound::send_heartbeat`], but is recorded in [`last_seen`](RpcInbound::last_seen).
+/// Use [`poll_frame`](RpcInbound::poll_frame) to read frames of every type.
 pub struct RpcInbound {
     inner: Pin<Box<dyn Stream<Item = Result<Bytes, moq_lite::Error>> + Send>>,
+    compression: Compression,
+    last_seen: Instant,
 }
 
 impl RpcInbound {
@@ -45,44 +58,198 @@ impl RpcInbound {
 
         Self {
             inner: Box::pin(inner),
+            compression: Compression::None,
+            last_seen: Instant::now(),
         }
     }
+
+    /// Expect frames to be compressed with `compression`, rejecting frames using any other codec.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// The time the last frame, including heartbeats, was read from the stream.
+    ///
+    /// Frames are only read while the stream is polled. Before the first frame this is the time
+    /// the stream was created.
+    pub fn last_seen(&self) -> Instant {
+        self.last_seen
+    }
+
+    /// Whether no frame has been read from the stream for longer than `max_idle`.
+    pub fn is_stale(&self, max_idle: Duration) -> bool {
+        self.last_seen.elapsed() > max_idle
+    }
+
+    /// Poll for the next frame of any type.
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame, RpcWireError>>> {
+        let frame = match ready!(self.inner.as_mut().poll_next(cx)) {
+            Some(Ok(frame)) => frame,
+            Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+            None => return Poll::Ready(None),
+        };
+        self.last_seen = Instant::now();
+
+        Poll::Ready(Some(
+            Frame::decode(frame, self.compression).map_err(Into::into),
+        ))
+    }
 }
 
 impl Stream for RpcInbound {
-    type Item = Result<Bytes, moq_lite::Error>;
+    type Item = Result<Bytes, RpcWireError>;
 
-    fn poll_next(
-        mut self: Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<Option<Self::Item>> {
-        self.inner.as_mut().poll_next(cx)
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            return match ready!(self.poll_frame(cx)) {
+                Some(Ok(Frame::Message(payload))) => Poll::Ready(Some(Ok(payload))),
+                // Not yet surfaced to readers of messages
+                Some(Ok(Frame::Trailer(_) | Frame::Error(_) | Frame::Heartbeat)) => continue,
+                Some(Err(err)) => Poll::Ready(Some(Err(err))),
+                None => Poll::Ready(None),
+            };
+        }
     }
 }
 
 /// A sink for sending responses back to a MoQ track.
+///
+/// By default frames are written directly to the track. A [buffered](RpcOutbound::buffered)
+/// outbound instead queues frames for a background writer, allowing senders to be slowed down
+/// via [`poll_ready`](RpcOutbound::poll_ready) when the buffer is full.
 #[derive(Clone)]
 pub struct RpcOutbound {
     track: TrackProducer,
+    buffer: Option<PollSender<Bytes>>,
+    compression: Compression,
 }
 
 impl RpcOutbound {
     /// Create a new outbound sink from a track producer.
     pub fn new(track: TrackProducer) -> Self {
-        Self { track }
+        Self {
+            track,
+            buffer: None,
+            compression: Compression::None,
+        }
+    }
+
+    /// Create a new outbound sink which buffers up to `high_water` frames before they are written
+    /// to the track by a background task.
+    ///
+    /// Must be called from within a tokio runtime. Frames still buffered when the track is
+    /// [aborted](RpcOutbound::abort_app) are discarded.
+    ///
+    /// # Panics
+    /// Panics if `high_water` is zero.
+    pub fn buffered(track: TrackProducer, high_water: usize) -> Self {
+        let (tx, mut rx) = mpsc::channel::<Bytes>(high_water);
+
+        let mut writer = track.clone();
+        tokio::spawn(async move {
+            while let Some(frame) = rx.recv().await {
+                writer.write_frame(frame);
+            }
+        });
+
+        Self {
+            track,
+            buffer: Some(PollSender::new(tx)),
+            compression: Compression::None,
+        }
+    }
+
+    /// Compress the payload of every frame sent with `compression`.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Poll for space to send a frame, returning `Pending` while the buffer is full.
+    ///
+    /// An unbuffered outbound is always ready. For a buffered outbound this must return
+    /// `Ready(Ok(()))` before each [`send`](RpcOutbound::send).
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), RpcSendError>> {
+        match &mut self.buffer {
+            Some(buffer) => buffer
+                .poll_reserve(cx)
+                .map_err(|_| RpcSendError::BufferClosed),
+            None => Poll::Ready(Ok(())),
+        }
     }
 
     /// Send a protobuf message.
+    ///
+    /// See [`send_raw`](RpcOutbound::send_raw) for the requirements of a buffered outbound.
     pub fn send<M: Message>(&mut self, msg: &M) -> Result<(), RpcSendError> {
         let mut buf = Vec::with_capacity(msg.encoded_len());
         msg.encode(&mut buf)?;
-        self.send_raw(buf);
-        Ok(())
+        self.send_raw(buf)
+    }
+
+    /// Send raw bytes as a message frame, compressed according to the configured [`Compression`].
+    ///
+    /// # Panics
+    /// Panics for a buffered outbound if space was not first reserved with
+    /// [`poll_ready`](RpcOutbound::poll_ready).
+    pub fn send_raw(&mut self, bytes: impl AsRef<[u8]>) -> Result<(), RpcSendError> {
+        let frame = Frame::encode_message(bytes.as_ref(), self.compression)
+            .map_err(RpcSendError::Compress)?;
+        self.write(frame)
+    }
+
+    /// Send a frame of any type, with any payload compressed according to the configured
+    /// [`Compression`].
+    ///
+    /// See [`send_raw`](RpcOutbound::send_raw) for the requirements of a buffered outbound.
+    pub fn send_frame(&mut self, frame: &Frame) -> Result<(), RpcSendError> {
+        let frame = frame
+            .to_bytes(self.compression)
+            .map_err(RpcSendError::Compress)?;
+        self.write(frame)
     }
 
-    /// Send raw bytes.
-    pub fn send_raw(&mut self, bytes: impl Into<Bytes>) {
-        self.track.write_frame(bytes.into());
+    fn write(&mut self, frame: Bytes) -> Result<(), RpcSendError> {
+        match &mut self.buffer {
+            Some(buffer) => buffer
+                .send_item(frame)
+                .map_err(|_| RpcSendError::BufferClosed),
+            None => {
+                self.track.write_frame(frame);
+                Ok(())
+            }
+        }
+    }
+
+    /// Send a heartbeat frame, which the peer's [`RpcInbound`] records but does not yield.
+    ///
+    /// Heartbeats are written directly to the track, bypassing any send buffer.
+    pub fn send_heartbeat(&mut self) {
+        let frame = Frame::Heartbeat
+            .to_bytes(self.compression)
+            .expect("heartbeats carry no payload to compress");
+        self.track.write_frame(frame);
+    }
+
+    /// Send a heartbeat every `interval` from a background task, until the returned [`Keepalive`]
+    /// is dropped.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub(crate) fn spawn_keepalive(&self, interval: Duration) -> Keepalive {
+        let mut outbound = self.clone();
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        Keepalive(tokio::spawn(async move {
+            loop {
+                ticker.tick().await;
+                outbound.send_heartbeat();
+            }
+        }))
     }
 
     /// Abort the underlying track with an application error code.
@@ -90,3 +257,141 @@ impl RpcOutbound {
         self.track.clone().abort(MoqError::App(code));
     }
 }
+
+/// A background task sending heartbeats on an [`RpcOutbound`], stopped when dropped.
+///
+/// The task holds a handle to the track, so it must be stopped for the track to close.
+pub(crate) struct Keepalive(JoinHandle<()>);
+
+impl Drop for Keepalive {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{FutureExt, StreamExt};
+    use moq_lite::Track;
+
+    use super::*;
+
+    fn poll_ready(outbound: &mut RpcOutbound) -> Option<Result<(), RpcSendError>> {
+        std::future::poll_fn(|cx| outbound.poll_ready(cx)).now_or_never()
+    }
+
+    #[tokio::test]
+    async fn test_buffered_send_waits_for_space() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::buffered(track.producer, 2);
+
+        // The writer task can't run without yielding, so the buffer fills up
+        for frame in ["one", "two"] {
+            assert!(matches!(poll_ready(&mut outbound), Some(Ok(()))));
+            outbound.send_raw(frame).unwrap();
+        }
+        assert!(poll_ready(&mut outbound).is_none());
+
+        // Waiting lets the writer drain the buffer to the track
+        std::future::poll_fn(|cx| outbound.poll_ready(cx))
+            .await
+            .unwrap();
+        outbound.send_raw("three").unwrap();
+
+        // Only the latest frame is guaranteed to be observed
+        let mut inbound = RpcInbound::from_track(track.consumer);
+        loop {
+            let frame = inbound.next().await.unwrap().unwrap();
+            if frame == "three" {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_unbuffered_is_always_ready() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::new(track.producer);
+
+        for _ in 0..8 {
+            assert!(matches!(poll_ready(&mut outbound), Some(Ok(()))));
+            outbound.send_raw("frame").unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_sends_heartbeats() {
+        let track = Track::new("primary").produce();
+        let outbound = RpcOutbound::new(track.producer);
+        let mut consumer = track.consumer;
+
+        let keepalive = outbound.spawn_keepalive(Duration::from_millis(10));
+        for _ in 0..3 {
+            let mut group = consumer.next_group().await.unwrap().unwrap();
+            let frame = group.read_frame().await.unwrap().unwrap();
+            assert_eq!(
+                Frame::decode(frame, Compression::None).unwrap(),
+                Frame::Heartbeat
+            );
+        }
+
+        // Stopping the keepalive releases the track, which closes once the outbound is dropped
+        drop(keepalive);
+        drop(outbound);
+        assert!(consumer.next_group().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_are_filtered() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::new(track.producer);
+        let mut inbound = RpcInbound::from_track(track.consumer);
+        let created = inbound.last_seen();
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(inbound.is_stale(Duration::from_millis(10)));
+
+        outbound.send_heartbeat();
+        assert!(inbound.next().now_or_never().is_none());
+        assert!(inbound.last_seen() > created);
+        assert!(!inbound.is_stale(Duration::from_millis(10)));
+
+        outbound.send_raw("payload").unwrap();
+        assert_eq!(inbound.next().await.unwrap().unwrap(), "payload");
+    }
+
+    #[tokio::test]
+    async fn test_poll_frame_reads_every_frame_type() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::new(track.producer).with_compression(Compression::Gzip);
+        let mut inbound =
+            RpcInbound::from_track(track.consumer).with_compression(Compression::Gzip);
+
+        let frames = [
+            Frame::Message(Bytes::from_static(b"message")),
+            Frame::Trailer(Bytes::from_static(b"trailer")),
+            Frame::Error(Bytes::from_static(b"error")),
+            Frame::Heartbeat,
+        ];
+        for frame in frames {
+            outbound.send_frame(&frame).unwrap();
+            let received = std::future::poll_fn(|cx| inbound.poll_frame(cx)).await;
+            assert_eq!(received.unwrap().unwrap(), frame);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_skips_non_message_frames() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::new(track.producer);
+        let mut inbound = RpcInbound::from_track(track.consumer);
+
+        outbound
+            .send_frame(&Frame::Trailer(Bytes::from_static(b"trailer")))
+            .unwrap();
+        assert!(inbound.next().now_or_never().is_none());
+
+        outbound.send_raw("payload").unwrap();
+        assert_eq!(inbound.next().await.unwrap().unwrap(), "payload");
+    }
+}
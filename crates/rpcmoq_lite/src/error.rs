@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::compression::Compression;
+
 /// Errors that can occur while parsing RPC or gRPC paths.
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -27,6 +29,48 @@ pub enum RpcClientError {
     /// The RPC connection was closed.
     #[error("RPC connection closed")]
     ConnectionClosed,
+
+    /// The channel is not configured for the connection, or has already been opened.
+    #[error("channel '{0}' is not configured or already open")]
+    UnknownChannel(String),
+
+    /// The channel is configured more than once, or is named after the primary track.
+    #[error("channel '{0}' is configured twice or named after the primary track")]
+    InvalidChannel(String),
+
+    /// Failed to send a request over the connection.
+    #[error(transparent)]
+    Send(#[from] RpcSendError),
+
+    /// The server responded with an error or the response could not be decoded.
+    #[error(transparent)]
+    Wire(#[from] RpcWireError),
+}
+
+/// Errors that can occur while reading an RPC client configuration from the environment.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RpcConfigError {
+    /// A required environment variable is not set.
+    #[error("environment variable {0} is not set")]
+    MissingVar(&'static str),
+
+    /// An environment variable is set to a value that cannot be parsed.
+    #[error("environment variable {name} has invalid value '{value}'")]
+    InvalidVar { name: &'static str, value: String },
+}
+
+/// Errors that can occur while constructing a [`RateLimit`](crate::RateLimit).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RpcRateLimitError {
+    /// A burst of zero would decline every announcement.
+    #[error("rate limit burst must be non-zero")]
+    ZeroBurst,
+
+    /// A window of zero would refill tokens instantly.
+    #[error("rate limit window must be non-zero")]
+    ZeroWindow,
 }
 
 /// Errors that can occur while running the RPC server router.
@@ -48,10 +92,42 @@ pub enum RpcServerError {
     #[error("no handler registered for '{0}'")]
     NoHandler(String),
 
+    /// The client announced more often than the configured rate limit allows.
+    #[error("client '{client_id}' is rate limited, declining '{grpc_path}'")]
+    RateLimited {
+        client_id: String,
+        grpc_path: String,
+    },
+
+    /// The router is already serving its maximum number of concurrent sessions.
+    #[error("connection limit of {limit} reached, declining '{client_id}' on '{grpc_path}'")]
+    ConnectionLimit {
+        client_id: String,
+        grpc_path: String,
+        limit: usize,
+    },
+
+    /// A channel was registered under the name of the primary track, which it would collide with.
+    #[error("channel '{channel}' of '{grpc_path}' is named after the primary track")]
+    ReservedChannel { grpc_path: String, channel: String },
+
+    /// A handler is already registered for the channel of the gRPC path.
+    #[error("channel '{channel}' already registered for '{grpc_path}'")]
+    DuplicateChannel { grpc_path: String, channel: String },
+
+    /// A backend pool was registered without any backend of non-zero weight.
+    #[error("no backends with non-zero weight registered for '{0}'")]
+    EmptyPool(String),
+
     /// Failed to create a broadcast for the response channel.
     #[error("failed to create broadcast: {0}")]
     BroadcastCreate(String),
 
+    /// A response broadcast is already live at the path, typically because the same client
+    /// announced the same RPC twice.
+    #[error("response broadcast already live at '{path}'")]
+    BroadcastConflict { path: String },
+
     /// Authorization failed for the requested operation.
     #[error("unauthorized: {0}")]
     Unauthorized(String),
@@ -64,6 +140,50 @@ pub enum RpcSendError {
     /// Failed to encode a protobuf message.
     #[error("protobuf encode error")]
     Encode(#[from] prost::EncodeError),
+
+    /// The outbound send buffer has closed.
+    #[error("outbound send buffer unavailable")]
+    BufferClosed,
+
+    /// The connection was closed, so no more messages can be sent.
+    #[error("RPC connection closed")]
+    ConnectionClosed,
+
+    /// Failed to compress a frame.
+    #[error("frame compression error")]
+    Compress(#[source] std::io::Error),
+}
+
+/// Errors that can occur while reading the headers and payload of a frame.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RpcCodecError {
+    /// The frame is empty, so it has no frame type header.
+    #[error("frame is missing its type header")]
+    MissingFrameType,
+
+    /// The frame type header tags a frame type that is not known.
+    #[error("frame has an unknown type ({0})")]
+    UnknownFrameType(u8),
+
+    /// The frame is empty, so it has no compression header.
+    #[error("frame is missing its compression header")]
+    MissingHeader,
+
+    /// The compression header tags a codec that is not known.
+    #[error("frame is compressed with an unknown codec ({0})")]
+    UnknownCodec(u8),
+
+    /// The frame was compressed with a different codec than the one configured.
+    #[error("frame is compressed with {found:?} but {expected:?} was expected")]
+    Mismatch {
+        expected: Compression,
+        found: Compression,
+    },
+
+    /// Failed to decompress the frame payload.
+    #[error("frame decompression error")]
+    Decompress(#[source] std::io::Error),
 }
 
 /// Errors that can occur on the wire after a connection is established.
@@ -82,14 +202,26 @@ pub enum RpcWireError {
     #[error("protobuf decode error")]
     Decode,
 
-    /// The gRPC backend returned an error.
+    /// Failed to decompress a frame.
+    #[error(transparent)]
+    Codec(#[from] RpcCodecError),
+
+    /// The gRPC backend returned an error, without reporting its status.
     #[error("gRPC error")]
     Grpc,
 
+    /// The gRPC backend returned an error status, forwarded by the server.
+    #[error("gRPC error: {0}")]
+    Status(tonic::Status),
+
     /// Internal server error while handling the request.
     #[error("internal error")]
     Internal,
 
+    /// No response was received within the configured response timeout.
+    #[error("timeout waiting for response")]
+    Timeout,
+
     /// An error from the underlying MoQ transport.
     #[error("MoQ transport error")]
     Transport(#[source] moq_lite::Error),
@@ -105,6 +237,7 @@ impl RpcWireError {
     pub const CODE_DECODE: u32 = 3;
     pub const CODE_GRPC: u32 = 4;
     pub const CODE_INTERNAL: u32 = 5;
+    pub const CODE_TIMEOUT: u32 = 6;
 
     pub fn transport_with(err: moq_lite::Error) -> Self {
         match err {
@@ -118,8 +251,11 @@ impl RpcWireError {
             RpcWireError::NoHandler => Self::CODE_NO_HANDLER,
             RpcWireError::SessionAlreadyActive => Self::CODE_SESSION_ALREADY_ACTIVE,
             RpcWireError::Decode => Self::CODE_DECODE,
-            RpcWireError::Grpc => Self::CODE_GRPC,
+            // Reported to peers as a decode failure since no payload could be decoded
+            RpcWireError::Codec(_) => Self::CODE_DECODE,
+            RpcWireError::Grpc | RpcWireError::Status(_) => Self::CODE_GRPC,
             RpcWireError::Internal => Self::CODE_INTERNAL,
+            RpcWireError::Timeout => Self::CODE_TIMEOUT,
             RpcWireError::Transport(e) => e.to_code(),
             RpcWireError::Unknown(code) => *code,
         }
@@ -132,6 +268,7 @@ impl RpcWireError {
             Self::CODE_DECODE => RpcWireError::Decode,
             Self::CODE_GRPC => RpcWireError::Grpc,
             Self::CODE_INTERNAL => RpcWireError::Internal,
+            Self::CODE_TIMEOUT => RpcWireError::Timeout,
             // TODO: Go implement from_code in the moq-lite codebase
             other => RpcWireError::Unknown(other),
         }
@@ -143,3 +280,31 @@ impl From<moq_lite::Error> for RpcWireError {
         RpcWireError::transport_with(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_round_trip() {
+        let errors = [
+            RpcWireError::NoHandler,
+            RpcWireError::SessionAlreadyActive,
+            RpcWireError::Decode,
+            RpcWireError::Grpc,
+            RpcWireError::Internal,
+            RpcWireError::Timeout,
+            RpcWireError::Unknown(100),
+        ];
+
+        for error in errors {
+            let decoded = RpcWireError::from_code(error.to_code());
+            assert_eq!(
+                std::mem::discriminant(&decoded),
+                std::mem::discriminant(&error),
+                "{error:?} decoded as {decoded:?}"
+            );
+            assert_eq!(decoded.to_code(), error.to_code());
+        }
+    }
+}
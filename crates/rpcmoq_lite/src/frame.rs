@@ -0,0 +1,175 @@
+use bytes::Bytes;
+use prost::Message;
+use tonic::{Code, Status};
+
+use crate::compression::Compression;
+use crate::error::RpcCodecError;
+
+/// A frame sent over an RPC track.
+///
+/// Every frame starts with a one-byte header tagging its type. Frames carrying a payload follow
+/// it with the payload, compressed as described by [`Compression`]. Heartbeats carry nothing else.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Frame {
+    /// An encoded protobuf message.
+    Message(Bytes),
+    /// Metadata sent once a stream of messages has ended, such as gRPC trailers.
+    Trailer(Bytes),
+    /// An error ending the stream, such as a gRPC status.
+    Error(Bytes),
+    /// A keepalive, recorded by the peer but carrying no data.
+    Heartbeat,
+}
+
+impl Frame {
+    const TAG_MESSAGE: u8 = 0;
+    const TAG_TRAILER: u8 = 1;
+    const TAG_ERROR: u8 = 2;
+    const TAG_HEARTBEAT: u8 = 3;
+
+    /// Encode a frame of the type tagged `tag`, compressing `payload` with `compression`.
+    pub(crate) fn encode(
+        tag: u8,
+        payload: &[u8],
+        compression: Compression,
+    ) -> std::io::Result<Bytes> {
+        let body = compression.encode_frame(payload)?;
+
+        let mut frame = Vec::with_capacity(body.len() + 1);
+        frame.push(tag);
+        frame.extend_from_slice(&body);
+        Ok(frame.into())
+    }
+
+    /// Encode a message frame, compressing `payload` with `compression`.
+    pub(crate) fn encode_message(
+        payload: &[u8],
+        compression: Compression,
+    ) -> std::io::Result<Bytes> {
+        Self::encode(Self::TAG_MESSAGE, payload, compression)
+    }
+
+    /// Encode this frame, compressing any payload with `compression`.
+    pub(crate) fn to_bytes(&self, compression: Compression) -> std::io::Result<Bytes> {
+        match self {
+            Frame::Message(payload) => Self::encode(Self::TAG_MESSAGE, payload, compression),
+            Frame::Trailer(payload) => Self::encode(Self::TAG_TRAILER, payload, compression),
+            Frame::Error(payload) => Self::encode(Self::TAG_ERROR, payload, compression),
+            Frame::Heartbeat => Ok(Bytes::from_static(&[Self::TAG_HEARTBEAT])),
+        }
+    }
+
+    /// Build an error frame carrying `status`.
+    pub(crate) fn status(status: &Status) -> Self {
+        let payload = StatusPayload {
+            code: status.code() as i32,
+            message: status.message().to_string(),
+            details: status.details().to_vec(),
+        };
+        Frame::Error(payload.encode_to_vec().into())
+    }
+
+    /// Read the status carried by the payload of an error frame.
+    pub(crate) fn decode_status(payload: &[u8]) -> Result<Status, prost::DecodeError> {
+        let payload = StatusPayload::decode(payload)?;
+        Ok(Status::with_details(
+            Code::from_i32(payload.code),
+            payload.message,
+            payload.details.into(),
+        ))
+    }
+
+    /// Decode a frame, whose payload must be compressed with `compression`.
+    pub(crate) fn decode(frame: Bytes, compression: Compression) -> Result<Self, RpcCodecError> {
+        let tag = *frame.first().ok_or(RpcCodecError::MissingFrameType)?;
+        let body = frame.slice(1..);
+
+        match tag {
+            Self::TAG_MESSAGE => Ok(Frame::Message(compression.decode_frame(body)?)),
+            Self::TAG_TRAILER => Ok(Frame::Trailer(compression.decode_frame(body)?)),
+            Self::TAG_ERROR => Ok(Frame::Error(compression.decode_frame(body)?)),
+            Self::TAG_HEARTBEAT => Ok(Frame::Heartbeat),
+            other => Err(RpcCodecError::UnknownFrameType(other)),
+        }
+    }
+}
+
+/// The payload of an error frame, laid out like `google.rpc.Status`.
+#[derive(Clone, PartialEq, prost::Message)]
+struct StatusPayload {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(bytes = "vec", tag = "3")]
+    details: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &[u8] = b"drone telemetry";
+
+    fn round_trip(frame: Frame) {
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            let bytes = frame.to_bytes(compression).unwrap();
+            assert_eq!(Frame::decode(bytes, compression).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn test_message_round_trip() {
+        round_trip(Frame::Message(Bytes::from_static(PAYLOAD)));
+    }
+
+    #[test]
+    fn test_trailer_round_trip() {
+        round_trip(Frame::Trailer(Bytes::from_static(PAYLOAD)));
+    }
+
+    #[test]
+    fn test_error_round_trip() {
+        round_trip(Frame::Error(Bytes::from_static(PAYLOAD)));
+    }
+
+    #[test]
+    fn test_heartbeat_round_trip() {
+        round_trip(Frame::Heartbeat);
+    }
+
+    #[test]
+    fn test_encode_message_matches_message_frame() {
+        let frame = Frame::Message(Bytes::from_static(PAYLOAD));
+        assert_eq!(
+            Frame::encode_message(PAYLOAD, Compression::None).unwrap(),
+            frame.to_bytes(Compression::None).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        let status = Status::permission_denied("not allowed");
+
+        let Frame::Error(payload) = Frame::status(&status) else {
+            panic!("status should be sent as an error frame");
+        };
+        let decoded = Frame::decode_status(&payload).unwrap();
+
+        assert_eq!(decoded.code(), Code::PermissionDenied);
+        assert_eq!(decoded.message(), "not allowed");
+    }
+
+    #[test]
+    fn test_unknown_frame_type_is_rejected() {
+        let result = Frame::decode(Bytes::from_static(&[42, 0, 1, 2]), Compression::None);
+        assert!(matches!(result, Err(RpcCodecError::UnknownFrameType(42))));
+    }
+
+    #[test]
+    fn test_empty_frame_is_rejected() {
+        let result = Frame::decode(Bytes::new(), Compression::None);
+        assert!(matches!(result, Err(RpcCodecError::MissingFrameType)));
+    }
+}
@@ -75,19 +75,32 @@
 //! - Server responds: `drone-123/drone.EchoService/Echo`
 
 // Shared modules at root level
+mod compression;
 mod connection;
 mod error;
+mod frame;
 mod path;
 
 // Submodules for client and server
 pub mod client;
 pub mod server;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 // Re-export shared types
+pub use compression::Compression;
 pub use connection::{RpcInbound, RpcOutbound};
-pub use error::{RpcClientError, RpcPathError, RpcSendError, RpcServerError, RpcWireError};
+pub use error::{
+    RpcClientError, RpcCodecError, RpcConfigError, RpcPathError, RpcRateLimitError, RpcSendError,
+    RpcServerError, RpcWireError,
+};
+pub use frame::Frame;
 pub use path::{GrpcPath, RpcRequestPath};
 
 // Convenience re-exports for common use
 pub use client::{RpcClient, RpcClientConfig, RpcConnection, RpcReceiver, RpcSender};
-pub use server::{DecodedInbound, RpcRouter, RpcRouterConfig, SessionGuard, SessionKey, SessionMap};
+pub use server::{
+    DecodedInbound, HEALTH_PATH, HealthCheckRequest, HealthCheckResponse, RateLimit, RpcMetrics,
+    RpcMetricsSnapshot, RpcRouter, RpcRouterConfig, RpcRouterHandle, SessionGuard, SessionKey,
+    SessionMap,
+};
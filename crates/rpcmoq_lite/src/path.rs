@@ -14,43 +14,35 @@ pub struct RpcRequestPath {
 impl RpcRequestPath {
     /// Parse a path string into an RpcRequestPath.
     ///
-    /// Expected format: `{client_id}/{package}.{service}/{method}`
-    /// The client_id can contain slashes, so we split from the right.
+    /// Expected format: `[{prefix}/]{client_id}/{package}.{service}/{method}`
+    /// The client_id can contain slashes, so we split from the right. Any prefix left on the path
+    /// becomes part of the client_id.
+    ///
+    /// A single leading slash is allowed, but every segment must be non-empty, so paths with
+    /// repeated or trailing slashes are rejected.
     pub fn parse(path: &str) -> Result<Self, RpcPathError> {
-        let path = path.strip_prefix('/').unwrap_or(path);
-
-        // Split on '/' and work backwards to find the service/method boundary
-        let parts: Vec<&str> = path.split('/').collect();
-        if parts.len() < 2 {
-            return Err(RpcPathError::Invalid(format!(
-                "path must have at least client_id and grpc_path: '{path}'"
-            )));
-        }
+        let trimmed = path.strip_prefix('/').unwrap_or(path);
 
-        // The method is the last part
-        let method = parts[parts.len() - 1];
+        // Split off the method and service from the right, leaving the client_id
+        let mut parts = trimmed.rsplitn(3, '/');
+        let method = parts.next().unwrap_or_default();
+        let service_segment = parts.next().ok_or_else(|| {
+            RpcPathError::Invalid(format!("missing package.service segment in '{path}'"))
+        })?;
+        let client_id = parts.next().ok_or_else(|| {
+            RpcPathError::Invalid(format!("missing client_id segment in '{path}'"))
+        })?;
 
-        // The service (with package) is the second-to-last part, and must contain a '.'
-        let service_part = parts[parts.len() - 2];
-        if !service_part.contains('.') {
+        if client_id.split('/').any(str::is_empty) {
             return Err(RpcPathError::Invalid(format!(
-                "service part must contain package.service: '{service_part}'"
+                "empty client_id segment in '{path}'"
             )));
         }
 
-        // Everything before the service part is the client_id
-        let client_id = if parts.len() > 2 {
-            parts[..parts.len() - 2].join("/")
-        } else {
-            return Err(RpcPathError::Invalid(format!(
-                "path must include client_id before grpc path: '{path}'"
-            )));
-        };
-
-        let grpc_path = GrpcPath::parse(&format!("{service_part}/{method}"))?;
+        let grpc_path = GrpcPath::from_segments(service_segment, method, path)?;
 
         Ok(RpcRequestPath {
-            client_id,
+            client_id: client_id.to_owned(),
             grpc_path,
         })
     }
@@ -70,25 +62,81 @@ pub struct GrpcPath {
 }
 
 impl GrpcPath {
+    /// Create a gRPC path from its parts, validating them as [`parse`](Self::parse) would.
+    ///
+    /// The `service` must not contain a `.`, which would make the path ambiguous.
+    pub fn new(
+        package: impl Into<String>,
+        service: impl Into<String>,
+        method: impl Into<String>,
+    ) -> Result<Self, RpcPathError> {
+        let (package, service, method) = (package.into(), service.into(), method.into());
+
+        if service.contains('.') {
+            return Err(RpcPathError::Invalid(format!(
+                "service '{service}' must not contain '.'"
+            )));
+        }
+
+        let service_segment = format!("{package}.{service}");
+        let path = format!("{service_segment}/{method}");
+        Self::from_segments(&service_segment, &method, &path)
+    }
+
     /// Parse a gRPC path string.
     ///
     /// Expected format: `{package}.{service}/{method}`
     pub fn parse(path: &str) -> Result<Self, RpcPathError> {
-        let path = path.strip_prefix('/').unwrap_or(path);
+        let trimmed = path.strip_prefix('/').unwrap_or(path);
+
+        let (service_segment, method) = trimmed
+            .split_once('/')
+            .ok_or_else(|| RpcPathError::Invalid(format!("missing method segment in '{path}'")))?;
 
-        let (service_path, method) = path
-            .rsplit_once('/')
-            .ok_or_else(|| RpcPathError::Invalid(format!("gRPC path must contain '/': '{path}'")))?;
+        Self::from_segments(service_segment, method, path)
+    }
 
-        let (package, service) = service_path.rsplit_once('.').ok_or_else(|| {
+    /// Build from the `{package}.{service}` and `{method}` segments of `path`, validating each.
+    fn from_segments(
+        service_segment: &str,
+        method: &str,
+        path: &str,
+    ) -> Result<Self, RpcPathError> {
+        if method.is_empty() {
+            return Err(RpcPathError::Invalid(format!(
+                "empty method segment in '{path}'"
+            )));
+        }
+        if service_segment.is_empty() {
+            return Err(RpcPathError::Invalid(format!(
+                "empty package.service segment in '{path}'"
+            )));
+        }
+        if service_segment.contains('/') {
+            return Err(RpcPathError::Invalid(format!(
+                "package.service segment '{service_segment}' must not contain '/' in '{path}'"
+            )));
+        }
+
+        let (package, service) = service_segment.rsplit_once('.').ok_or_else(|| {
             RpcPathError::Invalid(format!(
-                "service path must contain package.service: '{service_path}'"
+                "package.service segment '{service_segment}' is missing a '.' in '{path}'"
             ))
         })?;
 
-        if package.is_empty() || service.is_empty() || method.is_empty() {
+        if package.is_empty() {
+            return Err(RpcPathError::Invalid(format!(
+                "empty package in package.service segment '{service_segment}' of '{path}'"
+            )));
+        }
+        if service.is_empty() {
             return Err(RpcPathError::Invalid(format!(
-                "package, service, and method must all be non-empty: '{path}'"
+                "empty service in package.service segment '{service_segment}' of '{path}'"
+            )));
+        }
+        if method.contains('/') {
+            return Err(RpcPathError::Invalid(format!(
+                "method segment '{method}' must not contain '/' in '{path}'"
             )));
         }
 
@@ -99,6 +147,21 @@ impl GrpcPath {
         })
     }
 
+    /// Returns the package, e.g. `drone` or `com.example.drone`.
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// Returns the service name without its package, e.g. `EchoService`.
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    /// Returns the method name, e.g. `Echo`.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
     /// Returns the full service name: `{package}.{service}`
     pub fn full_service(&self) -> String {
         format!("{}.{}", self.package, self.service)
@@ -117,9 +180,9 @@ mod tests {
     #[test]
     fn test_grpc_path_parse() {
         let path = GrpcPath::parse("drone.EchoService/Echo").unwrap();
-        assert_eq!(path.package, "drone");
-        assert_eq!(path.service, "EchoService");
-        assert_eq!(path.method, "Echo");
+        assert_eq!(path.package(), "drone");
+        assert_eq!(path.service(), "EchoService");
+        assert_eq!(path.method(), "Echo");
         assert_eq!(path.full_service(), "drone.EchoService");
         assert_eq!(path.full_path(), "drone.EchoService/Echo");
     }
@@ -127,26 +190,26 @@ mod tests {
     #[test]
     fn test_grpc_path_with_leading_slash() {
         let path = GrpcPath::parse("/drone.EchoService/Echo").unwrap();
-        assert_eq!(path.package, "drone");
-        assert_eq!(path.service, "EchoService");
-        assert_eq!(path.method, "Echo");
+        assert_eq!(path.package(), "drone");
+        assert_eq!(path.service(), "EchoService");
+        assert_eq!(path.method(), "Echo");
     }
 
     #[test]
     fn test_grpc_path_nested_package() {
         let path = GrpcPath::parse("com.example.drone.EchoService/Echo").unwrap();
-        assert_eq!(path.package, "com.example.drone");
-        assert_eq!(path.service, "EchoService");
-        assert_eq!(path.method, "Echo");
+        assert_eq!(path.package(), "com.example.drone");
+        assert_eq!(path.service(), "EchoService");
+        assert_eq!(path.method(), "Echo");
     }
 
     #[test]
     fn test_rpc_request_path_parse() {
         let path = RpcRequestPath::parse("drone-123/drone.EchoService/Echo").unwrap();
         assert_eq!(path.client_id, "drone-123");
-        assert_eq!(path.grpc_path.package, "drone");
-        assert_eq!(path.grpc_path.service, "EchoService");
-        assert_eq!(path.grpc_path.method, "Echo");
+        assert_eq!(path.grpc_path.package(), "drone");
+        assert_eq!(path.grpc_path.service(), "EchoService");
+        assert_eq!(path.grpc_path.method(), "Echo");
     }
 
     #[test]
@@ -173,4 +236,143 @@ mod tests {
         let result = GrpcPath::parse("EchoService/Echo");
         assert!(result.is_err());
     }
+
+    fn invalid_message(result: Result<RpcRequestPath, RpcPathError>) -> String {
+        match result {
+            Err(RpcPathError::Invalid(message)) => message,
+            Ok(path) => panic!("expected an invalid path, parsed {path:?}"),
+        }
+    }
+
+    #[test]
+    fn test_rpc_request_path_prefixed() {
+        let path = RpcRequestPath::parse("drone/drone-123/drone.EchoService/Echo").unwrap();
+        assert_eq!(path.client_id, "drone/drone-123");
+        assert_eq!(path.grpc_path.full_path(), "drone.EchoService/Echo");
+    }
+
+    #[test]
+    fn test_rpc_request_path_leading_slash() {
+        let path = RpcRequestPath::parse("/drone-123/drone.EchoService/Echo").unwrap();
+        assert_eq!(path.client_id, "drone-123");
+        assert_eq!(path.grpc_path.full_path(), "drone.EchoService/Echo");
+    }
+
+    #[test]
+    fn test_rpc_request_path_missing_segments() {
+        let message = invalid_message(RpcRequestPath::parse("Echo"));
+        assert_eq!(message, "missing package.service segment in 'Echo'");
+
+        let message = invalid_message(RpcRequestPath::parse("drone.EchoService/Echo"));
+        assert_eq!(
+            message,
+            "missing client_id segment in 'drone.EchoService/Echo'"
+        );
+    }
+
+    #[test]
+    fn test_rpc_request_path_trailing_slash() {
+        let message = invalid_message(RpcRequestPath::parse("drone-123/drone.EchoService/Echo/"));
+        assert_eq!(
+            message,
+            "empty method segment in 'drone-123/drone.EchoService/Echo/'"
+        );
+
+        let message = invalid_message(RpcRequestPath::parse("drone-123/drone.EchoService/"));
+        assert_eq!(
+            message,
+            "empty method segment in 'drone-123/drone.EchoService/'"
+        );
+    }
+
+    #[test]
+    fn test_rpc_request_path_empty_client_id() {
+        let message = invalid_message(RpcRequestPath::parse("/drone.EchoService/Echo"));
+        assert_eq!(
+            message,
+            "missing client_id segment in '/drone.EchoService/Echo'"
+        );
+
+        let message = invalid_message(RpcRequestPath::parse("//drone.EchoService/Echo"));
+        assert_eq!(
+            message,
+            "empty client_id segment in '//drone.EchoService/Echo'"
+        );
+
+        let message = invalid_message(RpcRequestPath::parse("fleet//drone.EchoService/Echo"));
+        assert_eq!(
+            message,
+            "empty client_id segment in 'fleet//drone.EchoService/Echo'"
+        );
+    }
+
+    #[test]
+    fn test_rpc_request_path_empty_service_segment() {
+        let message = invalid_message(RpcRequestPath::parse("drone-123//Echo"));
+        assert_eq!(
+            message,
+            "empty package.service segment in 'drone-123//Echo'"
+        );
+    }
+
+    #[test]
+    fn test_rpc_request_path_missing_dot() {
+        let message = invalid_message(RpcRequestPath::parse("drone-123/EchoService/Echo"));
+        assert_eq!(
+            message,
+            "package.service segment 'EchoService' is missing a '.' in 'drone-123/EchoService/Echo'"
+        );
+    }
+
+    #[test]
+    fn test_rpc_request_path_empty_package_or_service() {
+        let message = invalid_message(RpcRequestPath::parse("drone-123/.EchoService/Echo"));
+        assert_eq!(
+            message,
+            "empty package in package.service segment '.EchoService' of 'drone-123/.EchoService/Echo'"
+        );
+
+        let message = invalid_message(RpcRequestPath::parse("drone-123/drone./Echo"));
+        assert_eq!(
+            message,
+            "empty service in package.service segment 'drone.' of 'drone-123/drone./Echo'"
+        );
+    }
+
+    #[test]
+    fn test_grpc_path_extra_segment() {
+        let result = GrpcPath::parse("drone.EchoService/Echo/Extra");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_grpc_path_accessors_round_trip() {
+        let path = GrpcPath::parse("drone.EchoService/Echo").unwrap();
+        assert_eq!(path.package(), "drone");
+        assert_eq!(path.service(), "EchoService");
+        assert_eq!(path.method(), "Echo");
+
+        let rebuilt = GrpcPath::new(path.package(), path.service(), path.method()).unwrap();
+        assert_eq!(rebuilt, path);
+        assert_eq!(rebuilt.full_path(), "drone.EchoService/Echo");
+    }
+
+    #[test]
+    fn test_grpc_path_new_rejects_invalid_parts() {
+        assert!(GrpcPath::new("", "EchoService", "Echo").is_err());
+        assert!(GrpcPath::new("drone", "", "Echo").is_err());
+        assert!(GrpcPath::new("drone", "EchoService", "").is_err());
+        assert!(GrpcPath::new("drone", "Echo.Service", "Echo").is_err());
+        assert!(GrpcPath::new("drone/fleet", "EchoService", "Echo").is_err());
+        assert!(GrpcPath::new("drone", "EchoService", "Echo/Extra").is_err());
+    }
+
+    #[test]
+    fn test_grpc_path_missing_slash() {
+        let message = match GrpcPath::parse("drone.EchoService") {
+            Err(RpcPathError::Invalid(message)) => message,
+            Ok(path) => panic!("expected an invalid path, parsed {path:?}"),
+        };
+        assert_eq!(message, "missing method segment in 'drone.EchoService'");
+    }
 }
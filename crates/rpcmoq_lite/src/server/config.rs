@@ -1,5 +1,10 @@
+use std::time::Duration;
+
 use bon::Builder;
 
+use crate::compression::Compression;
+use crate::server::rate_limit::RateLimit;
+
 /// Configuration for the RPC router.
 #[derive(Debug, Clone, Builder)]
 pub struct RpcRouterConfig {
@@ -16,6 +21,46 @@ pub struct RpcRouterConfig {
     /// Track name for RPC messages (e.g., "primary").
     #[builder(default = "primary".to_string())]
     pub track_name: String,
+
+    /// Skip request frames that fail to decode instead of ending the connection.
+    /// Defaults to `false`, so handlers observe decode errors as items of their `DecodedInbound`.
+    #[builder(default)]
+    pub skip_decode_errors: bool,
+
+    /// Compression applied to outgoing frames and expected of incoming frames.
+    /// Both peers must be configured with the same compression. Defaults to none.
+    #[builder(default)]
+    pub compression: Compression,
+
+    /// Interval at which heartbeats are sent on each connection's response track, letting
+    /// clients tell an idle connection from a dropped one.
+    /// If not set, no heartbeats are sent.
+    pub keepalive_interval: Option<Duration>,
+
+    /// Maximum number of concurrent sessions across the router.
+    /// Announcements arriving while this many sessions are active are declined without creating
+    /// a response broadcast, so rejected clients never see a response and time out connecting.
+    /// If not set, the number of sessions is unlimited.
+    pub max_connections: Option<usize>,
+
+    /// Limit on how often each client's announcements are honored.
+    /// Announcements beyond the limit are declined without creating a response broadcast, as
+    /// with [`max_connections`](Self::max_connections).
+    /// If not set, announcements are not rate limited.
+    pub rate_limit: Option<RateLimit>,
+
+    /// Replace the session of a client announcing an RPC it already has a session for, instead of
+    /// declining the announcement.
+    /// The previous connection is closed and the new one served, letting a client whose transport
+    /// dropped reconnect before its previous session has been torn down. Defaults to `false`.
+    #[builder(default)]
+    pub replace_sessions: bool,
+
+    /// Answer health checks at [`HEALTH_PATH`](crate::server::HEALTH_PATH) with the registered
+    /// paths and the number of active sessions.
+    /// A handler registered for the path takes precedence. Defaults to `false`.
+    #[builder(default)]
+    pub enable_health: bool,
 }
 
 impl RpcRouterConfig {
@@ -4,35 +4,51 @@ use std::future::Future;
 use std::marker::PhantomData;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::task::{Context, Poll};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll, ready};
+use tokio::task::JoinSet;
 use tonic::Status;
+use tracing::Instrument;
 
 use crate::connection::{RpcInbound, RpcOutbound};
 use crate::error::RpcWireError;
-use crate::server::session::SessionGuard;
+use crate::frame::Frame;
+use crate::server::config::RpcRouterConfig;
+use crate::server::metrics::{ConnectionMetrics, RpcMetrics};
+use crate::server::session::{SessionGuard, SessionKey};
 
 /// A type-erased handler that can be stored in a HashMap.
 ///
 /// This trait allows us to store handlers with different type parameters
 /// in a single registry.
 pub(crate) trait ErasedHandler: Send + Sync {
-    /// Spawn a task to handle the connection.
+    /// Spawn a task onto `tasks` to handle the connection.
     ///
     /// Takes raw bytes from MoQ, decodes them, calls the connector,
     /// encodes responses, and writes them back to MoQ.
     fn spawn_handler(
         &self,
+        tasks: &mut JoinSet<()>,
+        config: &RpcRouterConfig,
         client_id: String,
         inbound: RpcInbound,
         outbound: RpcOutbound,
-        connection_guard: ConnectionGuard,
+        connection_guard: Arc<ConnectionGuard>,
     );
 }
 
 /// A concrete typed inbound stream that decodes protobuf messages from `RpcInbound`.
+///
+/// Yields an error item if a frame fails to decode or the underlying track errors, after which
+/// the stream ends. Decode errors can instead be skipped with
+/// [`with_skip_decode_errors`](DecodedInbound::with_skip_decode_errors).
 pub struct DecodedInbound<Req> {
     inner: RpcInbound,
     on_decode_error: Option<std::sync::Arc<dyn Fn() + Send + Sync>>,
+    metrics: Option<Arc<RpcMetrics>>,
+    skip_decode_errors: bool,
+    // Set once an error has been yielded, after which the stream ends
+    done: bool,
     _marker: PhantomData<fn() -> Req>,
 }
 
@@ -41,10 +57,38 @@ impl<Req> DecodedInbound<Req> {
         Self {
             inner,
             on_decode_error: None,
+            metrics: None,
+            skip_decode_errors: false,
+            done: false,
             _marker: PhantomData,
         }
     }
 
+    /// Skip frames that fail to decode instead of yielding an error and ending the stream.
+    ///
+    /// By default decode errors are yielded, see the [`Stream`] implementation.
+    pub fn with_skip_decode_errors(mut self, skip: bool) -> Self {
+        self.skip_decode_errors = skip;
+        self
+    }
+
+    /// Convert into a stream of only the successfully decoded requests, ending at the first error.
+    ///
+    /// This is the form expected by the request argument of tonic clients.
+    pub fn into_ok_stream(self) -> impl Stream<Item = Req> + Send + 'static
+    where
+        Req: prost::Message + Default + 'static,
+    {
+        self.take_while(|result| std::future::ready(result.is_ok()))
+            .filter_map(|result| std::future::ready(result.ok()))
+    }
+
+    /// Record every decoded message in `metrics`.
+    pub(crate) fn with_metrics(mut self, metrics: Arc<RpcMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     /// Attach a callback that runs when a decode error occurs.
     pub fn with_decode_error_handler<F>(mut self, f: F) -> Self
     where
@@ -59,29 +103,44 @@ impl<Req> Stream for DecodedInbound<Req>
 where
     Req: prost::Message + Default,
 {
-    type Item = Req;
+    type Item = Result<Req, RpcWireError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.as_mut().get_mut();
-        match Pin::new(&mut this.inner).poll_next(cx) {
-            Poll::Ready(Some(Ok(bytes))) => match Req::decode(bytes) {
-                Ok(msg) => Poll::Ready(Some(msg)),
-                // stop the stream, close the connection if we cannot decode the
-                // message
-                Err(_) => {
-                    if let Some(handler) = &this.on_decode_error {
-                        handler();
+
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(Ok(bytes)) => match Req::decode(bytes.as_ref()) {
+                    Ok(msg) => {
+                        if let Some(metrics) = &this.metrics {
+                            metrics.message_in(bytes.len());
+                        }
+                        return Poll::Ready(Some(Ok(msg)));
+                    }
+                    Err(err) if this.skip_decode_errors => {
+                        tracing::warn!(%err, "Skipping inbound frame that failed to decode");
+                    }
+                    // surface the error and end the stream, closing the connection
+                    Err(_) => {
+                        if let Some(handler) = &this.on_decode_error {
+                            handler();
+                        }
+                        this.done = true;
+                        return Poll::Ready(Some(Err(RpcWireError::Decode)));
                     }
-                    Poll::Ready(None)
+                },
+                // if we got an error, surface it and close the connection
+                Some(Err(err)) => {
+                    tracing::error!(%err, "Got an error from MoQ");
+                    this.done = true;
+                    return Poll::Ready(Some(Err(err)));
                 }
-            },
-            // if we got an error, close the connection
-            Poll::Ready(Some(Err(err))) => {
-                tracing::error!(%err, "Got an error from MoQ");
-                Poll::Ready(None)
+                None => return Poll::Ready(None),
             }
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
         }
     }
 }
@@ -140,45 +199,64 @@ where
 {
     fn spawn_handler(
         &self,
+        tasks: &mut JoinSet<()>,
+        config: &RpcRouterConfig,
         client_id: String,
         inbound: RpcInbound,
         outbound: RpcOutbound,
-        connection_guard: ConnectionGuard,
+        connection_guard: Arc<ConnectionGuard>,
     ) {
         let connector = Arc::clone(&self.connector);
         let grpc_path = connection_guard.session_guard.grpc_path().to_string();
+        let skip_decode_errors = config.skip_decode_errors;
+        let keepalive_interval = config.keepalive_interval;
 
-        tokio::spawn(async move {
+        // Every event logged while handling the connection carries the client and path
+        let span =
+            tracing::info_span!("rpc_handler", client_id = %client_id, grpc_path = %grpc_path);
+
+        let task = async move {
             // Keep the session guard alive for the duration of the task
-            let _guard = connection_guard;
+            let guard = connection_guard;
+            let metrics = Arc::clone(guard.metrics.metrics());
 
             // Decode inbound bytes to typed messages with a concrete stream type.
             let abort_outbound = outbound.clone();
             let decode_client_id = client_id.clone();
             let decode_grpc_path = grpc_path.clone();
-            let typed_inbound =
-                DecodedInbound::<Req>::new(inbound).with_decode_error_handler(move || {
+            let typed_inbound = DecodedInbound::<Req>::new(inbound)
+                .with_decode_error_handler(move || {
                     tracing::warn!(
                         client_id = %decode_client_id,
                         grpc_path = %decode_grpc_path,
                         "Failed to decode request from client"
                     );
                     abort_outbound.abort_app(RpcWireError::Decode.to_code());
-                });
+                })
+                .with_metrics(Arc::clone(&metrics))
+                .with_skip_decode_errors(skip_decode_errors);
 
             // Call the connector to get the response stream
             let mut outbound = outbound;
+            let _keepalive = keepalive_interval.map(|interval| outbound.spawn_keepalive(interval));
+
+            let connected = tokio::select! {
+                connected = connector(client_id, typed_inbound) => connected,
+                // The backend may never answer, so a replaced session must not wait on it
+                () = guard.session_guard.replaced() => {
+                    tracing::debug!("Session replaced while connecting, closing connection");
+                    return;
+                }
+            };
 
-            let response_stream = match connector(client_id.clone(), typed_inbound).await {
+            let response_stream = match connected {
                 Ok(stream) => stream,
                 Err(status) => {
                     tracing::warn!(
-                        client_id = %client_id,
-                        grpc_path = %grpc_path,
                         error = %status,
                         "Connector failed to establish gRPC connection"
                     );
-                    outbound.abort_app(RpcWireError::Grpc.to_code());
+                    send_status(&mut outbound, &status);
                     return;
                 }
             };
@@ -186,48 +264,150 @@ where
             // Pipe responses back to MoQ
             let mut response_stream = response_stream;
 
-            while let Some(result) = response_stream.next().await {
+            loop {
+                let result = tokio::select! {
+                    result = response_stream.next() => match result {
+                        Some(result) => result,
+                        None => break,
+                    },
+                    // A reconnecting client took over the session, so stop serving this one
+                    () = guard.session_guard.replaced() => {
+                        tracing::debug!("Session replaced, closing connection");
+                        return;
+                    }
+                };
+
                 match result {
                     Ok(msg) => {
+                        let encoded_len = msg.encoded_len();
                         if let Err(e) = outbound.send(&msg) {
                             tracing::warn!(
-                                client_id = %client_id,
-                                grpc_path = %grpc_path,
                                 error = %e,
                                 "Failed to send response to MoQ"
                             );
                             outbound.abort_app(RpcWireError::Internal.to_code());
                             return;
                         }
+                        metrics.message_out(encoded_len);
                     }
                     Err(status) => {
                         tracing::warn!(
-                            client_id = %client_id,
-                            grpc_path = %grpc_path,
                             error = %status,
                             "gRPC response stream error"
                         );
-                        outbound.abort_app(RpcWireError::Grpc.to_code());
+                        send_status(&mut outbound, &status);
                         return;
                     }
                 }
             }
 
             tracing::debug!(
-                client_id = %client_id,
-                grpc_path = %grpc_path,
+                duration_ms = guard.metrics.duration().as_millis(),
                 "Handler completed"
             );
-        });
+        };
+
+        tasks.spawn(task.instrument(span));
     }
 }
 
+/// A handler spreading connections across several backends by weighted round-robin.
+///
+/// Each connection is handed to the next backend in a rotation where every backend appears as
+/// many times as its weight, so a backend of weight 3 serves three connections for each one
+/// served by a backend of weight 1.
+pub(crate) struct PoolHandler {
+    // Backends with the cumulative weight up to and including each one
+    backends: Vec<(u64, Arc<dyn ErasedHandler>)>,
+    total_weight: u64,
+    rotation: AtomicU64,
+}
+
+impl PoolHandler {
+    /// Build a pool from backends and their weights, skipping backends of weight zero.
+    ///
+    /// Returns `None` if no backend has a non-zero weight.
+    pub fn new(backends: impl IntoIterator<Item = (u32, Arc<dyn ErasedHandler>)>) -> Option<Self> {
+        let mut total_weight = 0;
+        let backends: Vec<_> = backends
+            .into_iter()
+            .filter(|(weight, _)| *weight > 0)
+            .map(|(weight, handler)| {
+                total_weight += u64::from(weight);
+                (total_weight, handler)
+            })
+            .collect();
+
+        (!backends.is_empty()).then(|| Self {
+            backends,
+            total_weight,
+            rotation: AtomicU64::new(0),
+        })
+    }
+
+    /// Pick the backend for the next connection.
+    fn next(&self) -> &Arc<dyn ErasedHandler> {
+        let slot = self.rotation.fetch_add(1, Ordering::Relaxed) % self.total_weight;
+        let index = self
+            .backends
+            .partition_point(|(cumulative, _)| *cumulative <= slot);
+        &self.backends[index].1
+    }
+}
+
+impl ErasedHandler for PoolHandler {
+    fn spawn_handler(
+        &self,
+        tasks: &mut JoinSet<()>,
+        config: &RpcRouterConfig,
+        client_id: String,
+        inbound: RpcInbound,
+        outbound: RpcOutbound,
+        connection_guard: Arc<ConnectionGuard>,
+    ) {
+        self.next().spawn_handler(
+            tasks,
+            config,
+            client_id,
+            inbound,
+            outbound,
+            connection_guard,
+        );
+    }
+}
+
+/// Forward a terminal gRPC `status` to the client as an error frame.
+///
+/// The track is left to close once the handler ends rather than aborted, as aborting discards any
+/// frame the client has yet to read.
+fn send_status(outbound: &mut RpcOutbound, status: &Status) {
+    if outbound.send_frame(&Frame::status(status)).is_err() {
+        outbound.abort_app(RpcWireError::Grpc.to_code());
+    }
+}
+
+/// A callback run as each connection ends, see [`RpcRouter::on_disconnect`](crate::RpcRouter::on_disconnect).
+pub(crate) type DisconnectHook = Arc<dyn Fn(&SessionKey) + Send + Sync>;
+
 // A guard that keeps relevant pieces of data alive until they need to be dropped.
+// Shared by the handlers of the primary track and every channel of a connection.
 pub(crate) struct ConnectionGuard {
     // Session guard needs to stay alive for the handler call duration
     pub session_guard: SessionGuard,
     // If we drop the response_broadcast, the broadcast will close
     pub _response_broadcast: BroadcastProducer,
+    // Counts the connection as active for the handler call duration
+    pub metrics: ConnectionMetrics,
+    // Run once every handler of the connection ends, however they end
+    pub disconnect_hooks: Vec<DisconnectHook>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        for hook in &self.disconnect_hooks {
+            hook(self.session_guard.key());
+        }
+    }
 }
 
 /// Helper to create a boxed connector from an async closure.
@@ -249,3 +429,94 @@ where
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+    use moq_lite::Track;
+    use prost::Message;
+
+    use super::*;
+    use crate::compression::Compression;
+    use crate::error::RpcCodecError;
+
+    // Truncated varint which cannot be decoded as any message
+    const MALFORMED: &[u8] = &[0xff];
+
+    fn encode(msg: &str) -> Vec<u8> {
+        msg.to_string().encode_to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_decode_error_is_yielded_by_default() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::new(track.producer);
+        let mut inbound = DecodedInbound::<String>::new(RpcInbound::from_track(track.consumer));
+
+        outbound.send_raw(MALFORMED).unwrap();
+
+        assert!(matches!(
+            inbound.next().await,
+            Some(Err(RpcWireError::Decode))
+        ));
+        assert!(inbound.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_decode_error_is_skipped_when_configured() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::new(track.producer);
+        let mut inbound = DecodedInbound::<String>::new(RpcInbound::from_track(track.consumer))
+            .with_skip_decode_errors(true);
+
+        outbound.send_raw(MALFORMED).unwrap();
+        assert!(inbound.next().now_or_never().is_none());
+
+        outbound.send_raw(encode("hello")).unwrap();
+        assert_eq!(inbound.next().await.unwrap().unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_ok_stream_ends_at_first_error() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::new(track.producer);
+        let inbound =
+            DecodedInbound::<String>::new(RpcInbound::from_track(track.consumer)).into_ok_stream();
+        let mut inbound = std::pin::pin!(inbound);
+
+        outbound.send_raw(encode("hello")).unwrap();
+        assert_eq!(inbound.next().await.unwrap(), "hello");
+
+        outbound.send_raw(MALFORMED).unwrap();
+        assert!(inbound.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_compressed_frames_are_decoded() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::new(track.producer).with_compression(Compression::Zstd);
+        let mut inbound = DecodedInbound::<String>::new(
+            RpcInbound::from_track(track.consumer).with_compression(Compression::Zstd),
+        );
+
+        outbound.send_raw(encode("hello")).unwrap();
+        assert_eq!(inbound.next().await.unwrap().unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_compression_mismatch_is_rejected() {
+        let track = Track::new("primary").produce();
+        let mut outbound = RpcOutbound::new(track.producer).with_compression(Compression::Gzip);
+        let mut inbound = DecodedInbound::<String>::new(RpcInbound::from_track(track.consumer));
+
+        outbound.send_raw(encode("hello")).unwrap();
+        assert!(matches!(
+            inbound.next().await,
+            Some(Err(RpcWireError::Codec(RpcCodecError::Mismatch {
+                expected: Compression::None,
+                found: Compression::Gzip,
+            })))
+        ));
+        assert!(inbound.next().await.is_none());
+    }
+}
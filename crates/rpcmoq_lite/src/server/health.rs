@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+
+use crate::server::handler::{DecodedInbound, ErasedHandler, TypedHandler, make_connector};
+use crate::server::router::RpcRouterHandle;
+
+/// The gRPC path answered by the built-in health check, see
+/// [`RpcRouterConfig::enable_health`](crate::RpcRouterConfig::enable_health).
+pub const HEALTH_PATH: &str = "grpc.health.v1.Health/Check";
+
+/// A request to the built-in health check, laid out like `grpc.health.v1.HealthCheckRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct HealthCheckRequest {
+    /// The service to check. Ignored, the router reports on itself.
+    #[prost(string, tag = "1")]
+    pub service: String,
+}
+
+/// The response of the built-in health check.
+///
+/// Extends `grpc.health.v1.HealthCheckResponse` with the state of the router, so standard health
+/// clients can still read the serving status.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct HealthCheckResponse {
+    /// The serving status, always `SERVING` (1) while the router runs.
+    #[prost(int32, tag = "1")]
+    pub status: i32,
+    /// The gRPC paths and patterns with a registered handler, sorted.
+    #[prost(string, repeated, tag = "2")]
+    pub registered_paths: Vec<String>,
+    /// The number of active sessions.
+    #[prost(uint64, tag = "3")]
+    pub active_sessions: u64,
+}
+
+impl HealthCheckResponse {
+    /// The `SERVING` value of `status`.
+    pub const SERVING: i32 = 1;
+}
+
+/// Build the handler answering each health check with the current state of the router.
+pub(crate) fn health_handler(handle: RpcRouterHandle) -> Arc<dyn ErasedHandler> {
+    let connector = make_connector(
+        move |_client_id, inbound: DecodedInbound<HealthCheckRequest>| {
+            let handle = handle.clone();
+            async move {
+                Ok(inbound.into_ok_stream().map(move |_| {
+                    Ok(HealthCheckResponse {
+                        status: HealthCheckResponse::SERVING,
+                        registered_paths: handle.registered_paths(),
+                        active_sessions: handle.active_sessions() as u64,
+                    })
+                }))
+            }
+        },
+    );
+    Arc::new(TypedHandler::<HealthCheckRequest, HealthCheckResponse>::new(connector))
+}
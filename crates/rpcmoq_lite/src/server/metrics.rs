@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Counters describing the traffic handled by an [`RpcRouter`](super::RpcRouter).
+///
+/// All counters are atomic, so they can be read while handlers are running. Use
+/// [`snapshot`](RpcMetrics::snapshot) to read a copy of every counter at once.
+#[derive(Debug, Default)]
+pub struct RpcMetrics {
+    active_connections: AtomicU64,
+    total_connections: AtomicU64,
+    messages_in: AtomicU64,
+    messages_out: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+/// A point-in-time copy of the counters of [`RpcMetrics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RpcMetricsSnapshot {
+    /// The number of connections currently being handled.
+    pub active_connections: u64,
+    /// The number of connections handled since the router was created.
+    pub total_connections: u64,
+    /// The number of request messages decoded from clients.
+    pub messages_in: u64,
+    /// The number of response messages sent to clients.
+    pub messages_out: u64,
+    /// The number of encoded request bytes decoded from clients.
+    pub bytes_in: u64,
+    /// The number of encoded response bytes sent to clients.
+    pub bytes_out: u64,
+}
+
+impl RpcMetrics {
+    /// Read the current value of every counter.
+    ///
+    /// Each counter is read individually, so the snapshot may not be consistent across counters
+    /// while handlers are running.
+    pub fn snapshot(&self) -> RpcMetricsSnapshot {
+        RpcMetricsSnapshot {
+            active_connections: self.active_connections.load(Ordering::Relaxed),
+            total_connections: self.total_connections.load(Ordering::Relaxed),
+            messages_in: self.messages_in.load(Ordering::Relaxed),
+            messages_out: self.messages_out.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Record a new connection, which is counted as active until the returned guard is dropped.
+    pub(crate) fn connection_opened(self: &Arc<Self>) -> ConnectionMetrics {
+        self.active_connections.fetch_add(1, Ordering::Relaxed);
+        self.total_connections.fetch_add(1, Ordering::Relaxed);
+
+        ConnectionMetrics {
+            metrics: Arc::clone(self),
+            opened: Instant::now(),
+        }
+    }
+
+    pub(crate) fn message_in(&self, bytes: usize) {
+        self.messages_in.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_out(&self, bytes: usize) {
+        self.messages_out.fetch_add(1, Ordering::Relaxed);
+        self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+/// A guard that counts a connection as active until dropped.
+pub(crate) struct ConnectionMetrics {
+    metrics: Arc<RpcMetrics>,
+    opened: Instant,
+}
+
+impl ConnectionMetrics {
+    pub fn metrics(&self) -> &Arc<RpcMetrics> {
+        &self.metrics
+    }
+
+    /// How long the connection has been open.
+    pub fn duration(&self) -> Duration {
+        self.opened.elapsed()
+    }
+}
+
+impl Drop for ConnectionMetrics {
+    fn drop(&mut self) {
+        self.metrics
+            .active_connections
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
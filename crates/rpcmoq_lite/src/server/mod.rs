@@ -5,10 +5,16 @@
 
 mod config;
 mod handler;
+mod health;
+mod metrics;
+mod rate_limit;
 mod router;
 mod session;
 
 pub use config::RpcRouterConfig;
 pub use handler::DecodedInbound;
-pub use router::RpcRouter;
+pub use health::{HEALTH_PATH, HealthCheckRequest, HealthCheckResponse};
+pub use metrics::{RpcMetrics, RpcMetricsSnapshot};
+pub use rate_limit::RateLimit;
+pub use router::{RpcRouter, RpcRouterHandle};
 pub use session::{SessionGuard, SessionKey, SessionMap};
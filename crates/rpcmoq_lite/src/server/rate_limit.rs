@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::error::RpcRateLimitError;
+
+/// How often each client's announcements are honored, see
+/// [`RpcRouterConfig::rate_limit`](crate::RpcRouterConfig::rate_limit).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    burst: u32,
+    window: Duration,
+}
+
+impl RateLimit {
+    /// Allow a burst of `burst` announcements in quick succession, earned back over `window`.
+    ///
+    /// Returns an error if either is zero, which would decline every announcement or refill
+    /// instantly.
+    pub fn new(burst: u32, window: Duration) -> Result<Self, RpcRateLimitError> {
+        if burst == 0 {
+            return Err(RpcRateLimitError::ZeroBurst);
+        }
+        if window.is_zero() {
+            return Err(RpcRateLimitError::ZeroWindow);
+        }
+        Ok(Self { burst, window })
+    }
+
+    /// The number of announcements a client can make in quick succession.
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// The time taken to earn back a full burst of announcements.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+}
+
+/// A token bucket per client id, refilled continuously at `burst` tokens per `window`.
+///
+/// Reads the clock to refill, so it lives in the router loop rather than in any pure state.
+#[derive(Debug)]
+pub(crate) struct ClientRateLimiter {
+    limit: RateLimit,
+    buckets: HashMap<String, Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl ClientRateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Take a token for an announcement from `client_id`, returning whether one was available.
+    pub fn try_acquire(&mut self, client_id: &str) -> bool {
+        let now = Instant::now();
+        let burst = f64::from(self.limit.burst);
+        let per_second = burst / self.limit.window.as_secs_f64();
+
+        // Refill every bucket, forgetting clients whose bucket is full again
+        self.buckets.retain(|_, bucket| {
+            let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * per_second).min(burst);
+            bucket.refilled_at = now;
+            bucket.tokens < burst
+        });
+
+        let bucket = self.buckets.entry(client_id.to_string()).or_insert(Bucket {
+            tokens: burst,
+            refilled_at: now,
+        });
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> ClientRateLimiter {
+        ClientRateLimiter::new(RateLimit::new(2, Duration::from_secs(10)).unwrap())
+    }
+
+    #[test]
+    fn test_zero_limits_are_rejected() {
+        assert!(matches!(
+            RateLimit::new(0, Duration::from_secs(10)),
+            Err(RpcRateLimitError::ZeroBurst)
+        ));
+        assert!(matches!(
+            RateLimit::new(2, Duration::ZERO),
+            Err(RpcRateLimitError::ZeroWindow)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_then_refill() {
+        let mut limiter = limiter();
+
+        assert!(limiter.try_acquire("client-1"));
+        assert!(limiter.try_acquire("client-1"));
+        assert!(!limiter.try_acquire("client-1"));
+
+        // One token is earned back every five seconds
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert!(limiter.try_acquire("client-1"));
+        assert!(!limiter.try_acquire("client-1"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_clients_are_limited_independently() {
+        let mut limiter = limiter();
+
+        assert!(limiter.try_acquire("client-1"));
+        assert!(limiter.try_acquire("client-1"));
+        assert!(!limiter.try_acquire("client-1"));
+
+        assert!(limiter.try_acquire("client-2"));
+    }
+}
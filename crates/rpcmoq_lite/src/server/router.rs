@@ -1,29 +1,303 @@
 use futures::Stream;
 use moq_lite::{BroadcastConsumer, OriginConsumer, OriginProducer, Track};
 use std::collections::HashMap;
+use std::collections::hash_map::Entry as HashMapEntry;
 use std::future::Future;
-use std::sync::Arc;
+use std::pin::pin;
+use std::sync::{Arc, RwLock};
+use tokio::task::JoinSet;
 use tonic::Status;
 use tracing::{debug, info, warn};
 
 use crate::connection::{RpcInbound, RpcOutbound};
 use crate::error::{RpcServerError, RpcWireError};
-use crate::path::RpcRequestPath;
+use crate::path::{GrpcPath, RpcRequestPath};
 use crate::server::config::RpcRouterConfig;
 use crate::server::handler::{
-    ConnectionGuard, DecodedInbound, ErasedHandler, TypedHandler, make_connector,
+    ConnectionGuard, DecodedInbound, DisconnectHook, ErasedHandler, PoolHandler, TypedHandler,
+    make_connector,
 };
+use crate::server::health::{HEALTH_PATH, health_handler};
+use crate::server::metrics::{RpcMetrics, RpcMetricsSnapshot};
+use crate::server::rate_limit::ClientRateLimiter;
 use crate::server::session::{SessionKey, SessionMap};
 
 /// The main RPC router that manages connections and dispatches to handlers.
 pub struct RpcRouter {
     consumer: OriginConsumer,
     producer: Arc<OriginProducer>,
-    sessions: Arc<SessionMap>,
-    handlers: HashMap<String, Arc<dyn ErasedHandler>>,
+    handlers: RpcRouterHandle,
     config: RpcRouterConfig,
 }
 
+/// Handlers keyed by the gRPC path or channel they serve.
+type HandlerMap = HashMap<String, Arc<dyn ErasedHandler>>;
+
+/// A callback run as each connection is established, see [`RpcRouter::on_connect`].
+type ConnectHook = Arc<dyn Fn(&str, &GrpcPath) + Send + Sync>;
+
+/// The suffix marking a registered path as a pattern matching every path with the same prefix.
+const WILDCARD: &str = "*";
+
+/// A handle to an [`RpcRouter`], allowing its handlers to be changed and its sessions and
+/// metrics to be read while it runs.
+///
+/// Handles are cheap to clone and all share the same handlers. Changes only affect connections
+/// announced afterwards; connections already being handled are unaffected.
+#[derive(Clone)]
+pub struct RpcRouterHandle {
+    // The primary track name of the router, which channels must not be named after
+    track_name: Arc<str>,
+    handlers: Arc<RwLock<HandlerMap>>,
+    // Channel handlers by gRPC path, then channel name
+    channels: Arc<RwLock<HashMap<String, HandlerMap>>>,
+    sessions: Arc<SessionMap>,
+    metrics: Arc<RpcMetrics>,
+    connect_hooks: Arc<RwLock<Vec<ConnectHook>>>,
+    disconnect_hooks: Arc<RwLock<Vec<DisconnectHook>>>,
+}
+
+impl RpcRouterHandle {
+    fn new(track_name: &str) -> Self {
+        Self {
+            track_name: track_name.into(),
+            handlers: Arc::default(),
+            channels: Arc::default(),
+            sessions: Arc::default(),
+            metrics: Arc::default(),
+            connect_hooks: Arc::default(),
+            disconnect_hooks: Arc::default(),
+        }
+    }
+
+    /// Register a handler for a gRPC path or pattern, replacing any existing handler for it.
+    ///
+    /// See [`RpcRouter::register`].
+    pub fn register<Req, Resp, F, Fut, S>(
+        &self,
+        grpc_path: impl Into<String>,
+        connector: F,
+    ) -> Result<(), RpcServerError>
+    where
+        Req: prost::Message + Default + Send + 'static,
+        Resp: prost::Message + Send + 'static,
+        F: Fn(String, DecodedInbound<Req>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<S, Status>> + Send + 'static,
+        S: Stream<Item = Result<Resp, Status>> + Send + 'static,
+    {
+        let grpc_path = grpc_path.into();
+        let boxed_connector = make_connector(connector);
+        let handler = TypedHandler::<Req, Resp>::new(boxed_connector);
+        self.handlers
+            .write()
+            .expect("handler registry lock poisoned")
+            .insert(grpc_path.clone(), Arc::new(handler));
+
+        info!(grpc_path = %grpc_path, "Registered RPC handler");
+        Ok(())
+    }
+
+    /// Register a pool of weighted backends for a gRPC path or pattern, replacing any existing
+    /// handler for it.
+    ///
+    /// See [`RpcRouter::register_pool`].
+    pub fn register_pool<Req, Resp, F, Fut, S>(
+        &self,
+        grpc_path: impl Into<String>,
+        backends: impl IntoIterator<Item = (u32, F)>,
+    ) -> Result<(), RpcServerError>
+    where
+        Req: prost::Message + Default + Send + 'static,
+        Resp: prost::Message + Send + 'static,
+        F: Fn(String, DecodedInbound<Req>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<S, Status>> + Send + 'static,
+        S: Stream<Item = Result<Resp, Status>> + Send + 'static,
+    {
+        let grpc_path = grpc_path.into();
+        let pool = PoolHandler::new(backends.into_iter().map(|(weight, connector)| {
+            let handler = TypedHandler::<Req, Resp>::new(make_connector(connector));
+            (weight, Arc::new(handler) as Arc<dyn ErasedHandler>)
+        }))
+        .ok_or_else(|| RpcServerError::EmptyPool(grpc_path.clone()))?;
+        self.handlers
+            .write()
+            .expect("handler registry lock poisoned")
+            .insert(grpc_path.clone(), Arc::new(pool));
+
+        info!(grpc_path = %grpc_path, "Registered RPC backend pool");
+        Ok(())
+    }
+
+    /// Register a handler for a channel of connections to a gRPC path.
+    ///
+    /// See [`RpcRouter::register_channel`].
+    pub fn register_channel<Req, Resp, F, Fut, S>(
+        &self,
+        grpc_path: impl Into<String>,
+        channel: impl Into<String>,
+        connector: F,
+    ) -> Result<(), RpcServerError>
+    where
+        Req: prost::Message + Default + Send + 'static,
+        Resp: prost::Message + Send + 'static,
+        F: Fn(String, DecodedInbound<Req>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<S, Status>> + Send + 'static,
+        S: Stream<Item = Result<Resp, Status>> + Send + 'static,
+    {
+        let grpc_path = grpc_path.into();
+        let channel = channel.into();
+        if channel == *self.track_name {
+            return Err(RpcServerError::ReservedChannel { grpc_path, channel });
+        }
+
+        let mut channels = self
+            .channels
+            .write()
+            .expect("channel registry lock poisoned");
+        let HashMapEntry::Vacant(slot) = channels
+            .entry(grpc_path.clone())
+            .or_default()
+            .entry(channel.clone())
+        else {
+            return Err(RpcServerError::DuplicateChannel { grpc_path, channel });
+        };
+        let boxed_connector = make_connector(connector);
+        slot.insert(Arc::new(TypedHandler::<Req, Resp>::new(boxed_connector)));
+        drop(channels);
+
+        info!(grpc_path = %grpc_path, channel = %channel, "Registered RPC channel handler");
+        Ok(())
+    }
+
+    /// Unregister the handler for a specific gRPC path.
+    ///
+    /// Returns `true` if a handler was registered for the path.
+    pub fn unregister(&self, grpc_path: &str) -> bool {
+        let removed = self
+            .handlers
+            .write()
+            .expect("handler registry lock poisoned")
+            .remove(grpc_path)
+            .is_some();
+
+        if removed {
+            info!(grpc_path = %grpc_path, "Unregistered RPC handler");
+        }
+        removed
+    }
+
+    /// Check if a handler is registered for the given path.
+    pub fn has_handler(&self, grpc_path: &str) -> bool {
+        self.handlers
+            .read()
+            .expect("handler registry lock poisoned")
+            .contains_key(grpc_path)
+    }
+
+    /// Get the gRPC paths and patterns with a registered handler, sorted.
+    pub(crate) fn registered_paths(&self) -> Vec<String> {
+        let mut paths: Vec<_> = self
+            .handlers
+            .read()
+            .expect("handler registry lock poisoned")
+            .keys()
+            .cloned()
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Register a callback to run as each connection is established.
+    ///
+    /// See [`RpcRouter::on_connect`].
+    pub fn on_connect(&self, hook: impl Fn(&str, &GrpcPath) + Send + Sync + 'static) {
+        self.connect_hooks
+            .write()
+            .expect("connect hooks lock poisoned")
+            .push(Arc::new(hook));
+    }
+
+    /// Register a callback to run as each connection ends.
+    ///
+    /// See [`RpcRouter::on_disconnect`].
+    pub fn on_disconnect(&self, hook: impl Fn(&SessionKey) + Send + Sync + 'static) {
+        self.disconnect_hooks
+            .write()
+            .expect("disconnect hooks lock poisoned")
+            .push(Arc::new(hook));
+    }
+
+    /// Read the current traffic metrics of the router.
+    pub fn metrics_snapshot(&self) -> RpcMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Get the number of active sessions.
+    pub fn active_sessions(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Get a snapshot of the keys of all active sessions, in no particular order.
+    pub fn active_session_keys(&self) -> Vec<SessionKey> {
+        self.sessions.active_keys()
+    }
+
+    /// Get a snapshot of the channel handlers registered for the given path.
+    fn channels(&self, grpc_path: &str) -> Vec<(String, Arc<dyn ErasedHandler>)> {
+        self.channels
+            .read()
+            .expect("channel registry lock poisoned")
+            .get(grpc_path)
+            .map(|channels| {
+                channels
+                    .iter()
+                    .map(|(name, handler)| (name.clone(), Arc::clone(handler)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Run the connect hooks, in the order they were registered, for a new connection.
+    fn run_connect_hooks(&self, client_id: &str, grpc_path: &GrpcPath) {
+        // Cloned out so a hook can register further hooks without deadlocking
+        let hooks = self
+            .connect_hooks
+            .read()
+            .expect("connect hooks lock poisoned")
+            .clone();
+
+        for hook in hooks {
+            hook(client_id, grpc_path);
+        }
+    }
+
+    /// Get the handler currently registered for the given path.
+    ///
+    /// An exact registration always wins. Otherwise the wildcard pattern with the longest prefix
+    /// matching the path is used.
+    fn get(&self, grpc_path: &str) -> Option<Arc<dyn ErasedHandler>> {
+        let handlers = self
+            .handlers
+            .read()
+            .expect("handler registry lock poisoned");
+
+        if let Some(handler) = handlers.get(grpc_path) {
+            return Some(Arc::clone(handler));
+        }
+
+        handlers
+            .iter()
+            .filter_map(|(pattern, handler)| {
+                let prefix = pattern.strip_suffix(WILDCARD)?;
+                grpc_path
+                    .starts_with(prefix)
+                    .then_some((prefix.len(), handler))
+            })
+            .max_by_key(|(prefix_len, _)| *prefix_len)
+            .map(|(_, handler)| Arc::clone(handler))
+    }
+}
+
 impl RpcRouter {
     /// Create a new RPC router.
     pub fn new(
@@ -34,13 +308,16 @@ impl RpcRouter {
         Self {
             consumer,
             producer,
-            sessions: Arc::new(SessionMap::new()),
-            handlers: HashMap::new(),
+            handlers: RpcRouterHandle::new(&config.track_name),
             config,
         }
     }
 
-    /// Register a handler for a specific gRPC path.
+    /// Register a handler for a gRPC path or pattern.
+    ///
+    /// A path ending in `*` (e.g. `drone.EchoService/*`) is a wildcard pattern matching every
+    /// path with the same prefix. Announcements are routed to the handler registered for their
+    /// exact path if there is one, falling back to the most specific (longest) matching pattern.
     ///
     /// # Example
     /// ```ignore
@@ -66,23 +343,134 @@ impl RpcRouter {
         Fut: Future<Output = Result<S, Status>> + Send + 'static,
         S: Stream<Item = Result<Resp, Status>> + Send + 'static,
     {
-        let grpc_path = grpc_path.into();
-        let boxed_connector = make_connector(connector);
-        let handler = TypedHandler::<Req, Resp>::new(boxed_connector);
-        self.handlers.insert(grpc_path.clone(), Arc::new(handler));
+        self.handlers.register(grpc_path, connector)
+    }
 
-        info!(grpc_path = %grpc_path, "Registered RPC handler");
-        Ok(())
+    /// Register a pool of backends for a gRPC path or pattern, each with a weight.
+    ///
+    /// Each connection is served by one backend, chosen by weighted round-robin: over every
+    /// rotation, a backend serves as many connections as its weight. Backends of weight zero
+    /// never serve connections, and registering no backend of non-zero weight is an error.
+    /// Paths and patterns are matched as with [`register`](Self::register).
+    ///
+    /// # Example
+    /// ```ignore
+    /// let backends = [(1, "http://[::1]:50051"), (3, "http://[::1]:50052")];
+    /// router.register_pool::<DronePosition, DronePosition, _, _, _>(
+    ///     "drone.EchoService/Echo",
+    ///     backends.map(|(weight, addr)| {
+    ///         (weight, move |_client_id, inbound: DecodedInbound<DronePosition>| async move {
+    ///             let mut client = EchoServiceClient::connect(addr).await
+    ///                 .map_err(|e| tonic::Status::internal(e.to_string()))?;
+    ///             let response = client.echo(inbound.into_ok_stream()).await?;
+    ///             Ok(response.into_inner())
+    ///         })
+    ///     }),
+    /// )?;
+    /// ```
+    pub fn register_pool<Req, Resp, F, Fut, S>(
+        &mut self,
+        grpc_path: impl Into<String>,
+        backends: impl IntoIterator<Item = (u32, F)>,
+    ) -> Result<(), RpcServerError>
+    where
+        Req: prost::Message + Default + Send + 'static,
+        Resp: prost::Message + Send + 'static,
+        F: Fn(String, DecodedInbound<Req>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<S, Status>> + Send + 'static,
+        S: Stream<Item = Result<Resp, Status>> + Send + 'static,
+    {
+        self.handlers.register_pool(grpc_path, backends)
     }
 
-    /// Run the router, processing connections until shutdown.
+    /// Register a handler for a named channel of connections to a gRPC path.
+    ///
+    /// Channels let a connection carry streams besides its primary track, such as separate
+    /// control and data streams. Each channel is a pair of tracks named after the channel, under
+    /// the same broadcasts as the connection. For every connection to `grpc_path`, a handler is
+    /// spawned for each of its registered channels alongside the handler for the primary track.
+    /// Clients open the channel with [`RpcConnection::channel`](crate::RpcConnection::channel).
+    ///
+    /// Channels are registered for an exact gRPC path, not a wildcard pattern. The connection's
+    /// session lasts until the handlers of its primary track and every channel have ended.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel is named after the primary
+    /// [`track_name`](RpcRouterConfig::track_name), or a handler is already registered for the
+    /// channel of `grpc_path`.
+    pub fn register_channel<Req, Resp, F, Fut, S>(
+        &mut self,
+        grpc_path: impl Into<String>,
+        channel: impl Into<String>,
+        connector: F,
+    ) -> Result<(), RpcServerError>
+    where
+        Req: prost::Message + Default + Send + 'static,
+        Resp: prost::Message + Send + 'static,
+        F: Fn(String, DecodedInbound<Req>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<S, Status>> + Send + 'static,
+        S: Stream<Item = Result<Resp, Status>> + Send + 'static,
+    {
+        self.handlers
+            .register_channel(grpc_path, channel, connector)
+    }
+
+    /// Register a callback to run as each connection is established.
+    ///
+    /// The callback receives the client id and gRPC path of the connection. It runs once the
+    /// connection's session has been created and before its handler is spawned, so before any
+    /// messages flow. Callbacks run in the order they were registered, on the router's task, so
+    /// they should not block.
+    ///
+    /// # Example
+    /// ```ignore
+    /// router.on_connect(|client_id, grpc_path| {
+    ///     info!(client_id, grpc_path = %grpc_path.full_path(), "Client connected");
+    /// });
+    /// ```
+    pub fn on_connect(&mut self, hook: impl Fn(&str, &GrpcPath) + Send + Sync + 'static) {
+        self.handlers.on_connect(hook);
+    }
+
+    /// Register a callback to run as each connection ends.
+    ///
+    /// The callback receives the session key of the connection. It runs exactly once for every
+    /// connection seen by the [`on_connect`](Self::on_connect) callbacks, once the handlers of the
+    /// connection and its channels have ended, whether they complete, error, or are replaced by a
+    /// reconnecting client.
+    /// Callbacks run in the order they were registered, just before the session is released.
+    ///
+    /// Only connections established after the callback is registered are affected.
+    pub fn on_disconnect(&mut self, hook: impl Fn(&SessionKey) + Send + Sync + 'static) {
+        self.handlers.on_disconnect(hook);
+    }
+
+    /// Get a handle for changing the registered handlers, including while the router runs.
+    pub fn handle(&self) -> RpcRouterHandle {
+        self.handlers.clone()
+    }
+
+    /// Run the router, processing connections until the consumer is closed.
     ///
     /// This method consumes the router and runs until the consumer is closed
     /// or a fatal error occurs. Handler tasks continue to run independently.
     pub async fn run(self) -> Result<(), RpcServerError> {
+        self.run_until(std::future::pending()).await
+    }
+
+    /// Run the router, processing connections until `shutdown` resolves.
+    ///
+    /// Once `shutdown` resolves no new connections are accepted, and this method drains the
+    /// in-flight handlers by waiting for each of them to finish before returning. A handler
+    /// finishes when its response stream ends, which typically happens once the client
+    /// disconnects.
+    ///
+    /// If the consumer is closed before `shutdown` resolves, this returns immediately and
+    /// in-flight handlers continue to run independently, as with [`run`](Self::run).
+    pub async fn run_until(self, shutdown: impl Future<Output = ()>) -> Result<(), RpcServerError> {
         // Extract fields we need before consuming consumer
         let producer = self.producer;
-        let sessions = self.sessions;
         let handlers = self.handlers;
         let config = self.config;
 
@@ -98,53 +486,114 @@ impl RpcRouter {
             "RPC router started, listening for announcements"
         );
 
+        let mut tasks = JoinSet::new();
+        let mut shutdown = pin!(shutdown);
+        let mut rate_limiter = config.rate_limit.map(ClientRateLimiter::new);
+
         loop {
-            match announcements.announced().await {
-                Some((path, Some(broadcast))) => {
-                    let path_str = path.to_string();
-                    debug!(path = %path_str, "Received announcement");
-
-                    if let Err(e) = Self::handle_announcement(
-                        &producer, &sessions, &handlers, &config, &path_str, broadcast,
-                    ) {
-                        warn!(path = %path_str, error = %e, "Failed to handle announcement");
+            tokio::select! {
+                announced = announcements.announced() => match announced {
+                    Some((path, Some(broadcast))) => {
+                        let path_str = path.to_string();
+                        debug!(path = %path_str, "Received announcement");
+
+                        if let Err(e) = Self::handle_announcement(
+                            &producer,
+                            &handlers,
+                            &config,
+                            rate_limiter.as_mut(),
+                            &mut tasks,
+                            &path_str,
+                            broadcast,
+                        ) {
+                            warn!(path = %path_str, error = %e, "Failed to handle announcement");
+                        }
                     }
-                }
 
-                Some((path, None)) => {
-                    debug!(path = %path.to_string(), "Client disconnected");
-                    // Session cleanup happens automatically via SessionGuard drop
-                }
+                    Some((path, None)) => {
+                        debug!(path = %path.to_string(), "Client disconnected");
+                        // Session cleanup happens automatically via SessionGuard drop
+                    }
 
-                None => {
-                    info!("Announcement stream closed, router shutting down");
-                    break;
-                }
+                    None => {
+                        info!("Announcement stream closed, router shutting down");
+                        tasks.detach_all();
+                        return Ok(());
+                    }
+                },
+
+                // Reap finished handlers so the set doesn't grow unbounded
+                Some(_) = tasks.join_next(), if !tasks.is_empty() => {}
+
+                () = &mut shutdown => break,
             }
         }
 
+        info!(
+            in_flight = tasks.len(),
+            "RPC router shutting down, draining in-flight handlers"
+        );
+
+        while tasks.join_next().await.is_some() {}
+
+        info!("RPC router shut down");
         Ok(())
     }
 
     /// Handle a new client announcement.
     fn handle_announcement(
         producer: &Arc<OriginProducer>,
-        sessions: &Arc<SessionMap>,
-        handlers: &HashMap<String, Arc<dyn ErasedHandler>>,
+        handlers: &RpcRouterHandle,
         config: &RpcRouterConfig,
+        rate_limiter: Option<&mut ClientRateLimiter>,
+        tasks: &mut JoinSet<()>,
         path: &str,
         broadcast: BroadcastConsumer,
     ) -> Result<(), RpcServerError> {
-        let (client_id, grpc_path) = match RpcRequestPath::parse(path) {
-            Ok(request_path) => (
-                request_path.client_id.clone(),
-                request_path.grpc_path.full_path(),
-            ),
-            Err(e) => return Err(e.into()),
-        };
+        let request_path = RpcRequestPath::parse(path)?;
+        let client_id = request_path.client_id.clone();
+        let grpc_path = request_path.grpc_path.full_path();
+        let session_key = SessionKey::new(&client_id, &grpc_path);
+        let replacing = config.replace_sessions && handlers.sessions.contains(&session_key);
+
+        // Declined before any response broadcast exists, so the client simply never sees one
+        if let Some(rate_limiter) = rate_limiter
+            && !rate_limiter.try_acquire(&client_id)
+        {
+            return Err(RpcServerError::RateLimited {
+                client_id,
+                grpc_path,
+            });
+        }
+
+        // Replacing a session does not add to the number of sessions
+        if let Some(limit) = config.max_connections
+            && !replacing
+            && handlers.active_sessions() >= limit
+        {
+            return Err(RpcServerError::ConnectionLimit {
+                client_id,
+                grpc_path,
+                limit,
+            });
+        }
 
         // Create the response broadcast early so we can surface errors like "no handler".
         let response_path = config.response_path(&client_id, &grpc_path);
+
+        // Creating a broadcast on a live path would replace it, cutting off whoever it serves,
+        // which is only wanted when replacing the session it serves
+        if !config.replace_sessions
+            && producer
+                .consume()
+                .consume_broadcast(&response_path)
+                .is_some()
+        {
+            return Err(RpcServerError::BroadcastConflict {
+                path: response_path,
+            });
+        }
+
         let mut response_broadcast =
             producer.create_broadcast(&response_path).ok_or_else(|| {
                 RpcServerError::BroadcastCreate(format!(
@@ -153,29 +602,62 @@ impl RpcRouter {
             })?;
 
         let outbound_track = response_broadcast.create_track(Track::new(&config.track_name));
-        let outbound = RpcOutbound::new(outbound_track);
+        let outbound = RpcOutbound::new(outbound_track).with_compression(config.compression);
 
-        let handler = handlers.get(&grpc_path).ok_or_else(|| {
-            warn!(
-                client_id = %client_id,
-                grpc_path = %grpc_path,
-                "No handler registered for gRPC path"
-            );
-            outbound.abort_app(RpcWireError::NoHandler.to_code());
-            RpcServerError::NoHandler(grpc_path.clone())
-        })?;
+        // The built-in health check only answers when no handler is registered for its path
+        let handler = handlers
+            .get(&grpc_path)
+            .or_else(|| {
+                (config.enable_health && grpc_path == HEALTH_PATH)
+                    .then(|| health_handler(handlers.clone()))
+            })
+            .ok_or_else(|| {
+                warn!(
+                    client_id = %client_id,
+                    grpc_path = %grpc_path,
+                    "No handler registered for gRPC path"
+                );
+                outbound.abort_app(RpcWireError::NoHandler.to_code());
+                RpcServerError::NoHandler(grpc_path.clone())
+            })?;
 
-        // Try to create a session (prevents duplicate connections)
-        let session_key = SessionKey::new(&client_id, &grpc_path);
-        let session_guard = match sessions.try_create(session_key) {
-            Ok(guard) => guard,
-            Err(e @ RpcServerError::SessionAlreadyActive { .. }) => {
-                outbound.abort_app(RpcWireError::SessionAlreadyActive.to_code());
-                return Err(e);
+        // Try to create a session (prevents duplicate connections, unless replacing them)
+        let session_guard = if config.replace_sessions {
+            let (guard, replaced) = handlers.sessions.try_create_or_replace(session_key);
+            if replaced {
+                info!(
+                    client_id = %client_id,
+                    grpc_path = %grpc_path,
+                    "Replaced existing session of reconnecting client"
+                );
+            }
+            guard
+        } else {
+            match handlers.sessions.try_create(session_key) {
+                Ok(guard) => guard,
+                Err(e @ RpcServerError::SessionAlreadyActive { .. }) => {
+                    outbound.abort_app(RpcWireError::SessionAlreadyActive.to_code());
+                    return Err(e);
+                }
+                Err(e) => return Err(e),
             }
-            Err(e) => return Err(e),
         };
-        let inbound = RpcInbound::new(&broadcast, &config.track_name);
+        handlers.run_connect_hooks(&client_id, &request_path.grpc_path);
+
+        let inbound =
+            RpcInbound::new(&broadcast, &config.track_name).with_compression(config.compression);
+
+        let channels: Vec<_> = handlers
+            .channels(&grpc_path)
+            .into_iter()
+            .map(|(name, handler)| {
+                let track = response_broadcast.create_track(Track::new(&name));
+                let outbound = RpcOutbound::new(track).with_compression(config.compression);
+                let inbound =
+                    RpcInbound::new(&broadcast, &name).with_compression(config.compression);
+                (handler, inbound, outbound)
+            })
+            .collect();
 
         info!(
             client_id = %client_id,
@@ -184,23 +666,773 @@ impl RpcRouter {
             "Spawning handler for new connection"
         );
 
-        let connection_guard = ConnectionGuard {
+        let connection_guard = Arc::new(ConnectionGuard {
             session_guard,
             _response_broadcast: response_broadcast,
-        };
+            metrics: handlers.metrics.connection_opened(),
+            disconnect_hooks: handlers
+                .disconnect_hooks
+                .read()
+                .expect("disconnect hooks lock poisoned")
+                .clone(),
+        });
 
-        handler.spawn_handler(client_id, inbound, outbound, connection_guard);
+        for (channel_handler, inbound, outbound) in channels {
+            channel_handler.spawn_handler(
+                tasks,
+                config,
+                client_id.clone(),
+                inbound,
+                outbound,
+                Arc::clone(&connection_guard),
+            );
+        }
+
+        handler.spawn_handler(
+            tasks,
+            config,
+            client_id,
+            inbound,
+            outbound,
+            connection_guard,
+        );
 
         Ok(())
     }
 
     /// Get the number of active sessions.
+    ///
+    /// Use [`handle`](Self::handle) to read the sessions once the router is running.
     pub fn active_sessions(&self) -> usize {
-        self.sessions.len()
+        self.handlers.active_sessions()
+    }
+
+    /// Get a snapshot of the keys of all active sessions, in no particular order.
+    ///
+    /// Use [`handle`](Self::handle) to read the sessions once the router is running.
+    pub fn active_session_keys(&self) -> Vec<SessionKey> {
+        self.handlers.active_session_keys()
     }
 
     /// Check if a handler is registered for the given path.
     pub fn has_handler(&self, grpc_path: &str) -> bool {
-        self.handlers.contains_key(grpc_path)
+        self.handlers.has_handler(grpc_path)
+    }
+
+    /// Read the current traffic metrics of the router.
+    ///
+    /// Use [`handle`](Self::handle) to read metrics once the router is running.
+    pub fn metrics_snapshot(&self) -> RpcMetricsSnapshot {
+        self.handlers.metrics_snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use futures::{SinkExt, StreamExt};
+    use moq_lite::Origin;
+    use prost::Message;
+
+    use super::*;
+    use crate::client::{RpcClient, RpcClientConfig};
+    use crate::error::RpcClientError;
+    use crate::server::RateLimit;
+    use crate::testing::Ping;
+
+    const ECHO_PATH: &str = "test.EchoService/Echo";
+
+    #[tokio::test]
+    async fn test_shutdown_drains_in_flight_handlers() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+
+        let mut router = server_sides.router(RpcRouterConfig::builder().build());
+        router
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let run = tokio::spawn(router.run_until(async {
+            let _ = shutdown_rx.await;
+        }));
+
+        let mut client = client_sides.client(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        );
+        let conn = client.connect::<Ping, Ping>(ECHO_PATH).await.unwrap();
+
+        // The handler for the open connection keeps the router from returning
+        shutdown_tx.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!run.is_finished());
+
+        // Disconnecting completes the handler, letting the drain finish
+        drop(conn);
+        tokio::time::timeout(Duration::from_secs(1), run)
+            .await
+            .expect("router should shut down once handlers complete")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_handler_rejects_new_connections() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+        let producer = Arc::clone(&server_sides.producer);
+
+        let router = server_sides.router(RpcRouterConfig::builder().build());
+        let handle = router.handle();
+        handle
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+        tokio::spawn(router.run());
+
+        let mut client = client_sides.client(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        );
+        let request = Ping {
+            text: "hello".to_string(),
+        };
+        let response: Ping = client.unary(ECHO_PATH, request.clone()).await.unwrap();
+        assert_eq!(response, request);
+
+        assert!(handle.unregister(ECHO_PATH));
+        assert!(!handle.has_handler(ECHO_PATH));
+        assert!(!handle.unregister(ECHO_PATH));
+
+        let result = RpcRouter::handle_announcement(
+            &producer,
+            &handle,
+            &RpcRouterConfig::builder().build(),
+            None,
+            &mut JoinSet::new(),
+            &format!("client-2/{ECHO_PATH}"),
+            moq_lite::Broadcast::produce().consumer,
+        );
+        assert!(matches!(result, Err(RpcServerError::NoHandler(path)) if path == ECHO_PATH));
+    }
+
+    fn echo_handle(patterns: &[&str]) -> RpcRouterHandle {
+        let handle = RpcRouterHandle::new("primary");
+        for pattern in patterns {
+            handle
+                .register::<Ping, Ping, _, _, _>(*pattern, |_client_id, inbound| async move {
+                    Ok(inbound.into_ok_stream().map(Ok))
+                })
+                .unwrap();
+        }
+        handle
+    }
+
+    fn registered(handle: &RpcRouterHandle, pattern: &str) -> Arc<dyn ErasedHandler> {
+        Arc::clone(&handle.handlers.read().unwrap()[pattern])
+    }
+
+    #[test]
+    fn test_exact_handler_wins_over_wildcard() {
+        let handle = echo_handle(&["test.EchoService/*", ECHO_PATH]);
+
+        let handler = handle.get(ECHO_PATH).unwrap();
+
+        assert!(Arc::ptr_eq(&handler, &registered(&handle, ECHO_PATH)));
+    }
+
+    #[test]
+    fn test_wildcard_matches_method() {
+        let handle = echo_handle(&["test.*", "test.EchoService/*", ECHO_PATH]);
+
+        let handler = handle.get("test.EchoService/Other").unwrap();
+        assert!(Arc::ptr_eq(
+            &handler,
+            &registered(&handle, "test.EchoService/*")
+        ));
+
+        let handler = handle.get("test.OtherService/Other").unwrap();
+        assert!(Arc::ptr_eq(&handler, &registered(&handle, "test.*")));
+
+        assert!(handle.get("other.EchoService/Echo").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_connection_traffic() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+
+        let mut router = server_sides.router(RpcRouterConfig::builder().build());
+        router
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+        let handle = router.handle();
+        tokio::spawn(router.run());
+
+        let mut client = client_sides.client(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        );
+        let mut conn = client.connect::<Ping, Ping>(ECHO_PATH).await.unwrap();
+
+        let request = Ping {
+            text: "hello".to_string(),
+        };
+        for _ in 0..3 {
+            conn.send(request.clone()).await.unwrap();
+            assert_eq!(conn.next().await.unwrap().unwrap(), request);
+        }
+
+        let metrics = handle.metrics_snapshot();
+        let message_len = request.encoded_len() as u64;
+        assert_eq!(metrics.active_connections, 1);
+        assert_eq!(metrics.total_connections, 1);
+        assert_eq!(metrics.messages_in, 3);
+        assert_eq!(metrics.messages_out, 3);
+        assert_eq!(metrics.bytes_in, 3 * message_len);
+        assert_eq!(metrics.bytes_out, 3 * message_len);
+    }
+
+    #[tokio::test]
+    async fn test_closed_connection_releases_session() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+
+        let mut router = server_sides.router(RpcRouterConfig::builder().build());
+        router
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+        let handle = router.handle();
+        tokio::spawn(router.run());
+
+        let mut client = client_sides.client(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        );
+        let mut conn = client.connect::<Ping, Ping>(ECHO_PATH).await.unwrap();
+
+        let request = Ping {
+            text: "hello".to_string(),
+        };
+        conn.send(request.clone()).await.unwrap();
+        assert_eq!(conn.next().await.unwrap().unwrap(), request);
+        assert_eq!(handle.active_sessions(), 1);
+
+        conn.close();
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while handle.active_sessions() > 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(handle.metrics_snapshot().active_connections, 0);
+    }
+
+    #[tokio::test]
+    async fn test_connect_hooks_observe_connection() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+
+        let mut router = server_sides.router(RpcRouterConfig::builder().build());
+        router
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+
+        let observed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        for hook in ["first", "second"] {
+            let observed = Arc::clone(&observed);
+            router.on_connect(move |client_id, grpc_path| {
+                observed
+                    .lock()
+                    .unwrap()
+                    .push((hook, client_id.to_string(), grpc_path.full_path()));
+            });
+        }
+        tokio::spawn(router.run());
+
+        let mut client = client_sides.client(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        );
+        let _conn = client.connect::<Ping, Ping>(ECHO_PATH).await.unwrap();
+
+        let observed = observed.lock().unwrap().clone();
+        assert_eq!(
+            observed,
+            vec![
+                ("first", "client-1".to_string(), ECHO_PATH.to_string()),
+                ("second", "client-1".to_string(), ECHO_PATH.to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_hook_runs_once_per_connection() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+
+        let mut router = server_sides.router(RpcRouterConfig::builder().build());
+        router
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+
+        let disconnected = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let observed = Arc::clone(&disconnected);
+        router.on_disconnect(move |key| observed.lock().unwrap().push(key.clone()));
+        let handle = router.handle();
+        tokio::spawn(router.run());
+
+        let mut client = client_sides.client(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        );
+        let mut conn = client.connect::<Ping, Ping>(ECHO_PATH).await.unwrap();
+
+        let request = Ping {
+            text: "hello".to_string(),
+        };
+        conn.send(request.clone()).await.unwrap();
+        assert_eq!(conn.next().await.unwrap().unwrap(), request);
+        assert!(disconnected.lock().unwrap().is_empty());
+
+        conn.close();
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while handle.active_sessions() > 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            *disconnected.lock().unwrap(),
+            vec![SessionKey::new("client-1", ECHO_PATH)]
+        );
+    }
+
+    /// Collects everything written by a `tracing_subscriber::fmt` subscriber.
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_events_carry_connection_span() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        // The current-thread test runtime runs the handler task on this thread
+        let _default = tracing::subscriber::set_default(subscriber);
+
+        let (client_sides, server_sides) = crate::testing::loopback();
+
+        let mut router = server_sides.router(RpcRouterConfig::builder().build());
+        router
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                tracing::info!("Connector called");
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+        tokio::spawn(router.run());
+
+        let mut client = client_sides.client(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        );
+        let request = Ping {
+            text: "hello".to_string(),
+        };
+        let response: Ping = client.unary(ECHO_PATH, request.clone()).await.unwrap();
+        assert_eq!(response, request);
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let line = logs
+            .lines()
+            .find(|line| line.contains("Connector called"))
+            .expect("connector event should be logged");
+        assert!(line.contains("rpc_handler{client_id=client-1 grpc_path=test.EchoService/Echo}"));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_announcement_conflicts() {
+        let producer = Arc::new(Origin::produce().producer);
+        let handle = echo_handle(&[ECHO_PATH]);
+        let config = RpcRouterConfig::builder().build();
+        let mut tasks = JoinSet::new();
+        let path = format!("client-1/{ECHO_PATH}");
+
+        let client_broadcast = moq_lite::Broadcast::produce();
+        RpcRouter::handle_announcement(
+            &producer,
+            &handle,
+            &config,
+            None,
+            &mut tasks,
+            &path,
+            client_broadcast.consumer.clone(),
+        )
+        .unwrap();
+
+        let result = RpcRouter::handle_announcement(
+            &producer,
+            &handle,
+            &config,
+            None,
+            &mut tasks,
+            &path,
+            client_broadcast.consumer,
+        );
+        assert!(matches!(
+            result,
+            Err(RpcServerError::BroadcastConflict { path: conflict }) if conflict == path
+        ));
+        assert_eq!(handle.active_sessions(), 1);
+        assert_eq!(
+            handle.active_session_keys(),
+            vec![SessionKey::new("client-1", ECHO_PATH)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_announcement_replaces_session() {
+        let producer = Arc::new(Origin::produce().producer);
+        let handle = echo_handle(&[ECHO_PATH]);
+        let config = RpcRouterConfig::builder().replace_sessions(true).build();
+        let mut tasks = JoinSet::new();
+        let path = format!("client-1/{ECHO_PATH}");
+
+        let client_broadcast = moq_lite::Broadcast::produce();
+        for _ in 0..2 {
+            RpcRouter::handle_announcement(
+                &producer,
+                &handle,
+                &config,
+                None,
+                &mut tasks,
+                &path,
+                client_broadcast.consumer.clone(),
+            )
+            .unwrap();
+        }
+        assert_eq!(handle.active_sessions(), 1);
+
+        // The replaced connection stops being served, while its replacement carries on
+        tokio::time::timeout(Duration::from_secs(1), tasks.join_next())
+            .await
+            .expect("replaced handler should stop")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            handle.active_session_keys(),
+            vec![SessionKey::new("client-1", ECHO_PATH)]
+        );
+        assert!(producer.consume().consume_broadcast(&path).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_session_replaced_while_connecting() {
+        let producer = Arc::new(Origin::produce().producer);
+        let handle = RpcRouterHandle::new("primary");
+        // A backend which never answers the connection
+        handle
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, _inbound| {
+                futures::future::pending::<Result<futures::stream::Pending<_>, Status>>()
+            })
+            .unwrap();
+        let config = RpcRouterConfig::builder().replace_sessions(true).build();
+        let mut tasks = JoinSet::new();
+        let path = format!("client-1/{ECHO_PATH}");
+
+        let client_broadcast = moq_lite::Broadcast::produce();
+        for _ in 0..2 {
+            RpcRouter::handle_announcement(
+                &producer,
+                &handle,
+                &config,
+                None,
+                &mut tasks,
+                &path,
+                client_broadcast.consumer.clone(),
+            )
+            .unwrap();
+        }
+
+        // The replaced connection stops waiting on its backend, while its replacement carries on
+        tokio::time::timeout(Duration::from_secs(1), tasks.join_next())
+            .await
+            .expect("replaced handler should stop")
+            .unwrap()
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(handle.active_sessions(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_lists_registered_paths() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+
+        let mut router =
+            server_sides.router(RpcRouterConfig::builder().enable_health(true).build());
+        router
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+        tokio::spawn(router.run());
+
+        let mut client = client_sides.client(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        );
+        let response: crate::HealthCheckResponse = client
+            .unary(HEALTH_PATH, crate::HealthCheckRequest::default())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, crate::HealthCheckResponse::SERVING);
+        assert_eq!(response.registered_paths, vec![ECHO_PATH.to_string()]);
+        // The health check itself is the only active session
+        assert_eq!(response.active_sessions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_does_not_shadow_registered_handler() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+
+        let mut router =
+            server_sides.router(RpcRouterConfig::builder().enable_health(true).build());
+        router
+            .register::<Ping, Ping, _, _, _>(HEALTH_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(|_| {
+                    Ok(Ping {
+                        text: "custom".to_string(),
+                    })
+                }))
+            })
+            .unwrap();
+        tokio::spawn(router.run());
+
+        let mut client = client_sides.client(
+            RpcClientConfig::builder()
+                .client_id("client-1".to_string())
+                .timeout(Duration::from_secs(5))
+                .build(),
+        );
+        let response: Ping = client.unary(HEALTH_PATH, Ping::default()).await.unwrap();
+
+        assert_eq!(response.text, "custom");
+    }
+
+    #[tokio::test]
+    async fn test_pool_distributes_connections_by_weight() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+        let client_producer = Arc::clone(&client_sides.producer);
+        let client_consumer = client_sides.consumer;
+
+        let served = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+        let mut router = server_sides.router(RpcRouterConfig::builder().build());
+        router
+            .register_pool::<Ping, Ping, _, _, _>(
+                ECHO_PATH,
+                [(1, 0), (3, 1)].map(|(weight, backend)| {
+                    let served = Arc::clone(&served);
+                    (weight, move |_client_id, inbound: DecodedInbound<Ping>| {
+                        served[backend].fetch_add(1, Ordering::Relaxed);
+                        async move { Ok(inbound.into_ok_stream().map(Ok)) }
+                    })
+                }),
+            )
+            .unwrap();
+        tokio::spawn(router.run());
+
+        for i in 0..40 {
+            let mut client = RpcClient::new(
+                Arc::clone(&client_producer),
+                client_consumer.clone(),
+                RpcClientConfig::builder()
+                    .client_id(format!("client-{i}"))
+                    .timeout(Duration::from_secs(5))
+                    .build(),
+            );
+            let request = Ping {
+                text: "hello".to_string(),
+            };
+            let response: Ping = client.unary(ECHO_PATH, request.clone()).await.unwrap();
+            assert_eq!(response, request);
+        }
+
+        let light = served[0].load(Ordering::Relaxed);
+        let heavy = served[1].load(Ordering::Relaxed);
+        assert_eq!(light + heavy, 40);
+        assert!((8..=12).contains(&light), "light backend served {light}");
+        assert!((28..=32).contains(&heavy), "heavy backend served {heavy}");
+    }
+
+    #[test]
+    fn test_pool_without_weighted_backends_is_rejected() {
+        let (_client_sides, server_sides) = crate::testing::loopback();
+        let mut router = server_sides.router(RpcRouterConfig::builder().build());
+
+        let result = router.register_pool::<Ping, Ping, _, _, _>(
+            ECHO_PATH,
+            [(0, |_client_id, inbound: DecodedInbound<Ping>| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })],
+        );
+
+        assert!(matches!(result, Err(RpcServerError::EmptyPool(path)) if path == ECHO_PATH));
+        assert!(!router.has_handler(ECHO_PATH));
+    }
+
+    #[test]
+    fn test_conflicting_channels_are_rejected() {
+        let (_client_sides, server_sides) = crate::testing::loopback();
+        let config = RpcRouterConfig::builder()
+            .track_name("requests".to_string())
+            .build();
+        let mut router = server_sides.router(config);
+        let mut register = |channel: &str| {
+            router.register_channel::<Ping, Ping, _, _, _>(
+                ECHO_PATH,
+                channel,
+                |_client_id, inbound| async move { Ok(inbound.into_ok_stream().map(Ok)) },
+            )
+        };
+
+        assert!(register("control").is_ok());
+        assert!(matches!(
+            register("control"),
+            Err(RpcServerError::DuplicateChannel { channel, .. }) if channel == "control"
+        ));
+        assert!(matches!(
+            register("requests"),
+            Err(RpcServerError::ReservedChannel { channel, .. }) if channel == "requests"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_connection_limit_declines_new_sessions() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+        let client_producer = Arc::clone(&client_sides.producer);
+        let client_consumer = client_sides.consumer;
+
+        let mut router = server_sides.router(RpcRouterConfig::builder().max_connections(1).build());
+        router
+            .register::<Ping, Ping, _, _, _>(ECHO_PATH, |_client_id, inbound| async move {
+                Ok(inbound.into_ok_stream().map(Ok))
+            })
+            .unwrap();
+        let handle = router.handle();
+        tokio::spawn(router.run());
+
+        let client = |client_id: &str| {
+            RpcClient::new(
+                Arc::clone(&client_producer),
+                client_consumer.clone(),
+                RpcClientConfig::builder()
+                    .client_id(client_id.to_string())
+                    .timeout(Duration::from_millis(200))
+                    .build(),
+            )
+        };
+
+        let mut first = client("client-1");
+        let mut conn = first.connect::<Ping, Ping>(ECHO_PATH).await.unwrap();
+        let request = Ping {
+            text: "hello".to_string(),
+        };
+        conn.send(request.clone()).await.unwrap();
+        assert_eq!(conn.next().await.unwrap().unwrap(), request);
+
+        let mut second = client("client-2");
+        let result = second.connect::<Ping, Ping>(ECHO_PATH).await;
+        assert!(matches!(result, Err(RpcClientError::Timeout(_))));
+        assert_eq!(handle.active_sessions(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_declines_rapid_announcements() {
+        let (client_sides, server_sides) = crate::testing::loopback();
+        let client_producer = Arc::clone(&client_sides.producer);
+        let client_consumer = client_sides.consumer;
+
+        let mut router = server_sides.router(
+            RpcRouterConfig::builder()
+                .rate_limit(RateLimit::new(2, Duration::from_secs(60)).unwrap())
+                .build(),
+        );
+        router
+            .register::<Ping, Ping, _, _, _>(
+                "test.EchoService/*",
+                |_client_id, inbound| async move { Ok(inbound.into_ok_stream().map(Ok)) },
+            )
+            .unwrap();
+        tokio::spawn(router.run());
+
+        let client = |client_id: &str| {
+            RpcClient::new(
+                Arc::clone(&client_producer),
+                client_consumer.clone(),
+                RpcClientConfig::builder()
+                    .client_id(client_id.to_string())
+                    .timeout(Duration::from_millis(200))
+                    .build(),
+            )
+        };
+
+        // Each announcement is a distinct method, so only the rate limit can decline it
+        let mut flapping = client("client-1");
+        let mut conns = Vec::new();
+        for method in ["A", "B"] {
+            let path = format!("test.EchoService/{method}");
+            conns.push(flapping.connect::<Ping, Ping>(&path).await.unwrap());
+        }
+        let result = flapping.connect::<Ping, Ping>("test.EchoService/C").await;
+        assert!(matches!(result, Err(RpcClientError::Timeout(_))));
+
+        // Other clients have their own budget
+        let mut other = client("client-2");
+        other
+            .connect::<Ping, Ping>("test.EchoService/C")
+            .await
+            .unwrap();
     }
 }
@@ -1,6 +1,9 @@
 use dashmap::DashMap;
 use std::fmt;
+use std::future::Future;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio_util::sync::CancellationToken;
 
 use crate::error::RpcServerError;
 
@@ -18,6 +21,16 @@ impl SessionKey {
             grpc_path: grpc_path.into(),
         }
     }
+
+    /// Get the client ID.
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    /// Get the gRPC path.
+    pub fn grpc_path(&self) -> &str {
+        &self.grpc_path
+    }
 }
 
 impl fmt::Display for SessionKey {
@@ -33,13 +46,22 @@ impl fmt::Display for SessionKey {
 /// that automatically removes the session when dropped.
 #[derive(Debug)]
 pub struct SessionMap {
-    sessions: DashMap<SessionKey, (), ahash::RandomState>,
+    sessions: DashMap<SessionKey, SessionEntry, ahash::RandomState>,
+    // Distinguishes a session from any that replaced it under the same key
+    next_generation: AtomicU64,
+}
+
+#[derive(Debug)]
+struct SessionEntry {
+    generation: u64,
+    replaced: CancellationToken,
 }
 
 impl SessionMap {
     pub fn new() -> Self {
         Self {
             sessions: DashMap::default(),
+            next_generation: AtomicU64::new(0),
         }
     }
 
@@ -55,20 +77,74 @@ impl SessionMap {
                 grpc_path: key.grpc_path,
             }),
             Entry::Vacant(slot) => {
-                slot.insert(());
-                Ok(SessionGuard {
-                    key,
-                    map: Arc::clone(self),
-                })
+                let (entry, guard) = self.new_session(key);
+                slot.insert(entry);
+                Ok(guard)
             }
         }
     }
 
+    /// Create a new session, replacing any existing session for this key.
+    ///
+    /// Unlike [`try_create`](Self::try_create) this never fails, which allows a client whose
+    /// transport dropped to reconnect before its previous session has been torn down. Returns the
+    /// new guard and whether an existing session was replaced.
+    ///
+    /// A replaced session is invalidated: its guard reports [`is_replaced`](SessionGuard::is_replaced)
+    /// and its [`replaced`](SessionGuard::replaced) future resolves, signalling its owner to drop
+    /// it. Dropping the old guard no longer removes the session for the key.
+    ///
+    /// The router creates sessions this way when configured with
+    /// [`replace_sessions`](crate::RpcRouterConfig::replace_sessions).
+    pub fn try_create_or_replace(self: &Arc<Self>, key: SessionKey) -> (SessionGuard, bool) {
+        use dashmap::mapref::entry::Entry;
+
+        let (entry, guard) = self.new_session(key.clone());
+        let replaced = match self.sessions.entry(key) {
+            Entry::Occupied(mut slot) => {
+                slot.insert(entry).replaced.cancel();
+                true
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(entry);
+                false
+            }
+        };
+
+        (guard, replaced)
+    }
+
+    fn new_session(self: &Arc<Self>, key: SessionKey) -> (SessionEntry, SessionGuard) {
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        let replaced = CancellationToken::new();
+
+        let entry = SessionEntry {
+            generation,
+            replaced: replaced.clone(),
+        };
+        let guard = SessionGuard {
+            key,
+            generation,
+            replaced,
+            map: Arc::clone(self),
+        };
+
+        (entry, guard)
+    }
+
     /// Check if a session exists for the given key.
     pub fn contains(&self, key: &SessionKey) -> bool {
         self.sessions.contains_key(key)
     }
 
+    /// Get a snapshot of the keys of all active sessions, in no particular order.
+    pub fn active_keys(&self) -> Vec<SessionKey> {
+        self.sessions
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
     /// Get the number of active sessions.
     pub fn len(&self) -> usize {
         self.sessions.len()
@@ -80,8 +156,11 @@ impl SessionMap {
     }
 
     /// Remove a session directly (used internally by SessionGuard).
-    fn remove(&self, key: &SessionKey) {
-        self.sessions.remove(key);
+    ///
+    /// Does nothing if the session has since been replaced by another generation.
+    fn remove(&self, key: &SessionKey, generation: u64) {
+        self.sessions
+            .remove_if(key, |_, entry| entry.generation == generation);
     }
 }
 
@@ -94,6 +173,8 @@ impl Default for SessionMap {
 /// A guard that holds an active session. When dropped, the session is removed.
 pub struct SessionGuard {
     key: SessionKey,
+    generation: u64,
+    replaced: CancellationToken,
     map: Arc<SessionMap>,
 }
 
@@ -112,11 +193,23 @@ impl SessionGuard {
     pub fn grpc_path(&self) -> &str {
         &self.key.grpc_path
     }
+
+    /// Check whether the session has been replaced by
+    /// [`try_create_or_replace`](SessionMap::try_create_or_replace).
+    pub fn is_replaced(&self) -> bool {
+        self.replaced.is_cancelled()
+    }
+
+    /// Wait until the session has been replaced by
+    /// [`try_create_or_replace`](SessionMap::try_create_or_replace).
+    pub fn replaced(&self) -> impl Future<Output = ()> + Send + '_ {
+        self.replaced.cancelled()
+    }
 }
 
 impl Drop for SessionGuard {
     fn drop(&mut self) {
-        self.map.remove(&self.key);
+        self.map.remove(&self.key, self.generation);
     }
 }
 
@@ -124,12 +217,15 @@ impl fmt::Debug for SessionGuard {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SessionGuard")
             .field("key", &self.key)
+            .field("generation", &self.generation)
             .finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use futures::FutureExt;
+
     use super::*;
 
     #[test]
@@ -197,4 +293,53 @@ mod tests {
         let _guard = map.try_create(key).unwrap();
         assert_eq!(map.len(), 1);
     }
+
+    #[test]
+    fn test_create_or_replace_new_session() {
+        let map = Arc::new(SessionMap::new());
+        let key = SessionKey::new("drone-1", "drone.EchoService/Echo");
+
+        let (guard, replaced) = map.try_create_or_replace(key.clone());
+        assert!(!replaced);
+        assert!(!guard.is_replaced());
+        assert!(map.contains(&key));
+    }
+
+    #[test]
+    fn test_create_or_replace_invalidates_old_session() {
+        let map = Arc::new(SessionMap::new());
+        let key = SessionKey::new("drone-1", "drone.EchoService/Echo");
+
+        let old = map.try_create(key.clone()).unwrap();
+        let (new, replaced) = map.try_create_or_replace(key.clone());
+        assert!(replaced);
+        assert!(old.is_replaced());
+        assert!(old.replaced().now_or_never().is_some());
+        assert!(!new.is_replaced());
+        assert_eq!(map.len(), 1);
+
+        // Dropping the replaced guard leaves the new session in place
+        drop(old);
+        assert!(map.contains(&key));
+
+        drop(new);
+        assert!(!map.contains(&key));
+    }
+
+    #[test]
+    fn test_active_keys() {
+        let map = Arc::new(SessionMap::new());
+        let key1 = SessionKey::new("drone-1", "drone.EchoService/Echo");
+        let key2 = SessionKey::new("drone-1", "drone.CommandService/Execute");
+
+        assert!(map.active_keys().is_empty());
+
+        let _guard1 = map.try_create(key1.clone()).unwrap();
+        let _guard2 = map.try_create(key2.clone()).unwrap();
+
+        let mut keys = map.active_keys();
+        keys.sort_by(|a, b| a.grpc_path().cmp(b.grpc_path()));
+        assert_eq!(keys, vec![key2, key1]);
+        assert_eq!(keys[0].client_id(), "drone-1");
+    }
 }
@@ -4,11 +4,13 @@ use moq_prototype::connect_bidirectional;
 use moq_prototype::drone::DroneSessionMap;
 use moq_prototype::drone_proto::DronePosition;
 use moq_prototype::grpc::{self, EchoServiceClient};
+use moq_prototype::shutdown;
 use moq_prototype::unit_context::UnitContext;
 use moq_prototype::unit_map::UnitMap;
 use rpcmoq_lite::DecodedInbound;
 use rpcmoq_lite::{RpcRouter, RpcRouterConfig};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{error, info};
 
 const GRPC_ADDR: &str = "[::1]:50051";
@@ -22,21 +24,33 @@ async fn main() -> Result<()> {
     let unit_map: Arc<UnitMap<UnitContext>> = Arc::new(UnitMap::new());
     let session_map: Arc<DroneSessionMap> = Arc::new(DroneSessionMap::new());
 
+    let mut server_config = grpc::ServerConfig::default();
+    if let Ok(poll_interval_ms) = std::env::var("POLL_INTERVAL_MS") {
+        server_config.poll_interval = Duration::from_millis(poll_interval_ms.parse()?);
+    }
+
     let grpc_addr = GRPC_ADDR.parse()?;
     let server_unit_map = Arc::clone(&unit_map);
     let server_session_map = Arc::clone(&session_map);
     tokio::spawn(async move {
-        if let Err(e) = grpc::start_server(grpc_addr, server_unit_map, server_session_map).await {
+        if let Err(e) = grpc::start_server_with(
+            grpc_addr,
+            server_unit_map,
+            server_session_map,
+            server_config,
+        )
+        .await
+        {
             error!("gRPC server error: {e}");
         }
     });
 
     // Wait for server to start
-    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
 
     info!("Server connecting to relay at {url}");
 
-    let (_session, producer, consumer) = connect_bidirectional(&url).await?;
+    let (session, producer, consumer) = connect_bidirectional(&url).await?;
     let producer = Arc::new(producer);
 
     let config = RpcRouterConfig::builder()
@@ -45,6 +59,8 @@ async fn main() -> Result<()> {
         .client_prefix("drone".to_string())
         .response_prefix("server".to_string())
         .track_name(PRIMARY_TRACK.to_string())
+        // Drones whose transport drops reconnect before their previous session is torn down
+        .replace_sessions(true)
         .build();
 
     let mut router = RpcRouter::new(consumer.clone(), producer.clone(), config);
@@ -59,14 +75,18 @@ async fn main() -> Result<()> {
                 .await
                 .inspect_err(|e| tracing::error!(?e))
                 .map_err(|e| tonic::Status::internal(e.to_string()))?;
-            let response = client.echo(inbound).await?;
+            let response = client.echo(inbound.into_ok_stream()).await?;
             Ok(response.into_inner())
         },
     )?;
 
     info!("Waiting for drones to connect...");
 
-    router.run().await?;
+    router.run_until(shutdown::shutdown_signal()).await?;
+
+    // The router has dropped its response broadcasts, unannouncing them
+    shutdown::end_sessions(&session_map);
+    shutdown::close_relay(session);
 
     Ok(())
 }
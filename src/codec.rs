@@ -0,0 +1,106 @@
+//! Codecs for the payload of frames published over MoQ.
+//!
+//! Frames are encoded as protobuf with [`ProstCodec`] by default. With the `serde` feature
+//! enabled, [`JsonCodec`] encodes frames as JSON instead, which is easier to inspect with web
+//! tooling while debugging.
+
+/// Indicates that a frame could not be decoded.
+#[derive(Debug, thiserror::Error)]
+pub enum CodecError {
+    #[error("failed to decode protobuf frame")]
+    Prost(#[from] prost::DecodeError),
+    #[cfg(feature = "serde")]
+    #[error("failed to decode JSON frame")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Encodes values of type `T` into frame payloads and decodes them back.
+pub trait FrameCodec<T> {
+    /// Encode `value` into a frame payload.
+    fn encode(&self, value: &T) -> Vec<u8>;
+
+    /// Decode a value from the frame payload `bytes`.
+    fn decode(&self, bytes: &[u8]) -> Result<T, CodecError>;
+}
+
+/// A [`FrameCodec`] encoding frames in the protobuf wire format.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProstCodec;
+
+impl<T: prost::Message + Default> FrameCodec<T> for ProstCodec {
+    fn encode(&self, value: &T) -> Vec<u8> {
+        value.encode_to_vec()
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, CodecError> {
+        Ok(T::decode(bytes)?)
+    }
+}
+
+/// A [`FrameCodec`] encoding frames as JSON.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> FrameCodec<T> for JsonCodec {
+    /// # Panics
+    /// Panics if the `Serialize` implementation of `T` fails, which derived implementations of
+    /// plain data types never do.
+    fn encode(&self, value: &T) -> Vec<u8> {
+        serde_json::to_vec(value).expect("value should serialize to JSON")
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, CodecError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry_proto::SensorData;
+
+    fn sensor_data() -> SensorData {
+        SensorData {
+            sensor_id: "thermometer-1".to_string(),
+            temperature: 21.5,
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_prost_codec_round_trip() {
+        let data = sensor_data();
+
+        let bytes = ProstCodec.encode(&data);
+
+        let decoded: SensorData = ProstCodec.decode(&bytes).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_prost_codec_rejects_malformed() {
+        let result: Result<SensorData, _> = ProstCodec.decode(&[0xff]);
+        assert!(matches!(result, Err(CodecError::Prost(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_codec_round_trip() {
+        let data = sensor_data();
+
+        let bytes = JsonCodec.encode(&data);
+        assert!(bytes.starts_with(b"{"));
+
+        let decoded: SensorData = JsonCodec.decode(&bytes).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_codec_rejects_malformed() {
+        let result: Result<SensorData, _> = JsonCodec.decode(b"not json");
+        assert!(matches!(result, Err(CodecError::Json(_))));
+    }
+}
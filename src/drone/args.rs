@@ -0,0 +1,112 @@
+//! Validated arguments for running a drone.
+
+use url::Url;
+use uuid::Uuid;
+
+use super::error::DroneArgsError;
+
+/// The relay URL used when `RELAY_URL` is not set.
+pub const DEFAULT_RELAY_URL: &str = "https://localhost:4443";
+
+/// The URL schemes a relay can be reached over.
+const SUPPORTED_SCHEMES: [&str; 2] = ["https", "moq"];
+
+/// The arguments a drone runs with, validated before connecting to the relay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroneArgs {
+    pub relay_url: Url,
+    pub drone_id: String,
+}
+
+impl DroneArgs {
+    /// Validate `relay_url` and `drone_id`.
+    pub fn new(relay_url: &str, drone_id: impl Into<String>) -> Result<Self, DroneArgsError> {
+        let url = Url::parse(relay_url).map_err(|source| DroneArgsError::InvalidUrl {
+            url: relay_url.to_string(),
+            source,
+        })?;
+
+        if !SUPPORTED_SCHEMES.contains(&url.scheme()) {
+            return Err(DroneArgsError::UnsupportedScheme {
+                url: relay_url.to_string(),
+                scheme: url.scheme().to_string(),
+            });
+        }
+
+        let drone_id = drone_id.into();
+        if drone_id.trim().is_empty() {
+            return Err(DroneArgsError::EmptyDroneId);
+        }
+
+        Ok(Self {
+            relay_url: url,
+            drone_id,
+        })
+    }
+
+    /// Read the arguments from the `RELAY_URL` and `DRONE_ID` environment variables.
+    ///
+    /// An unset `RELAY_URL` falls back to [`DEFAULT_RELAY_URL`], and an unset `DRONE_ID` to a
+    /// random UUID. Variables which are set are validated as with [`new`](Self::new).
+    pub fn from_env() -> Result<Self, DroneArgsError> {
+        let relay_url =
+            std::env::var("RELAY_URL").unwrap_or_else(|_| DEFAULT_RELAY_URL.to_string());
+        let drone_id = std::env::var("DRONE_ID").unwrap_or_else(|_| Uuid::new_v4().to_string());
+
+        Self::new(&relay_url, drone_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_supported_schemes() {
+        for url in [
+            "https://localhost:4443",
+            "https://relay.example.com",
+            "moq://relay:4443",
+        ] {
+            let args = DroneArgs::new(url, "drone-1").unwrap();
+            assert_eq!(args.drone_id, "drone-1");
+        }
+    }
+
+    #[test]
+    fn test_default_relay_url_is_valid() {
+        DroneArgs::new(DEFAULT_RELAY_URL, "drone-1").unwrap();
+    }
+
+    #[test]
+    fn test_rejects_malformed_url() {
+        for url in ["", "localhost:4443", "https://", "not a url"] {
+            let err = DroneArgs::new(url, "drone-1").unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    DroneArgsError::InvalidUrl { .. } | DroneArgsError::UnsupportedScheme { .. }
+                ),
+                "{url:?} gave {err:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_unsupported_scheme() {
+        let err = DroneArgs::new("http://localhost:4443", "drone-1").unwrap_err();
+
+        assert!(matches!(
+            err,
+            DroneArgsError::UnsupportedScheme { scheme, .. } if scheme == "http"
+        ));
+    }
+
+    #[test]
+    fn test_rejects_empty_drone_id() {
+        for drone_id in ["", "  "] {
+            let err = DroneArgs::new(DEFAULT_RELAY_URL, drone_id).unwrap_err();
+            assert!(matches!(err, DroneArgsError::EmptyDroneId));
+        }
+    }
+}
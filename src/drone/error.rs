@@ -1,12 +1,32 @@
 //! Error types for drone session management.
 
+use super::DroneSessionId;
 use crate::unit::UnitId;
 
 /// Indicates that a drone session could not be created because one already exists.
 #[derive(Debug, thiserror::Error)]
-#[error("drone {unit_id} already has an active session")]
+#[error("drone {unit_id} already has an active session ({session_id})")]
 pub struct SessionAlreadyActive {
     pub unit_id: UnitId,
+    /// The id of the session which is already active.
+    pub session_id: DroneSessionId,
+}
+
+/// Indicates that a drone session could not be created because the maximum number of concurrent
+/// sessions are already active.
+#[derive(Debug, thiserror::Error)]
+#[error("maximum number of active drone sessions ({max}) reached")]
+pub struct SessionCapacityReached {
+    pub max: usize,
+}
+
+/// Indicates why a drone session could not be created.
+#[derive(Debug, thiserror::Error)]
+pub enum CreateSessionError {
+    #[error(transparent)]
+    AlreadyActive(#[from] SessionAlreadyActive),
+    #[error(transparent)]
+    CapacityReached(#[from] SessionCapacityReached),
 }
 
 /// Indicates that a drone session could not be found for removal or query.
@@ -15,3 +35,18 @@ pub struct SessionAlreadyActive {
 pub struct SessionNotFound {
     pub unit_id: UnitId,
 }
+
+/// Indicates that the arguments given to a drone are invalid.
+#[derive(Debug, thiserror::Error)]
+pub enum DroneArgsError {
+    #[error("relay URL '{url}' is not a valid URL")]
+    InvalidUrl {
+        url: String,
+        #[source]
+        source: url::ParseError,
+    },
+    #[error("relay URL '{url}' must use the https or moq scheme, not '{scheme}'")]
+    UnsupportedScheme { url: String, scheme: String },
+    #[error("drone id must not be empty")]
+    EmptyDroneId,
+}
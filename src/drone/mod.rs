@@ -1,14 +1,25 @@
+pub mod args;
 pub mod error;
 
+use crate::metrics::SessionMetrics;
 use crate::unit::UnitId;
 use dashmap::{DashMap, Entry};
 use std::fmt;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::info;
 use uuid::Uuid;
 
-use self::error::{SessionAlreadyActive, SessionNotFound};
+use self::error::{
+    CreateSessionError, SessionAlreadyActive, SessionCapacityReached, SessionNotFound,
+};
 
-#[derive(Clone, Hash, PartialEq, Eq)]
+/// The id of a [`DroneSession`].
+///
+/// Ids are ordered by their underlying [`Uuid`], which allows sorting them for stable output.
+#[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DroneSessionId(Arc<Uuid>);
 
 impl DroneSessionId {
@@ -16,6 +27,11 @@ impl DroneSessionId {
         Self(Arc::new(Uuid::new_v4()))
     }
 
+    /// Reconstruct the id of a session from its `uuid`, e.g. as previously persisted.
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self(Arc::new(uuid))
+    }
+
     pub fn as_uuid(&self) -> &Uuid {
         &self.0
     }
@@ -37,43 +53,293 @@ impl fmt::Display for DroneSessionId {
 pub struct DroneSession {
     pub session_id: DroneSessionId,
     pub unit_id: UnitId,
+    /// The last time activity was observed for this session.
+    pub last_seen: Instant,
+    /// When the drone was seen to depart, if its session is waiting out the grace period before
+    /// being removed. See [`DroneSessionMap::begin_draining`].
+    pub draining_since: Option<Instant>,
+}
+
+/// A lifecycle event emitted by [`DroneSessionMap`] when sessions are created or removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    Created {
+        unit_id: UnitId,
+        session_id: DroneSessionId,
+    },
+    Removed {
+        unit_id: UnitId,
+        session_id: DroneSessionId,
+    },
 }
 
+/// The number of [`SessionEvent`]s buffered for each subscriber before the oldest are dropped.
+const SESSION_EVENT_CAPACITY: usize = 64;
+
+/// How long a departed drone's session is kept for it to reconnect to, unless configured with
+/// [`DroneSessionMap::with_drain_grace`].
+pub const DEFAULT_DRAIN_GRACE: Duration = Duration::from_secs(5);
+
 #[derive(Debug)]
 pub struct DroneSessionMap {
     sessions: DashMap<UnitId, DroneSession, ahash::RandomState>,
+    events: broadcast::Sender<SessionEvent>,
+    max_sessions: Option<usize>,
+    /// The number of sessions in `sessions`, counted separately so slots can be reserved against
+    /// `max_sessions` atomically.
+    session_count: AtomicUsize,
+    drain_grace: Duration,
 }
 
 impl DroneSessionMap {
     pub fn new() -> Self {
         Self {
             sessions: DashMap::default(),
+            events: broadcast::Sender::new(SESSION_EVENT_CAPACITY),
+            max_sessions: None,
+            session_count: AtomicUsize::new(0),
+            drain_grace: DEFAULT_DRAIN_GRACE,
+        }
+    }
+
+    /// Construct a [`DroneSessionMap`] which allows at most `max` concurrently active sessions.
+    pub fn with_capacity(max: usize) -> Self {
+        Self {
+            max_sessions: Some(max),
+            ..Self::new()
         }
     }
 
-    pub fn create_session(&self, unit_id: &UnitId) -> Result<DroneSessionId, SessionAlreadyActive> {
+    /// Keep the sessions of departed drones for `grace` before they are removed, see
+    /// [`begin_draining`](Self::begin_draining).
+    pub fn with_drain_grace(mut self, grace: Duration) -> Self {
+        self.drain_grace = grace;
+        self
+    }
+
+    /// Subscribe to [`SessionEvent`]s for sessions created or removed after this call.
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, event: SessionEvent) {
+        // Sending only fails when there are no subscribers, in which case no one is listening.
+        let _ = self.events.send(event);
+    }
+
+    pub fn create_session(&self, unit_id: &UnitId) -> Result<DroneSessionId, CreateSessionError> {
+        self.create_session_at(unit_id, Instant::now())
+    }
+
+    /// Create a session for `unit_id` at the time `now`.
+    ///
+    /// If the unit's previous session is draining and its grace period has not passed at `now`,
+    /// that session is resumed and its id returned. A session whose grace period has passed is
+    /// replaced by a new one, even if it has not been reaped yet.
+    pub fn create_session_at(
+        &self,
+        unit_id: &UnitId,
+        now: Instant,
+    ) -> Result<DroneSessionId, CreateSessionError> {
         match self.sessions.entry(unit_id.clone()) {
-            Entry::Occupied(_) => Err(SessionAlreadyActive {
+            Entry::Occupied(mut entry) if entry.get().draining_since.is_some() => {
+                let session = entry.get_mut();
+                if !self.is_drained(session, now) {
+                    // The drone reconnected within the grace period, so its session carries on
+                    session.draining_since = None;
+                    session.last_seen = session.last_seen.max(now);
+                    return Ok(session.session_id.clone());
+                }
+
+                let session_id = DroneSessionId::generate();
+                let drained = entry.insert(DroneSession {
+                    session_id: session_id.clone(),
+                    unit_id: unit_id.clone(),
+                    last_seen: now,
+                    draining_since: None,
+                });
+                self.emit_removed(&drained);
+                self.emit(SessionEvent::Created {
+                    unit_id: unit_id.clone(),
+                    session_id: session_id.clone(),
+                });
+                Ok(session_id)
+            }
+            Entry::Occupied(entry) => Err(SessionAlreadyActive {
                 unit_id: unit_id.clone(),
-            }),
+                session_id: entry.get().session_id.clone(),
+            }
+            .into()),
             Entry::Vacant(slot) => {
+                self.reserve_slot()?;
                 let session_id = DroneSessionId::generate();
                 slot.insert(DroneSession {
                     session_id: session_id.clone(),
                     unit_id: unit_id.clone(),
+                    last_seen: now,
+                    draining_since: None,
+                });
+                self.emit(SessionEvent::Created {
+                    unit_id: unit_id.clone(),
+                    session_id: session_id.clone(),
                 });
                 Ok(session_id)
             }
         }
     }
 
+    /// Reserve a slot for a new session, failing if every slot allowed by `max_sessions` is taken.
+    fn reserve_slot(&self) -> Result<(), SessionCapacityReached> {
+        let reserved = self.session_count.fetch_add(1, Ordering::AcqRel);
+        if let Some(max) = self.max_sessions
+            && reserved >= max
+        {
+            self.session_count.fetch_sub(1, Ordering::AcqRel);
+            return Err(SessionCapacityReached { max });
+        }
+        Ok(())
+    }
+
+    /// Release the slot of `session` after removing it from the map.
+    fn session_removed(&self, session: &DroneSession) {
+        self.session_count.fetch_sub(1, Ordering::AcqRel);
+        self.emit_removed(session);
+    }
+
     pub fn remove_session(&self, unit_id: &UnitId) -> Result<DroneSession, SessionNotFound> {
-        self.sessions
+        let (_, session) = self
+            .sessions
             .remove(unit_id)
-            .map(|(_, session)| session)
             .ok_or_else(|| SessionNotFound {
                 unit_id: unit_id.clone(),
+            })?;
+
+        self.session_removed(&session);
+        Ok(session)
+    }
+
+    fn emit_removed(&self, session: &DroneSession) {
+        self.emit(SessionEvent::Removed {
+            unit_id: session.unit_id.clone(),
+            session_id: session.session_id.clone(),
+        });
+    }
+
+    /// Record activity for the session of `unit_id` at the time `now`.
+    pub fn touch(&self, unit_id: &UnitId, now: Instant) -> Result<(), SessionNotFound> {
+        let mut session = self
+            .sessions
+            .get_mut(unit_id)
+            .ok_or_else(|| SessionNotFound {
+                unit_id: unit_id.clone(),
+            })?;
+
+        session.last_seen = session.last_seen.max(now);
+        Ok(())
+    }
+
+    /// Remove and return every session which has not been seen within `ttl` of `now`.
+    ///
+    /// This allows drones which disconnected without a clean teardown to create a new session.
+    pub fn reap_expired(&self, ttl: Duration, now: Instant) -> Vec<DroneSession> {
+        let is_expired =
+            |session: &DroneSession| now.saturating_duration_since(session.last_seen) > ttl;
+
+        let expired: Vec<UnitId> = self
+            .sessions
+            .iter()
+            .filter(|entry| is_expired(entry.value()))
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|unit_id| {
+                self.sessions
+                    .remove_if(&unit_id, |_, session| is_expired(session))
+                    .map(|(_, session)| session)
+            })
+            .inspect(|session| self.session_removed(session))
+            .collect()
+    }
+
+    /// Mark the session `session_id` of `unit_id` as draining after its drone was seen to depart
+    /// at `now`.
+    ///
+    /// A draining session stays active until removed by [`reap_drained`](Self::reap_drained) once
+    /// the grace period has passed. If the drone reconnects before then,
+    /// [`create_session`](Self::create_session) returns the draining session instead of creating a
+    /// new one. Marking an already draining session keeps the time it started draining.
+    ///
+    /// Fails if the unit's current session is not `session_id`, so a departure observed late does
+    /// not drain a newer session.
+    pub fn begin_draining(
+        &self,
+        unit_id: &UnitId,
+        session_id: &DroneSessionId,
+        now: Instant,
+    ) -> Result<(), SessionNotFound> {
+        let mut session = self
+            .sessions
+            .get_mut(unit_id)
+            .filter(|session| session.session_id == *session_id)
+            .ok_or_else(|| SessionNotFound {
+                unit_id: unit_id.clone(),
+            })?;
+
+        session.draining_since.get_or_insert(now);
+        Ok(())
+    }
+
+    pub fn is_draining(&self, unit_id: &UnitId) -> bool {
+        self.sessions
+            .get(unit_id)
+            .is_some_and(|session| session.draining_since.is_some())
+    }
+
+    /// Whether `session` has been draining for longer than the grace period at `now`.
+    fn is_drained(&self, session: &DroneSession, now: Instant) -> bool {
+        session
+            .draining_since
+            .is_some_and(|since| now.saturating_duration_since(since) > self.drain_grace)
+    }
+
+    /// Remove and return every session which has been draining for longer than the grace period
+    /// at `now`.
+    pub fn reap_drained(&self, now: Instant) -> Vec<DroneSession> {
+        let is_drained = |session: &DroneSession| self.is_drained(session, now);
+
+        let drained: Vec<UnitId> = self
+            .sessions
+            .iter()
+            .filter(|entry| is_drained(entry.value()))
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        drained
+            .into_iter()
+            .filter_map(|unit_id| {
+                self.sessions
+                    .remove_if(&unit_id, |_, session| is_drained(session))
+                    .map(|(_, session)| session)
             })
+            .inspect(|session| self.session_removed(session))
+            .collect()
+    }
+
+    /// Remove and return every active session, such as when shutting down.
+    pub fn remove_all(&self) -> Vec<DroneSession> {
+        let unit_ids: Vec<UnitId> = self
+            .sessions
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        unit_ids
+            .into_iter()
+            .filter_map(|unit_id| self.sessions.remove(&unit_id).map(|(_, session)| session))
+            .inspect(|session| self.session_removed(session))
+            .collect()
     }
 
     pub fn has_active_session(&self, unit_id: &UnitId) -> bool {
@@ -89,6 +355,58 @@ impl DroneSessionMap {
     pub fn active_session_count(&self) -> usize {
         self.sessions.len()
     }
+
+    /// Snapshot the counts of the map for export.
+    ///
+    /// The counts are read individually, so they may not be consistent with each other while
+    /// sessions are changing.
+    pub fn metrics(&self) -> SessionMetrics {
+        SessionMetrics {
+            active_sessions: self.active_session_count(),
+            draining_sessions: self
+                .sessions
+                .iter()
+                .filter(|entry| entry.draining_since.is_some())
+                .count(),
+        }
+    }
+
+    /// Snapshot every active session as a `(unit_id, session_id)` pair.
+    ///
+    /// The result is a point-in-time view and may be stale as soon as it is returned, since
+    /// sessions can be created or removed concurrently.
+    pub fn list_active_sessions(&self) -> Vec<(UnitId, DroneSessionId)> {
+        self.sessions
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.session_id.clone()))
+            .collect()
+    }
+}
+
+/// Periodically [reap](DroneSessionMap::reap_drained) the drained sessions of `session_map`
+/// every `interval`, until the map is dropped.
+pub fn spawn_drain_reaper(
+    session_map: &Arc<DroneSessionMap>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    let session_map = Arc::downgrade(session_map);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let Some(session_map) = session_map.upgrade() else {
+                break;
+            };
+            for session in session_map.reap_drained(Instant::now()) {
+                info!(
+                    unit_id = %session.unit_id,
+                    session_id = %session.session_id,
+                    "Drone did not reconnect, session removed"
+                );
+            }
+        }
+    })
 }
 
 impl Default for DroneSessionMap {
@@ -122,7 +440,25 @@ mod tests {
         // Second attempt should fail
         let result = map.create_session(&unit_id);
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), SessionAlreadyActive { .. }));
+        assert!(matches!(
+            result.unwrap_err(),
+            CreateSessionError::AlreadyActive(SessionAlreadyActive { .. })
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_session_error_carries_active_id() {
+        let map = DroneSessionMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        let session_id = map.create_session(&unit_id).unwrap();
+
+        let CreateSessionError::AlreadyActive(err) = map.create_session(&unit_id).unwrap_err()
+        else {
+            panic!("expected an already active error");
+        };
+        assert_eq!(err.session_id, session_id);
+        assert!(err.to_string().contains(&session_id.to_string()));
     }
 
     #[test]
@@ -137,6 +473,28 @@ mod tests {
         assert!(!map.has_active_session(&unit_id));
     }
 
+    #[test]
+    fn test_stale_departure_keeps_newer_session() {
+        let map = DroneSessionMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        let stale = map.create_session(&unit_id).unwrap();
+        let _ = map.remove_session(&unit_id).unwrap();
+        let current = map.create_session(&unit_id).unwrap();
+
+        // The task of the stale session ending must not drain the current one
+        assert!(
+            map.begin_draining(&unit_id, &stale, Instant::now())
+                .is_err()
+        );
+        assert_eq!(map.get_session_id(&unit_id), Some(current.clone()));
+        assert!(!map.is_draining(&unit_id));
+
+        map.begin_draining(&unit_id, &current, Instant::now())
+            .unwrap();
+        assert!(map.is_draining(&unit_id));
+    }
+
     #[test]
     fn test_remove_nonexistent_session() {
         let map = DroneSessionMap::new();
@@ -160,4 +518,311 @@ mod tests {
         let result = map.create_session(&unit_id);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_reap_expired_session() {
+        let map = DroneSessionMap::new();
+        let unit_id = UnitId::from("drone-1");
+        let ttl = Duration::from_secs(30);
+
+        let now = Instant::now();
+        let session_id = map.create_session(&unit_id).unwrap();
+
+        // Still within the ttl, nothing is reaped
+        assert!(map.reap_expired(ttl, now + ttl).is_empty());
+        assert!(map.has_active_session(&unit_id));
+
+        let reaped = map.reap_expired(ttl, now + ttl * 2);
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].session_id, session_id);
+        assert!(!map.has_active_session(&unit_id));
+
+        // A fresh session can be created after reaping
+        assert!(map.create_session(&unit_id).is_ok());
+    }
+
+    #[test]
+    fn test_touch_extends_session() {
+        let map = DroneSessionMap::new();
+        let unit_id = UnitId::from("drone-1");
+        let ttl = Duration::from_secs(30);
+
+        let now = Instant::now();
+        let _ = map.create_session(&unit_id).unwrap();
+
+        map.touch(&unit_id, now + ttl).unwrap();
+
+        assert!(map.reap_expired(ttl, now + ttl * 2).is_empty());
+        assert!(map.has_active_session(&unit_id));
+    }
+
+    const GRACE: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_reconnect_within_grace_preserves_session() {
+        let map = DroneSessionMap::new().with_drain_grace(GRACE);
+        let unit_id = UnitId::from("drone-1");
+        let mut events = map.subscribe();
+
+        let now = Instant::now();
+        let session_id = map.create_session_at(&unit_id, now).unwrap();
+        map.begin_draining(&unit_id, &session_id, now).unwrap();
+        assert!(map.is_draining(&unit_id));
+
+        // Reconnecting before the grace period passes resumes the same session
+        let resumed = map.create_session_at(&unit_id, now + GRACE).unwrap();
+        assert_eq!(resumed, session_id);
+        assert!(!map.is_draining(&unit_id));
+
+        assert!(map.reap_drained(now + GRACE * 2).is_empty());
+        assert_eq!(map.get_session_id(&unit_id), Some(session_id));
+        // Only the original creation is observed
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            SessionEvent::Created { .. }
+        ));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_reconnect_after_grace_replaces_unreaped_session() {
+        let map = DroneSessionMap::new().with_drain_grace(GRACE);
+        let unit_id = UnitId::from("drone-1");
+
+        let now = Instant::now();
+        let drained = map.create_session_at(&unit_id, now).unwrap();
+        map.begin_draining(&unit_id, &drained, now).unwrap();
+        let mut events = map.subscribe();
+
+        let replaced = map.create_session_at(&unit_id, now + GRACE * 2).unwrap();
+        assert_ne!(replaced, drained);
+        assert!(!map.is_draining(&unit_id));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SessionEvent::Removed {
+                unit_id: unit_id.clone(),
+                session_id: drained,
+            }
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SessionEvent::Created {
+                unit_id,
+                session_id: replaced,
+            }
+        );
+    }
+
+    #[test]
+    fn test_departure_removes_session_after_grace() {
+        let map = DroneSessionMap::new().with_drain_grace(GRACE);
+        let departed = UnitId::from("drone-1");
+        let staying = UnitId::from("drone-2");
+
+        let now = Instant::now();
+        let session_id = map.create_session(&departed).unwrap();
+        let _ = map.create_session(&staying).unwrap();
+        map.begin_draining(&departed, &session_id, now).unwrap();
+        // A repeated departure does not restart the grace period
+        map.begin_draining(&departed, &session_id, now + GRACE)
+            .unwrap();
+
+        // Still within the grace period, the session remains active
+        assert!(map.reap_drained(now + GRACE).is_empty());
+        assert!(map.has_active_session(&departed));
+
+        let reaped = map.reap_drained(now + GRACE * 2);
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].session_id, session_id);
+        assert!(!map.has_active_session(&departed));
+        assert!(map.has_active_session(&staying));
+    }
+
+    #[test]
+    fn test_begin_draining_other_session() {
+        let map = DroneSessionMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        let stale = DroneSessionId::generate();
+        assert!(
+            map.begin_draining(&unit_id, &stale, Instant::now())
+                .is_err()
+        );
+
+        let _ = map.create_session(&unit_id).unwrap();
+        assert!(
+            map.begin_draining(&unit_id, &stale, Instant::now())
+                .is_err()
+        );
+        assert!(!map.is_draining(&unit_id));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_reaper_removes_departed_session() {
+        let map = Arc::new(DroneSessionMap::new().with_drain_grace(GRACE));
+        let unit_id = UnitId::from("drone-1");
+        let session_id = map.create_session(&unit_id).unwrap();
+        let mut events = map.subscribe();
+
+        // The reaper reads the system clock, which paused time does not advance
+        let departed = Instant::now() - GRACE * 2;
+        map.begin_draining(&unit_id, &session_id, departed).unwrap();
+        let reaper = spawn_drain_reaper(&map, Duration::from_secs(1));
+
+        assert_eq!(
+            events.recv().await.unwrap(),
+            SessionEvent::Removed {
+                unit_id: unit_id.clone(),
+                session_id,
+            }
+        );
+        assert!(!map.has_active_session(&unit_id));
+
+        // The reaper stops once the map is dropped
+        drop(map);
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(reaper.is_finished());
+    }
+
+    #[test]
+    fn test_touch_nonexistent_session() {
+        let map = DroneSessionMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        let result = map.touch(&unit_id, Instant::now());
+        assert!(matches!(result.unwrap_err(), SessionNotFound { .. }));
+    }
+
+    #[test]
+    fn test_session_events() {
+        let map = DroneSessionMap::new();
+        let unit_id = UnitId::from("drone-1");
+        let mut events = map.subscribe();
+
+        let session_id = map.create_session(&unit_id).unwrap();
+        let _ = map.remove_session(&unit_id).unwrap();
+
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SessionEvent::Created {
+                unit_id: unit_id.clone(),
+                session_id: session_id.clone(),
+            }
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SessionEvent::Removed {
+                unit_id,
+                session_id,
+            }
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_session_events_without_subscribers() {
+        let map = DroneSessionMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        // Emitting with no subscribers must not affect session management
+        let _ = map.create_session(&unit_id).unwrap();
+        assert!(map.remove_session(&unit_id).is_ok());
+    }
+
+    #[test]
+    fn test_list_active_sessions() {
+        let map = DroneSessionMap::new();
+        let unit_ids: Vec<_> = ["drone-1", "drone-2", "drone-3"]
+            .into_iter()
+            .map(UnitId::from)
+            .collect();
+
+        for unit_id in &unit_ids {
+            let _ = map.create_session(unit_id).unwrap();
+        }
+
+        let sessions = map.list_active_sessions();
+        assert_eq!(sessions.len(), 3);
+        for unit_id in &unit_ids {
+            let (_, session_id) = sessions.iter().find(|(id, _)| id == unit_id).unwrap();
+            assert_eq!(map.get_session_id(unit_id).as_ref(), Some(session_id));
+        }
+    }
+
+    #[test]
+    fn test_session_capacity_reached() {
+        let map = DroneSessionMap::with_capacity(2);
+
+        let _ = map.create_session(&UnitId::from("drone-1")).unwrap();
+        let _ = map.create_session(&UnitId::from("drone-2")).unwrap();
+
+        let result = map.create_session(&UnitId::from("drone-3"));
+        assert!(matches!(
+            result.unwrap_err(),
+            CreateSessionError::CapacityReached(SessionCapacityReached { max: 2 })
+        ));
+        assert_eq!(map.active_session_count(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_creations_respect_capacity() {
+        let map = DroneSessionMap::with_capacity(4);
+
+        let created: usize = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..8)
+                .map(|worker| {
+                    let map = &map;
+                    scope.spawn(move || {
+                        (0..8)
+                            .filter(|n| {
+                                let unit_id = UnitId::from(format!("drone-{worker}-{n}"));
+                                map.create_session(&unit_id).is_ok()
+                            })
+                            .count()
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).sum()
+        });
+
+        assert_eq!(created, 4);
+        assert_eq!(map.active_session_count(), 4);
+    }
+
+    #[test]
+    fn test_session_capacity_freed_on_remove() {
+        let map = DroneSessionMap::with_capacity(1);
+        let unit_id = UnitId::from("drone-1");
+
+        let _ = map.create_session(&unit_id).unwrap();
+        let _ = map.remove_session(&unit_id).unwrap();
+
+        let reaped = UnitId::from("drone-2");
+        let now = Instant::now();
+        let session_id = map.create_session_at(&reaped, now).unwrap();
+        map.begin_draining(&reaped, &session_id, now).unwrap();
+        assert_eq!(map.reap_drained(now + DEFAULT_DRAIN_GRACE * 2).len(), 1);
+
+        assert!(map.create_session(&UnitId::from("drone-3")).is_ok());
+    }
+
+    #[test]
+    fn test_session_ids_sort_by_uuid() {
+        let mut ids: Vec<_> = (0..8).map(|_| DroneSessionId::generate()).collect();
+
+        ids.sort();
+
+        let uuids: Vec<_> = ids.iter().map(|id| *id.as_uuid()).collect();
+        assert!(uuids.is_sorted());
+    }
+
+    #[test]
+    fn test_session_id_from_uuid() {
+        let session_id = DroneSessionId::generate();
+
+        let reconstructed = DroneSessionId::from_uuid(*session_id.as_uuid());
+
+        assert_eq!(reconstructed, session_id);
+        assert_eq!(reconstructed.to_string(), session_id.to_string());
+    }
 }
@@ -0,0 +1,363 @@
+//! Simulated drones, flown alone by the drone binary or in fleets for load testing.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::{SinkExt, StreamExt};
+use moq_lite::{OriginConsumer, OriginProducer, Session};
+use rpcmoq_lite::{RpcClient, RpcClientConfig, RpcClientError, RpcSender};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::PRIMARY_TRACK;
+use crate::connect_bidirectional;
+use crate::drone_proto::DronePosition;
+use crate::state_machine::StateMachine;
+use crate::state_machine::echo::Position;
+use crate::state_machine::flight_path::{FlightPath, FlightPathInput, FlightPathOutput, Route};
+
+/// The RPC drones stream their positions over.
+pub const ECHO_PATH: &str = "drone.EchoService/Echo";
+
+/// How often a simulated drone sends its position.
+pub const TICK: Duration = Duration::from_secs(1);
+
+/// The center of the orbits flown by simulated drones.
+const ORBIT_LATITUDE: f64 = 37.7749;
+const ORBIT_LONGITUDE: f64 = -122.4194;
+const ORBIT_RADIUS_M: f64 = 200.0;
+const ORBIT_SPEED_MPS: f64 = 10.0;
+const ORBIT_ALTITUDE_M: f64 = 100.0;
+/// The spacing between the orbits of drones in a fleet, so they do not overlap.
+const FLEET_SPACING_M: f64 = 500.0;
+
+/// The client configuration a drone connects to the server with.
+pub fn client_config(drone_id: impl Into<String>) -> RpcClientConfig {
+    RpcClientConfig::builder()
+        .client_id(drone_id.into())
+        // TODO: Convert to postfix
+        // TODO: Default to client and server
+        .client_prefix("drone".to_string())
+        .server_prefix("server".to_string())
+        .track_name(PRIMARY_TRACK.to_string())
+        .timeout(Duration::from_secs(60))
+        .build()
+}
+
+/// The orbit flown by the `index`th drone of a fleet, spaced east of the orbits before it.
+pub fn orbit(drone_id: impl Into<String>, index: usize) -> FlightPath {
+    let center = Position {
+        drone_id: String::new(),
+        latitude: ORBIT_LATITUDE,
+        longitude: ORBIT_LONGITUDE,
+        altitude_m: ORBIT_ALTITUDE_M,
+        heading_deg: 0.0,
+        speed_mps: 0.0,
+        timestamp: 0,
+    }
+    .offset_by(0.0, FLEET_SPACING_M * index as f64);
+
+    FlightPath::new(
+        drone_id,
+        Route::Orbit {
+            latitude: center.latitude,
+            longitude: center.longitude,
+            radius_m: ORBIT_RADIUS_M,
+        },
+        ORBIT_SPEED_MPS,
+        ORBIT_ALTITUDE_M,
+    )
+    .expect("orbits have no waypoints to be missing")
+}
+
+/// Send a position along `flight_path` every [`TICK`] until sending fails, numbering each with
+/// the next sequence starting from zero.
+pub async fn fly(mut sender: RpcSender<DronePosition>, mut flight_path: FlightPath) {
+    let mut ticker = tokio::time::interval(TICK);
+    let mut sequence = 0;
+
+    loop {
+        ticker.tick().await;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        flight_path.process_input(FlightPathInput::Tick {
+            elapsed: TICK,
+            timestamp,
+        });
+        let Some(FlightPathOutput::Position(pos)) = flight_path.poll_output() else {
+            continue;
+        };
+
+        let (lat, lon, alt) = (pos.latitude, pos.longitude, pos.altitude_m);
+        let position = DronePosition {
+            sequence,
+            ..DronePosition::from(pos)
+        };
+        sequence += 1;
+        if let Err(e) = sender.send(position).await {
+            warn!(error = %e, "Failed to send position, stopping sender");
+            break;
+        }
+
+        debug!(lat, lon, alt, "Sent position");
+    }
+}
+
+/// A simulated drone flying in the background.
+pub struct SimulatedDrone {
+    drone_id: String,
+    task: JoinHandle<()>,
+    // Kept open for as long as the drone flies, when it has its own relay connection
+    session: Option<Session>,
+}
+
+impl SimulatedDrone {
+    pub fn drone_id(&self) -> &str {
+        &self.drone_id
+    }
+
+    /// Stop the drone, unannouncing its broadcast and closing its relay connection.
+    pub async fn stop(self) {
+        self.task.abort();
+        let _ = self.task.await;
+        if let Some(session) = self.session {
+            crate::shutdown::close_relay(session);
+        }
+    }
+}
+
+/// Connect a drone flying `flight_path` through `producer` and `consumer`, and fly it in the
+/// background.
+///
+/// Echoed positions are received and discarded.
+pub async fn spawn_drone(
+    producer: Arc<OriginProducer>,
+    consumer: OriginConsumer,
+    drone_id: String,
+    flight_path: FlightPath,
+) -> Result<SimulatedDrone, RpcClientError> {
+    let mut client = RpcClient::new(producer, consumer, client_config(drone_id.clone()));
+    let conn = client
+        .connect::<DronePosition, DronePosition>(ECHO_PATH)
+        .await?;
+    info!(drone_id = %drone_id, "Drone is online");
+
+    let (sender, mut receiver) = conn.split();
+    let task_drone_id = drone_id.clone();
+    let task = tokio::spawn(async move {
+        // The client owns the broadcast, so it must live as long as the drone flies
+        let _client = client;
+        let send = fly(sender, flight_path);
+        let receive = async {
+            while let Some(result) = receiver.next().await {
+                if let Err(e) = result {
+                    warn!(drone_id = %task_drone_id, error = %e, "Echo receive error");
+                }
+            }
+        };
+        tokio::select! {
+            () = send => {}
+            () = receive => {}
+        }
+        info!(drone_id = %task_drone_id, "Drone stopped");
+    });
+
+    Ok(SimulatedDrone {
+        drone_id,
+        task,
+        session: None,
+    })
+}
+
+/// A fleet of simulated drones.
+pub struct Fleet {
+    drones: Vec<SimulatedDrone>,
+}
+
+impl Fleet {
+    /// The simulated drones, in the order they were spawned.
+    pub fn drones(&self) -> &[SimulatedDrone] {
+        &self.drones
+    }
+
+    /// Stop every drone of the fleet.
+    pub async fn stop(self) {
+        for drone in self.drones {
+            drone.stop().await;
+        }
+    }
+}
+
+impl Fleet {
+    /// Spawn `count` drones with `spawn`, stopping those already spawned if any fails.
+    async fn spawn<E>(
+        count: usize,
+        mut spawn: impl AsyncFnMut(usize) -> Result<SimulatedDrone, E>,
+    ) -> Result<Self, E> {
+        let mut fleet = Fleet {
+            drones: Vec::with_capacity(count),
+        };
+
+        for index in 0..count {
+            match spawn(index).await {
+                Ok(drone) => fleet.drones.push(drone),
+                Err(e) => {
+                    fleet.stop().await;
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(fleet)
+    }
+}
+
+/// Spawn `count` drones, each with its own connection to the relay at `relay_url`, named
+/// `{id_prefix}-{index}` and flying its own orbit.
+///
+/// If any drone fails to connect, those already spawned are stopped.
+pub async fn spawn_fleet(relay_url: &str, count: usize, id_prefix: &str) -> anyhow::Result<Fleet> {
+    Fleet::spawn(count, async |index| {
+        let (session, producer, consumer) = connect_bidirectional(relay_url).await?;
+        let drone_id = format!("{id_prefix}-{index}");
+        let mut drone = spawn_drone(
+            Arc::new(producer),
+            consumer,
+            drone_id.clone(),
+            orbit(drone_id, index),
+        )
+        .await?;
+        drone.session = Some(session);
+        Ok(drone)
+    })
+    .await
+}
+
+/// Spawn `count` drones sharing `producer` and `consumer`, named `{id_prefix}-{index}` and each
+/// flying its own orbit.
+///
+/// Useful for running a fleet without a relay, such as over an in-process loopback. If any drone
+/// fails to connect, those already spawned are stopped.
+pub async fn spawn_fleet_on(
+    producer: Arc<OriginProducer>,
+    consumer: OriginConsumer,
+    count: usize,
+    id_prefix: &str,
+) -> Result<Fleet, RpcClientError> {
+    Fleet::spawn(count, async |index| {
+        let drone_id = format!("{id_prefix}-{index}");
+        spawn_drone(
+            Arc::clone(&producer),
+            consumer.clone(),
+            drone_id.clone(),
+            orbit(drone_id, index),
+        )
+        .await
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rpcmoq_lite::testing::ServerSides;
+    use rpcmoq_lite::{DecodedInbound, RpcRouterConfig, RpcRouterHandle};
+
+    use super::*;
+
+    /// Run a router echoing drone positions for up to `max_connections` drones, returning a
+    /// handle to it.
+    fn spawn_echo_router(
+        server_sides: ServerSides,
+        max_connections: Option<usize>,
+    ) -> RpcRouterHandle {
+        let mut router = server_sides.router(
+            RpcRouterConfig::builder()
+                .client_prefix("drone".to_string())
+                .response_prefix("server".to_string())
+                .track_name(PRIMARY_TRACK.to_string())
+                .maybe_max_connections(max_connections)
+                .build(),
+        );
+        router
+            .register(
+                ECHO_PATH,
+                |_client_id, inbound: DecodedInbound<DronePosition>| async move {
+                    Ok(inbound.into_ok_stream().map(Ok))
+                },
+            )
+            .unwrap();
+        let handle = router.handle();
+        tokio::spawn(router.run());
+        handle
+    }
+
+    #[tokio::test]
+    async fn test_fleet_announces_every_drone() {
+        let (client_sides, server_sides) = rpcmoq_lite::testing::loopback();
+        let handle = spawn_echo_router(server_sides, None);
+
+        let fleet = spawn_fleet_on(client_sides.producer, client_sides.consumer, 3, "sim")
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = fleet
+            .drones()
+            .iter()
+            .map(SimulatedDrone::drone_id)
+            .collect();
+        assert_eq!(ids, vec!["sim-0", "sim-1", "sim-2"]);
+
+        let connected: HashSet<_> = handle
+            .active_session_keys()
+            .into_iter()
+            .map(|key| key.client_id)
+            .collect();
+        assert_eq!(connected, ids.into_iter().map(String::from).collect());
+
+        fleet.stop().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_fleet_stops_spawned_drones() {
+        let (client_sides, server_sides) = rpcmoq_lite::testing::loopback();
+        // Declining the second drone makes it time out connecting
+        let handle = spawn_echo_router(server_sides, Some(1));
+
+        let result = spawn_fleet_on(client_sides.producer, client_sides.consumer, 3, "sim").await;
+        assert!(matches!(result, Err(RpcClientError::Timeout(_))));
+
+        // The first drone connected before the failure, and must have been stopped
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while handle.active_sessions() > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("first drone should be stopped");
+    }
+
+    #[test]
+    fn test_fleet_orbits_do_not_overlap() {
+        let mut first = orbit("sim-0", 0);
+        let mut second = orbit("sim-1", 1);
+
+        for machine in [&mut first, &mut second] {
+            machine.process_input(FlightPathInput::Tick {
+                elapsed: TICK,
+                timestamp: 1,
+            });
+        }
+        let (Some(FlightPathOutput::Position(a)), Some(FlightPathOutput::Position(b))) =
+            (first.poll_output(), second.poll_output())
+        else {
+            panic!("ticks should produce positions");
+        };
+
+        assert!(a.distance_to(&b) > 2.0 * ORBIT_RADIUS_M);
+    }
+}
@@ -1,5 +1,5 @@
 mod server;
 
-pub use server::start_server;
+pub use server::{ServerConfig, start_server, start_server_with};
 
 pub use crate::drone_proto::echo_service_client::EchoServiceClient;
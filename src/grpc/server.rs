@@ -1,33 +1,70 @@
 use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::StreamExt;
 use tonic::{Request, Response, Status, Streaming};
 use tracing::{debug, info, warn};
 
-use crate::drone::DroneSessionMap;
-use crate::drone_proto::DronePosition;
+use crate::drone::error::CreateSessionError;
+use crate::drone::{self, DroneSessionMap};
 use crate::drone_proto::echo_service_server::{EchoService, EchoServiceServer};
-use crate::state_machine::echo::Position;
+use crate::drone_proto::{DroneHealth, DronePosition};
+use crate::telemetry::GapDetector;
 use crate::unit::UnitId;
 use crate::unit_context::UnitContext;
 use crate::unit_map::UnitMap;
 
+/// Configuration for the gRPC server.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// How often each echo stream polls its unit for a new position to send.
+    ///
+    /// Shorter intervals reduce echo latency at the cost of more frequent wakeups. Defaults to
+    /// 50ms.
+    pub poll_interval: Duration,
+    /// How often sessions of drones which departed and did not reconnect within the grace period
+    /// are removed. Defaults to 1s.
+    pub reap_interval: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_millis(50),
+            reap_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Start the gRPC server with the default [`ServerConfig`].
 pub async fn start_server(
     addr: SocketAddr,
     unit_map: Arc<UnitMap<UnitContext>>,
     session_map: Arc<DroneSessionMap>,
 ) -> anyhow::Result<()> {
-    let service = DroneServiceImpl::new(unit_map, session_map);
+    start_server_with(addr, unit_map, session_map, ServerConfig::default()).await
+}
+
+/// Start the gRPC server configured by `config`.
+pub async fn start_server_with(
+    addr: SocketAddr,
+    unit_map: Arc<UnitMap<UnitContext>>,
+    session_map: Arc<DroneSessionMap>,
+    config: ServerConfig,
+) -> anyhow::Result<()> {
+    let reaper = drone::spawn_drain_reaper(&session_map, config.reap_interval);
+    let service = DroneServiceImpl::new(unit_map, session_map, config);
 
     info!(address = %addr, "gRPC server starting");
 
-    tonic::transport::Server::builder()
+    let served = tonic::transport::Server::builder()
         .add_service(EchoServiceServer::new(service))
         .serve(addr)
-        .await?;
+        .await;
+    reaper.abort();
+    served?;
 
     Ok(())
 }
@@ -35,13 +72,19 @@ pub async fn start_server(
 pub struct DroneServiceImpl {
     unit_map: Arc<UnitMap<UnitContext>>,
     session_map: Arc<DroneSessionMap>,
+    config: ServerConfig,
 }
 
 impl DroneServiceImpl {
-    pub fn new(unit_map: Arc<UnitMap<UnitContext>>, session_map: Arc<DroneSessionMap>) -> Self {
+    pub fn new(
+        unit_map: Arc<UnitMap<UnitContext>>,
+        session_map: Arc<DroneSessionMap>,
+        config: ServerConfig,
+    ) -> Self {
         Self {
             unit_map,
             session_map,
+            config,
         }
     }
 }
@@ -70,23 +113,25 @@ impl EchoService for DroneServiceImpl {
         info!(drone_id = %drone_id, "DroneSession started");
 
         // Create or reuse unit context
-        if self.unit_map.get_unit(&unit_id).is_err() {
-            let context = UnitContext::new();
-            self.unit_map
-                .insert_unit(unit_id.clone(), context)
-                .map_err(|e| Status::internal(e.to_string()))?;
-        }
+        self.unit_map
+            .get_or_insert_unit(unit_id.clone(), UnitContext::new);
 
-        match self.session_map.create_session(&unit_id) {
+        let session_id = match self.session_map.create_session(&unit_id) {
             Ok(session_id) => {
                 info!(drone_id = %drone_id, session_id = %session_id, "Session created");
+                session_id
             }
-            Err(e) => {
+            Err(e @ CreateSessionError::AlreadyActive(_)) => {
                 return Err(Status::already_exists(e.to_string()));
             }
-        }
+            Err(e @ CreateSessionError::CapacityReached(_)) => {
+                return Err(Status::resource_exhausted(e.to_string()));
+            }
+        };
 
         // Process that first telemetry message
+        let mut gaps = GapDetector::new();
+        gaps.observe(first_msg.sequence);
         self.process_position(&unit_id, first_msg);
 
         // Spawn task to process telemetry → StateMachine
@@ -99,20 +144,21 @@ impl EchoService for DroneServiceImpl {
             while let Some(msg_result) = inbound.next().await {
                 match msg_result {
                     Ok(pos) => {
-                        let position = Position {
-                            drone_id: pos.drone_id.clone(),
-                            latitude: pos.latitude,
-                            longitude: pos.longitude,
-                            altitude_m: pos.altitude_m,
-                            heading_deg: pos.heading_deg,
-                            speed_mps: pos.speed_mps,
-                            timestamp: pos.timestamp,
-                        };
-
-                        if let Ok(unit_ref) =
-                            unit_map_for_telemetry.get_unit(&unit_id_for_telemetry)
+                        let _ = telemetry_session_map.touch(&unit_id_for_telemetry, Instant::now());
+
+                        if let Some(missing) = gaps.observe(pos.sequence) {
+                            warn!(
+                                drone_id = %drone_id_for_task,
+                                first_missing = missing.start,
+                                count = missing.end - missing.start,
+                                "Telemetry gap detected"
+                            );
+                        }
+
+                        if let Err(e) = unit_map_for_telemetry
+                            .with_unit_mut(&unit_id_for_telemetry, |ctx| record_telemetry(ctx, pos))
                         {
-                            let _ = unit_ref.view(|ctx| ctx.update_position(position));
+                            warn!(drone_id = %drone_id_for_task, error = %e, "Dropped position update");
                         }
                     }
                     Err(e) => {
@@ -122,67 +168,148 @@ impl EchoService for DroneServiceImpl {
                 }
             }
 
-            // Cleanup on disconnect
+            // The session is kept for the grace period in case the drone reconnects
             info!(drone_id = %drone_id_for_task, "Telemetry stream closed");
-            let _ = telemetry_session_map.remove_session(&unit_id_for_telemetry);
+            let _ = telemetry_session_map.begin_draining(
+                &unit_id_for_telemetry,
+                &session_id,
+                Instant::now(),
+            );
         });
 
-        let unit_map_for_echo = Arc::clone(&self.unit_map);
-        let session_map_for_stream = Arc::clone(&self.session_map);
-        let unit_id_for_stream = unit_id.clone();
-        let drone_id_for_stream = drone_id.clone();
+        Ok(Response::new(self.echo_stream(unit_id, drone_id)))
+    }
+}
+
+impl DroneServiceImpl {
+    /// Stream positions from the unit's context back to the drone until its session ends, polling
+    /// every [`poll_interval`](ServerConfig::poll_interval).
+    fn echo_stream(&self, unit_id: UnitId, drone_id: String) -> <Self as EchoService>::EchoStream {
+        let unit_map = Arc::clone(&self.unit_map);
+        let session_map = Arc::clone(&self.session_map);
+        let poll_interval = self.config.poll_interval;
 
         let outbound = async_stream::stream! {
             loop {
-                if !session_map_for_stream.has_active_session(&unit_id_for_stream) {
-                    debug!(drone_id = %drone_id_for_stream, "Session ended, closing echo stream");
+                if !session_map.has_active_session(&unit_id) {
+                    debug!(drone_id = %drone_id, "Session ended, closing echo stream");
                     break;
                 }
 
-                let maybe_pos = unit_map_for_echo
-                    .get_unit(&unit_id_for_stream)
-                    .ok()
-                    .and_then(|unit_ref| {
-                        unit_ref.view(|ctx| ctx.poll_position()).ok().flatten()
-                    });
-
-                if let Some(pos_bytes) = maybe_pos {
-                    let pos = DronePosition {
-            drone_id: pos_bytes.drone_id,
-             latitude: pos_bytes.latitude,
-             longitude: pos_bytes.longitude,
-             altitude_m: pos_bytes.altitude_m,
-             heading_deg: pos_bytes.heading_deg,
-             speed_mps: pos_bytes.speed_mps,
-             timestamp: pos_bytes.timestamp,
-
-                    };
-                            debug!(drone_id = %drone_id_for_stream, position = ?pos, "Sending position");
-                            yield Ok(pos);
+                let maybe_pos = match unit_map.with_unit_mut(&unit_id, poll_echo) {
+                    Ok(pos) => pos,
+                    Err(e) => {
+                        warn!(drone_id = %drone_id, error = %e, "Failed to poll position");
+                        None
+                    }
+                };
+
+                if let Some(pos) = maybe_pos {
+                    debug!(drone_id = %drone_id, position = ?pos, "Sending position");
+                    yield Ok(pos);
                 }
 
-                tokio::time::sleep(Duration::from_millis(50)).await;
+                tokio::time::sleep(poll_interval).await;
             }
         };
 
-        Ok(Response::new(Box::pin(outbound)))
+        Box::pin(outbound)
+    }
+
+    fn process_position(&self, unit_id: &UnitId, pos: DronePosition) {
+        if let Err(e) = self
+            .unit_map
+            .with_unit_mut(unit_id, |ctx| record_telemetry(ctx, pos))
+        {
+            warn!(unit_id = %unit_id, error = %e, "Dropped position update");
+        }
     }
 }
 
-impl DroneServiceImpl {
-    fn process_position(&self, unit_id: &UnitId, pos: crate::drone_proto::DronePosition) {
-        let position = Position {
-            drone_id: pos.drone_id,
-            latitude: pos.latitude,
-            longitude: pos.longitude,
-            altitude_m: pos.altitude_m,
-            heading_deg: pos.heading_deg,
-            speed_mps: pos.speed_mps,
-            timestamp: pos.timestamp,
+/// Record the position reported in `pos` in `ctx`, along with the health it carries if any.
+fn record_telemetry(ctx: &mut UnitContext, mut pos: DronePosition) {
+    if let Some(health) = pos.health.take() {
+        ctx.update_health(health.into());
+    }
+    ctx.update_position(pos.into());
+}
+
+/// Poll `ctx` for the next position to echo, carrying the latest health not yet echoed.
+fn poll_echo(ctx: &mut UnitContext) -> Option<DronePosition> {
+    let position = ctx.poll_position()?;
+    Some(DronePosition {
+        health: ctx.poll_health().map(DroneHealth::from),
+        ..DronePosition::from(position)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine::echo::position;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_echo_stream_polls_at_configured_interval() {
+        let unit_map = Arc::new(UnitMap::new());
+        let session_map = Arc::new(DroneSessionMap::new());
+        let config = ServerConfig {
+            poll_interval: Duration::from_millis(200),
+            ..ServerConfig::default()
         };
+        let service =
+            DroneServiceImpl::new(Arc::clone(&unit_map), Arc::clone(&session_map), config);
 
-        if let Ok(unit_ref) = self.unit_map.get_unit(unit_id) {
-            let _ = unit_ref.view(|ctx| ctx.update_position(position));
-        }
+        let unit_id = UnitId::from("drone-1");
+        unit_map
+            .insert_unit(unit_id.clone(), UnitContext::new())
+            .unwrap();
+        session_map.create_session(&unit_id).unwrap();
+
+        unit_map
+            .with_unit_mut(&unit_id, |ctx| ctx.update_position(position(1)))
+            .unwrap();
+        let mut stream = service.echo_stream(unit_id.clone(), "drone-1".to_string());
+
+        let start = tokio::time::Instant::now();
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.timestamp, 1);
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // The next position is only picked up once the poll interval has elapsed
+        unit_map
+            .with_unit_mut(&unit_id, |ctx| ctx.update_position(position(2)))
+            .unwrap();
+        let second = stream.next().await.unwrap().unwrap();
+        assert_eq!(second.timestamp, 2);
+        assert_eq!(start.elapsed(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_health_is_echoed_with_next_position() {
+        let mut ctx = UnitContext::new();
+        let health = DroneHealth {
+            drone_id: "drone-1".to_string(),
+            battery_percent: 42.0,
+            status: crate::drone_proto::HealthStatus::Degraded.into(),
+            timestamp: 1,
+        };
+
+        record_telemetry(
+            &mut ctx,
+            DronePosition {
+                health: Some(health.clone()),
+                ..DronePosition::from(position(1))
+            },
+        );
+        let echoed = poll_echo(&mut ctx).unwrap();
+        assert_eq!(echoed.timestamp, 1);
+        assert_eq!(echoed.health, Some(health));
+
+        // Health is only echoed once, with the position following its report
+        record_telemetry(&mut ctx, DronePosition::from(position(2)));
+        let echoed = poll_echo(&mut ctx).unwrap();
+        assert_eq!(echoed.timestamp, 2);
+        assert_eq!(echoed.health, None);
+        assert_eq!(poll_echo(&mut ctx), None);
     }
 }
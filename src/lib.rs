@@ -1,6 +1,15 @@
+pub mod codec;
 pub mod drone;
+pub mod fleet;
 pub mod grpc;
+pub mod metrics;
+pub mod path;
+pub mod reconnect;
+pub mod shutdown;
 pub mod state_machine;
+pub mod subscribe;
+pub mod telemetry;
+pub mod track;
 pub mod unit;
 pub mod unit_context;
 pub mod unit_map;
@@ -9,25 +18,54 @@ use anyhow::Result;
 use moq_lite::{Client, Origin, Session};
 use url::Url;
 use web_transport_quinn::ClientBuilder;
+use web_transport_quinn::quinn::rustls::pki_types::CertificateDer;
 
 pub mod drone_proto {
     include!(concat!(env!("OUT_DIR"), "/drone.rs"));
 }
 
+pub mod telemetry_proto {
+    include!(concat!(env!("OUT_DIR"), "/telemetry.rs"));
+}
+
 pub const PRIMARY_TRACK: &str = "primary";
 
+/// How the relay's TLS certificate is verified when connecting.
+#[derive(Debug, Clone)]
+pub enum TlsConfig {
+    /// Accept any certificate presented by the relay without verification.
+    ///
+    /// This must never be used in production, as it provides no protection against an attacker
+    /// impersonating the relay. It exists for local development against self-signed relays.
+    Insecure,
+    /// Verify the relay's certificate against the platform's native root certificates.
+    NativeRoots,
+    /// Accept only a relay presenting one of the provided certificates.
+    CustomRoots(Vec<CertificateDer<'static>>),
+}
+
 /// Connect to the relay as a publisher + subscriber (bidirectional).
 /// Returns the session handle and the origin producer/consumer pair.
+///
+/// The relay's certificate is not verified, see [`TlsConfig::Insecure`]. Use
+/// [`connect_bidirectional_with`] to configure verification.
 pub async fn connect_bidirectional(
     relay_url: &str,
+) -> Result<(Session, moq_lite::OriginProducer, moq_lite::OriginConsumer)> {
+    connect_bidirectional_with(relay_url, TlsConfig::Insecure).await
+}
+
+/// Connect to the relay as a publisher + subscriber (bidirectional), verifying the relay's
+/// certificate according to `tls`.
+/// Returns the session handle and the origin producer/consumer pair.
+pub async fn connect_bidirectional_with(
+    relay_url: &str,
+    tls: TlsConfig,
 ) -> Result<(Session, moq_lite::OriginProducer, moq_lite::OriginConsumer)> {
     let pub_origin = Origin::produce();
     let sub_origin = Origin::produce();
 
-    let wt_client = ClientBuilder::new()
-        .dangerous()
-        .with_no_certificate_verification()?;
-    let wt_session = wt_client.connect(relay_url.parse::<Url>()?).await?;
+    let wt_session = connect_web_transport(relay_url, tls).await?;
 
     let client = Client::new()
         .with_publish(pub_origin.consumer)
@@ -36,3 +74,48 @@ pub async fn connect_bidirectional(
 
     Ok((session, pub_origin.producer, sub_origin.consumer))
 }
+
+/// Connect to the relay as a publisher only.
+/// Returns the session handle and the origin producer.
+///
+/// The relay's certificate is not verified, see [`TlsConfig::Insecure`].
+pub async fn connect_publisher(relay_url: &str) -> Result<(Session, moq_lite::OriginProducer)> {
+    let pub_origin = Origin::produce();
+
+    let wt_session = connect_web_transport(relay_url, TlsConfig::Insecure).await?;
+
+    let client = Client::new().with_publish(pub_origin.consumer);
+    let session = client.connect(wt_session).await?;
+
+    Ok((session, pub_origin.producer))
+}
+
+/// Connect to the relay as a subscriber only.
+/// Returns the session handle and the origin consumer.
+///
+/// The relay's certificate is not verified, see [`TlsConfig::Insecure`].
+pub async fn connect_subscriber(relay_url: &str) -> Result<(Session, moq_lite::OriginConsumer)> {
+    let sub_origin = Origin::produce();
+
+    let wt_session = connect_web_transport(relay_url, TlsConfig::Insecure).await?;
+
+    let client = Client::new().with_consume(sub_origin.producer);
+    let session = client.connect(wt_session).await?;
+
+    Ok((session, sub_origin.consumer))
+}
+
+/// Establish the WebTransport session to the relay which MoQ sessions are layered on top of.
+async fn connect_web_transport(
+    relay_url: &str,
+    tls: TlsConfig,
+) -> Result<web_transport_quinn::Session> {
+    let builder = ClientBuilder::new();
+    let wt_client = match tls {
+        TlsConfig::Insecure => builder.dangerous().with_no_certificate_verification()?,
+        TlsConfig::NativeRoots => builder.with_system_roots()?,
+        TlsConfig::CustomRoots(certs) => builder.with_server_certificates(certs)?,
+    };
+
+    Ok(wt_client.connect(relay_url.parse::<Url>()?).await?)
+}
@@ -0,0 +1,116 @@
+//! Gauge metrics of the server state, rendered in the Prometheus text exposition format.
+
+use std::fmt::Write;
+
+/// A single gauge reading.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gauge {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub value: f64,
+}
+
+/// A point-in-time set of metrics which can be exported as gauges.
+pub trait GaugeMetrics {
+    fn gauges(&self) -> Vec<Gauge>;
+}
+
+/// A point-in-time copy of the counts of a [`UnitMap`](crate::unit_map::UnitMap).
+///
+/// Created by [`UnitMap::metrics`](crate::unit_map::UnitMap::metrics).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnitMapMetrics {
+    /// The number of units currently tracked.
+    pub connected_units: usize,
+}
+
+impl GaugeMetrics for UnitMapMetrics {
+    fn gauges(&self) -> Vec<Gauge> {
+        vec![Gauge {
+            name: "moq_connected_units",
+            help: "The number of units currently tracked.",
+            value: self.connected_units as f64,
+        }]
+    }
+}
+
+/// A point-in-time copy of the counts of a [`DroneSessionMap`](crate::drone::DroneSessionMap).
+///
+/// Created by [`DroneSessionMap::metrics`](crate::drone::DroneSessionMap::metrics).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionMetrics {
+    /// The number of active sessions, including those draining.
+    pub active_sessions: usize,
+    /// The number of active sessions whose drone has departed, waiting out the grace period.
+    pub draining_sessions: usize,
+}
+
+impl GaugeMetrics for SessionMetrics {
+    fn gauges(&self) -> Vec<Gauge> {
+        vec![
+            Gauge {
+                name: "moq_active_sessions",
+                help: "The number of active drone sessions, including those draining.",
+                value: self.active_sessions as f64,
+            },
+            Gauge {
+                name: "moq_draining_sessions",
+                help: "The number of drone sessions waiting out the grace period after departing.",
+                value: self.draining_sessions as f64,
+            },
+        ]
+    }
+}
+
+/// Render the gauges of every entry of `metrics` in the Prometheus text exposition format, as
+/// served from a `/metrics` endpoint.
+pub fn render_prometheus(metrics: &[&dyn GaugeMetrics]) -> String {
+    let mut out = String::new();
+
+    for gauge in metrics.iter().flat_map(|metrics| metrics.gauges()) {
+        // Writing to a string cannot fail
+        let _ = writeln!(out, "# HELP {} {}", gauge.name, gauge.help);
+        let _ = writeln!(out, "# TYPE {} gauge", gauge.name);
+        let _ = writeln!(out, "{} {}", gauge.name, gauge.value);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::drone::DroneSessionMap;
+    use crate::unit::UnitId;
+    use crate::unit_map::UnitMap;
+
+    #[test]
+    fn test_render_session_and_unit_metrics() {
+        let unit_map = UnitMap::new();
+        let session_map = DroneSessionMap::new();
+        let mut session_ids = Vec::new();
+        for id in ["drone-1", "drone-2"] {
+            let unit_id = UnitId::from(id);
+            unit_map.insert_unit(unit_id.clone(), ()).unwrap();
+            session_ids.push(session_map.create_session(&unit_id).unwrap());
+        }
+        session_map
+            .begin_draining(&UnitId::from("drone-2"), &session_ids[1], Instant::now())
+            .unwrap();
+
+        let rendered = render_prometheus(&[&unit_map.metrics(), &session_map.metrics()]);
+
+        let lines: Vec<_> = rendered.lines().collect();
+        assert!(lines.contains(&"# TYPE moq_active_sessions gauge"));
+        assert!(lines.contains(&"moq_active_sessions 2"));
+        assert!(lines.contains(&"moq_draining_sessions 1"));
+        assert!(lines.contains(&"moq_connected_units 2"));
+    }
+
+    #[test]
+    fn test_render_nothing() {
+        assert_eq!(render_prometheus(&[]), "");
+    }
+}
@@ -0,0 +1,6 @@
+/// Indicates that a broadcast path segment was empty or contained a `/`.
+#[derive(Debug, thiserror::Error)]
+#[error("the broadcast path segment ({segment:?}) must be non-empty and not contain '/'")]
+pub struct InvalidPathSegment {
+    pub segment: String,
+}
@@ -0,0 +1,129 @@
+use std::fmt::Display;
+
+use self::error::InvalidPathSegment;
+
+pub mod error;
+
+const DRONE_PREFIX: &str = "drone";
+const CONTROL_PREFIX: &str = "control";
+
+/// A validated, `/`-separated MoQ broadcast path.
+///
+/// Every segment is guaranteed to be non-empty and free of `/`, so paths built from externally
+/// provided IDs cannot collide with or escape into other namespaces.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct BroadcastPath(String);
+
+impl BroadcastPath {
+    /// Create a path consisting of the single `segment`.
+    pub fn new(segment: impl Into<String>) -> Result<Self, InvalidPathSegment> {
+        let segment = segment.into();
+        validate_segment(&segment)?;
+        Ok(Self(segment))
+    }
+
+    /// The path a drone publishes its broadcast under, `drone/{id}`.
+    pub fn drone(id: impl Display) -> Result<Self, InvalidPathSegment> {
+        Self::new(DRONE_PREFIX)?.track(id)
+    }
+
+    /// The path a drone's control broadcast is published under, `control/{id}`.
+    pub fn control(id: impl Display) -> Result<Self, InvalidPathSegment> {
+        Self::new(CONTROL_PREFIX)?.track(id)
+    }
+
+    /// Append the segment `name` to this path.
+    pub fn track(mut self, name: impl Display) -> Result<Self, InvalidPathSegment> {
+        let name = name.to_string();
+        validate_segment(&name)?;
+
+        self.0.push('/');
+        self.0.push_str(&name);
+        Ok(self)
+    }
+
+    /// Returns the underlying path string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for BroadcastPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl AsRef<str> for BroadcastPath {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+fn validate_segment(segment: &str) -> Result<(), InvalidPathSegment> {
+    if segment.is_empty() || segment.contains('/') {
+        return Err(InvalidPathSegment {
+            segment: segment.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unit::UnitId;
+
+    #[test]
+    fn test_drone_path() {
+        let path = BroadcastPath::drone(UnitId::from("drone-1")).unwrap();
+        assert_eq!(path.as_str(), "drone/drone-1");
+    }
+
+    #[test]
+    fn test_control_path() {
+        let path = BroadcastPath::control("drone-1").unwrap();
+        assert_eq!(path.as_str(), "control/drone-1");
+    }
+
+    #[test]
+    fn test_track_appends_segment() {
+        let path = BroadcastPath::drone("drone-1")
+            .unwrap()
+            .track(crate::PRIMARY_TRACK)
+            .unwrap();
+        assert_eq!(path.to_string(), "drone/drone-1/primary");
+    }
+
+    #[test]
+    fn test_new_path() {
+        let path = BroadcastPath::new("server").unwrap();
+        assert_eq!(path.as_str(), "server");
+    }
+
+    #[test]
+    fn test_rejects_empty_segment() {
+        assert!(BroadcastPath::new("").is_err());
+        assert!(BroadcastPath::drone("").is_err());
+        assert!(
+            BroadcastPath::control("drone-1")
+                .unwrap()
+                .track("")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rejects_segment_with_slash() {
+        assert!(BroadcastPath::new("drone/1").is_err());
+        assert!(BroadcastPath::drone("a/b").is_err());
+        assert!(BroadcastPath::control("/").is_err());
+        assert!(
+            BroadcastPath::drone("drone-1")
+                .unwrap()
+                .track("a/")
+                .is_err()
+        );
+    }
+}
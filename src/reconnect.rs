@@ -0,0 +1,276 @@
+//! Automatic reconnection to the relay with exponential backoff.
+
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use anyhow::Result;
+use moq_lite::{OriginConsumer, OriginProducer, Session};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::connect_bidirectional;
+
+/// The exponential backoff schedule used between connection attempts.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    /// The delay before the first retry, doubled for each subsequent retry.
+    pub base: Duration,
+    /// The upper bound on the delay between retries, before jitter is applied.
+    pub max: Duration,
+    /// The fraction of each delay (`0.0..=1.0`) which is randomly added on top of it.
+    pub jitter: f64,
+    /// The number of consecutive failed attempts after which connecting gives up.
+    /// If not set, connecting is retried forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Backoff {
+    /// The delay before retry number `retry` (starting at `0`), without jitter.
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.base
+            .saturating_mul(2_u32.saturating_pow(retry))
+            .min(self.max)
+    }
+
+    fn jittered_delay(&self, retry: u32) -> Duration {
+        let delay = self.delay(retry);
+        delay + delay.mul_f64(self.jitter.clamp(0.0, 1.0) * jitter_sample())
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+            jitter: 0.1,
+            max_attempts: Some(10),
+        }
+    }
+}
+
+/// A uniformly distributed sample in `0.0..1.0`.
+///
+/// Each [`RandomState`] is randomly keyed, which is sufficient for spreading out retries without
+/// pulling in a dedicated RNG.
+fn jitter_sample() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1_u64 << 53) as f64
+}
+
+/// A connection to the relay which can report when it has closed.
+pub trait RelayConnection: Send + 'static {
+    /// Resolve once the connection has closed for any reason.
+    fn closed(&self) -> impl Future<Output = ()> + Send;
+}
+
+impl RelayConnection for Session {
+    async fn closed(&self) {
+        let _ = Session::closed(self).await;
+    }
+}
+
+/// The origin producer/consumer pair of an established relay connection.
+#[derive(Clone)]
+pub struct Origins {
+    pub producer: OriginProducer,
+    pub consumer: OriginConsumer,
+}
+
+/// Repeatedly call `connector` until it succeeds, sleeping according to `backoff` between
+/// attempts.
+///
+/// Returns the last connection error once [`max_attempts`](Backoff::max_attempts) is reached.
+pub async fn connect_with_backoff<C, Fut, T>(connector: &mut C, backoff: &Backoff) -> Result<T>
+where
+    C: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempts = 0;
+
+    loop {
+        let err = match connector().await {
+            Ok(connection) => return Ok(connection),
+            Err(err) => err,
+        };
+
+        attempts += 1;
+        if backoff.max_attempts.is_some_and(|max| attempts >= max) {
+            return Err(err.context(format!("giving up after {attempts} connection attempts")));
+        }
+
+        let delay = backoff.jittered_delay(attempts - 1);
+        warn!(attempts, ?delay, error = %err, "Failed to connect to relay, retrying");
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// A relay session that is automatically re-established with backoff whenever it closes.
+///
+/// The [`Origins`] of the current connection are published through a [`watch`] channel, which
+/// holds `None` while disconnected. The background task stops when the [`ReconnectingSession`]
+/// is dropped.
+pub struct ReconnectingSession {
+    origins: watch::Receiver<Option<Origins>>,
+    task: JoinHandle<Result<()>>,
+}
+
+impl ReconnectingSession {
+    /// Connect to the relay at `relay_url` as a publisher + subscriber, reconnecting on close.
+    pub fn connect(relay_url: impl Into<String>, backoff: Backoff) -> Self {
+        let relay_url = relay_url.into();
+        Self::spawn(
+            move || {
+                let relay_url = relay_url.clone();
+                async move { connect_bidirectional(&relay_url).await }
+            },
+            backoff,
+        )
+    }
+
+    /// Maintain a connection established by `connector`, reconnecting on close.
+    pub fn spawn<C, Fut, S>(mut connector: C, backoff: Backoff) -> Self
+    where
+        C: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(S, OriginProducer, OriginConsumer)>> + Send,
+        S: RelayConnection,
+    {
+        let (origins_tx, origins) = watch::channel(None);
+
+        let task = tokio::spawn(async move {
+            loop {
+                let (session, producer, consumer) =
+                    connect_with_backoff(&mut connector, &backoff).await?;
+
+                info!("Connected to relay");
+                origins_tx.send_replace(Some(Origins { producer, consumer }));
+
+                session.closed().await;
+
+                warn!("Relay session closed, reconnecting");
+                origins_tx.send_replace(None);
+            }
+        });
+
+        Self { origins, task }
+    }
+
+    /// Subscribe to the [`Origins`] of the current connection.
+    pub fn origins(&self) -> watch::Receiver<Option<Origins>> {
+        self.origins.clone()
+    }
+
+    /// Wait until reconnecting gives up, returning the final connection error.
+    pub async fn join(mut self) -> Result<()> {
+        (&mut self.task).await?
+    }
+}
+
+impl Drop for ReconnectingSession {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use anyhow::anyhow;
+    use moq_lite::Origin;
+    use tokio::sync::Notify;
+    use tokio::time::Instant;
+
+    use super::*;
+
+    struct MockConnection {
+        closed: Arc<Notify>,
+    }
+
+    impl RelayConnection for MockConnection {
+        async fn closed(&self) {
+            self.closed.notified().await;
+        }
+    }
+
+    fn backoff(max_attempts: u32) -> Backoff {
+        Backoff {
+            base: Duration::from_millis(100),
+            max: Duration::from_millis(250),
+            jitter: 0.0,
+            max_attempts: Some(max_attempts),
+        }
+    }
+
+    #[test]
+    fn test_backoff_delay_is_capped() {
+        let backoff = backoff(5);
+
+        assert_eq!(backoff.delay(0), Duration::from_millis(100));
+        assert_eq!(backoff.delay(1), Duration::from_millis(200));
+        assert_eq!(backoff.delay(2), Duration::from_millis(250));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_millis(250));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_after_max_attempts() {
+        let start = Instant::now();
+        let mut attempts = Vec::new();
+
+        let result: Result<()> = connect_with_backoff(
+            &mut || {
+                attempts.push(start.elapsed());
+                async { Err(anyhow!("relay unavailable")) }
+            },
+            &backoff(4),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            attempts,
+            [0, 100, 300, 550].map(Duration::from_millis).to_vec()
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reconnects_after_close() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let closed = Arc::new(Notify::new());
+
+        let connector_attempts = Arc::clone(&attempts);
+        let connector_closed = Arc::clone(&closed);
+        let session = ReconnectingSession::spawn(
+            move || {
+                let attempt = connector_attempts.fetch_add(1, Ordering::SeqCst);
+                let closed = Arc::clone(&connector_closed);
+                async move {
+                    // Fail the first attempt to exercise the backoff path
+                    if attempt == 0 {
+                        return Err(anyhow!("relay unavailable"));
+                    }
+
+                    let origin = Origin::produce();
+                    Ok((MockConnection { closed }, origin.producer, origin.consumer))
+                }
+            },
+            backoff(3),
+        );
+
+        let mut origins = session.origins();
+        origins.wait_for(Option::is_some).await.unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        // The disconnected `None` may be replaced before it is observed, so wait for the new
+        // connection instead
+        closed.notify_one();
+        origins
+            .wait_for(|current| current.is_some() && attempts.load(Ordering::SeqCst) == 3)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,77 @@
+//! Graceful shutdown shared by the binaries.
+
+use moq_lite::Session;
+use tracing::{info, warn};
+
+use crate::drone::{DroneSession, DroneSessionMap};
+
+/// Wait for the process to be asked to shut down with SIGINT (ctrl-c).
+///
+/// If the signal handler cannot be installed this never resolves, so the binary keeps running as
+/// it would have without graceful shutdown.
+pub async fn shutdown_signal() {
+    match tokio::signal::ctrl_c().await {
+        Ok(()) => info!("Received SIGINT, shutting down"),
+        Err(e) => {
+            warn!(error = %e, "Failed to listen for SIGINT, graceful shutdown disabled");
+            std::future::pending().await
+        }
+    }
+}
+
+/// End every active drone session, returning the sessions which were ended.
+pub fn end_sessions(session_map: &DroneSessionMap) -> Vec<DroneSession> {
+    let ended = session_map.remove_all();
+    for session in &ended {
+        info!(
+            drone_id = %session.unit_id,
+            session_id = %session.session_id,
+            "Ended session on shutdown"
+        );
+    }
+    ended
+}
+
+/// Close the connection to the relay.
+///
+/// Broadcasts are unannounced when their producers are dropped, so drop them before closing to
+/// let the relay tell subscribers they are gone rather than the connection being lost.
+pub fn close_relay(session: Session) {
+    session.close(moq_lite::Error::Cancel);
+    info!("Closed relay connection");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drone::SessionEvent;
+    use crate::unit::UnitId;
+
+    #[test]
+    fn test_end_sessions_removes_created_session() {
+        let session_map = DroneSessionMap::new();
+        let unit_id = UnitId::from("drone-1");
+        let session_id = session_map.create_session(&unit_id).unwrap();
+        let mut events = session_map.subscribe();
+
+        let ended = end_sessions(&session_map);
+
+        assert_eq!(ended.len(), 1);
+        assert_eq!(ended[0].session_id, session_id);
+        assert!(!session_map.has_active_session(&unit_id));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SessionEvent::Removed {
+                unit_id,
+                session_id
+            }
+        );
+    }
+
+    #[test]
+    fn test_end_sessions_without_sessions() {
+        let session_map = DroneSessionMap::new();
+
+        assert!(end_sessions(&session_map).is_empty());
+    }
+}
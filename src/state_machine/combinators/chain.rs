@@ -0,0 +1,138 @@
+use crate::state_machine::StateMachine;
+
+/// A [`StateMachine`] feeding the output of state machine `A` as input to state machine `B`.
+///
+/// Each processed input drives `A`, after which all of its available output is drained into `B`.
+/// Output is then [polled](StateMachine::poll_output) from `B`, so `A`'s output is never exposed
+/// directly.
+#[derive(Debug, Default)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Chain<A, B> {
+    /// Chain `first` into `second`.
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// The first state machine of the chain.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// The second state machine of the chain.
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// Unwrap both state machines of the chain.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A, B> StateMachine for Chain<A, B>
+where
+    A: StateMachine,
+    B: StateMachine,
+    A::Output: Into<B::Input>,
+{
+    type Input = A::Input;
+    type Output = B::Output;
+
+    fn process_input(&mut self, input: Self::Input) {
+        self.first.process_input(input);
+
+        while let Some(output) = self.first.poll_output() {
+            self.second.process_input(output.into());
+        }
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.second.poll_output()
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    /// Outputs only the even inputs it processes.
+    #[derive(Debug, Default)]
+    struct EvenFilter {
+        queue: VecDeque<u32>,
+    }
+
+    impl StateMachine for EvenFilter {
+        type Input = u32;
+        type Output = u32;
+
+        fn process_input(&mut self, input: Self::Input) {
+            if input % 2 == 0 {
+                self.queue.push_back(input);
+            }
+        }
+
+        fn poll_output(&mut self) -> Option<Self::Output> {
+            self.queue.pop_front()
+        }
+
+        fn reset(&mut self) {
+            self.queue.clear();
+        }
+    }
+
+    /// Outputs double each input it processes.
+    #[derive(Debug, Default)]
+    struct Doubler {
+        queue: VecDeque<u64>,
+    }
+
+    impl StateMachine for Doubler {
+        type Input = u64;
+        type Output = u64;
+
+        fn process_input(&mut self, input: Self::Input) {
+            self.queue.push_back(input * 2);
+        }
+
+        fn poll_output(&mut self) -> Option<Self::Output> {
+            self.queue.pop_front()
+        }
+
+        fn reset(&mut self) {
+            self.queue.clear();
+        }
+    }
+
+    #[test]
+    fn test_chain_feeds_first_output_into_second() {
+        let mut chain = Chain::new(EvenFilter::default(), Doubler::default());
+
+        chain.process_batch(1..=6);
+
+        assert_eq!(chain.drain_output().collect::<Vec<_>>(), vec![4, 8, 12]);
+        assert!(chain.first().queue.is_empty());
+    }
+
+    #[test]
+    fn test_reset_resets_both_machines() {
+        let mut chain = Chain::new(EvenFilter::default(), Doubler::default());
+        chain.process_batch(1..=4);
+
+        chain.reset();
+
+        assert!(chain.poll_output().is_none());
+        assert!(chain.first().queue.is_empty());
+        assert!(chain.second().queue.is_empty());
+    }
+}
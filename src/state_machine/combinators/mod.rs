@@ -0,0 +1,9 @@
+//! Combinators composing [`StateMachine`](super::StateMachine)s into larger state machines.
+//!
+//! Since each composed state machine is pure, so is their composition.
+
+mod chain;
+mod tee;
+
+pub use chain::Chain;
+pub use tee::{Tee, TeeSlot};
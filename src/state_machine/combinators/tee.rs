@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+use std::collections::vec_deque::Drain;
+
+use crate::state_machine::StateMachine;
+
+/// A handle to one of the output queues of a [`Tee`], created by [`Tee::fan_out`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TeeSlot(usize);
+
+/// A [`StateMachine`] wrapper copying every output of the wrapped state machine `S` into a set of
+/// independent queues, so the same output can be consumed by multiple consumers.
+///
+/// Each output [polled](StateMachine::poll_output) from the tee is returned as normal and a clone
+/// of it is pushed onto every queue registered with [`fan_out`](Tee::fan_out). Each queue is then
+/// drained separately with [`drain`](Tee::drain). Queues only receive outputs polled after they
+/// were registered.
+#[derive(Debug)]
+pub struct Tee<S: StateMachine> {
+    machine: S,
+    slots: Vec<VecDeque<S::Output>>,
+}
+
+impl<S> Tee<S>
+where
+    S: StateMachine,
+    S::Output: Clone,
+{
+    /// Wrap `machine` with no output queues.
+    pub fn new(machine: S) -> Self {
+        Self {
+            machine,
+            slots: Vec::new(),
+        }
+    }
+
+    /// Register `n` new output queues, returning a handle to each.
+    pub fn fan_out(&mut self, n: usize) -> Vec<TeeSlot> {
+        let start = self.slots.len();
+        self.slots.resize_with(start + n, VecDeque::new);
+        (start..start + n).map(TeeSlot).collect()
+    }
+
+    /// Poll all available output of the wrapped state machine into the output queues, returning
+    /// the number of outputs polled.
+    pub fn distribute(&mut self) -> usize {
+        self.drain_output().count()
+    }
+
+    /// Drain the outputs queued for `slot`, oldest first.
+    ///
+    /// # Panics
+    /// Panics if `slot` was not created by this tee.
+    pub fn drain(&mut self, slot: TeeSlot) -> Drain<'_, S::Output> {
+        self.slots[slot.0].drain(..)
+    }
+
+    /// The wrapped state machine.
+    pub fn machine(&self) -> &S {
+        &self.machine
+    }
+}
+
+impl<S> StateMachine for Tee<S>
+where
+    S: StateMachine,
+    S::Output: Clone,
+{
+    type Input = S::Input;
+    type Output = S::Output;
+
+    fn process_input(&mut self, input: Self::Input) {
+        self.machine.process_input(input);
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        let output = self.machine.poll_output()?;
+        for slot in &mut self.slots {
+            slot.push_back(output.clone());
+        }
+        Some(output)
+    }
+
+    /// Reset the wrapped state machine, discarding any output queued in the output queues.
+    ///
+    /// The output queues themselves stay registered.
+    fn reset(&mut self) {
+        self.machine.reset();
+        self.slots.iter_mut().for_each(VecDeque::clear);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Outputs a running total of the inputs processed.
+    #[derive(Debug, Default)]
+    struct SumMachine {
+        total: u32,
+        pending: VecDeque<u32>,
+    }
+
+    impl StateMachine for SumMachine {
+        type Input = u32;
+        type Output = u32;
+
+        fn process_input(&mut self, input: Self::Input) {
+            self.total += input;
+            self.pending.push_back(self.total);
+        }
+
+        fn poll_output(&mut self) -> Option<Self::Output> {
+            self.pending.pop_front()
+        }
+
+        fn reset(&mut self) {
+            self.total = 0;
+            self.pending.clear();
+        }
+    }
+
+    #[test]
+    fn test_fan_out_queues_receive_identical_outputs() {
+        let mut tee = Tee::new(SumMachine::default());
+        let slots = tee.fan_out(2);
+
+        tee.process_batch([1, 2, 3]);
+        assert_eq!(tee.poll_output(), Some(1));
+        assert_eq!(tee.distribute(), 2);
+
+        let first: Vec<_> = tee.drain(slots[0]).collect();
+        let second: Vec<_> = tee.drain(slots[1]).collect();
+        assert_eq!(first, vec![1, 3, 6]);
+        assert_eq!(first, second);
+        assert_eq!(tee.drain(slots[0]).count(), 0);
+    }
+
+    #[test]
+    fn test_slots_only_receive_later_outputs() {
+        let mut tee = Tee::new(SumMachine::default());
+        let [early] = tee.fan_out(1)[..] else {
+            unreachable!()
+        };
+
+        tee.process_input(1);
+        tee.distribute();
+        let [late] = tee.fan_out(1)[..] else {
+            unreachable!()
+        };
+        tee.process_input(2);
+        tee.distribute();
+
+        assert_eq!(tee.drain(early).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(tee.drain(late).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_reset_clears_queues() {
+        let mut tee = Tee::new(SumMachine::default());
+        let slots = tee.fan_out(1);
+        tee.process_batch([1, 2]);
+        tee.distribute();
+
+        tee.reset();
+
+        assert_eq!(tee.drain(slots[0]).count(), 0);
+        tee.process_input(5);
+        tee.distribute();
+        assert_eq!(tee.drain(slots[0]).collect::<Vec<_>>(), vec![5]);
+    }
+}
@@ -1,12 +1,20 @@
+use std::collections::VecDeque;
+
 use super::StateMachine;
+use super::wrappers::output::{Wait, WaitReason};
+use crate::drone_proto::DronePosition;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct EchoMachine {
     latest_position: Option<Position>,
     pending: bool,
+    history: VecDeque<Position>,
+    history_capacity: usize,
+    offset: PositionOffset,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     pub drone_id: String,
     pub latitude: f64,
@@ -17,15 +25,302 @@ pub struct Position {
     pub timestamp: u64,
 }
 
+/// A position of `drone-1` at `timestamp`, shared by tests as a fixture.
+#[cfg(test)]
+pub(crate) fn position(timestamp: u64) -> Position {
+    Position {
+        drone_id: "drone-1".to_string(),
+        latitude: 37.7749,
+        longitude: -122.4194,
+        altitude_m: 100.0,
+        heading_deg: 0.0,
+        speed_mps: 0.0,
+        timestamp,
+    }
+}
+
+/// The [`position`] fixture moved to `latitude` and `longitude`.
+#[cfg(test)]
+pub(crate) fn position_at(latitude: f64, longitude: f64) -> Position {
+    Position {
+        latitude,
+        longitude,
+        ..position(0)
+    }
+}
+
+/// Mean radius of the earth in metres, used to convert distances to degrees.
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+impl Position {
+    /// Linearly interpolate between this position and `other` at fraction `t`.
+    ///
+    /// A `t` of 0 gives this position and 1 gives `other`, values outside that range extrapolate.
+    /// Heading is interpolated along the shorter arc, so halfway between 350° and 10° is 0°. The
+    /// drone id of this position is kept and the timestamp is rounded to the nearest second.
+    pub fn interpolate(&self, other: &Position, t: f64) -> Position {
+        let lerp = |from: f64, to: f64| from + (to - from) * t;
+        let heading_delta =
+            (other.heading_deg - self.heading_deg + 540.0).rem_euclid(360.0) - 180.0;
+        let timestamp = lerp(self.timestamp as f64, other.timestamp as f64);
+
+        Position {
+            drone_id: self.drone_id.clone(),
+            latitude: lerp(self.latitude, other.latitude),
+            longitude: lerp(self.longitude, other.longitude),
+            altitude_m: lerp(self.altitude_m, other.altitude_m),
+            heading_deg: (self.heading_deg + heading_delta * t).rem_euclid(360.0),
+            speed_mps: lerp(self.speed_mps, other.speed_mps),
+            timestamp: timestamp.round() as u64,
+        }
+    }
+
+    /// The great-circle distance to `other` in metres, ignoring altitude.
+    pub fn distance_to(&self, other: &Position) -> f64 {
+        let lat1 = self.latitude.to_radians();
+        let lat2 = other.latitude.to_radians();
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude - self.longitude).to_radians();
+
+        // Haversine formula
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+    }
+
+    /// The initial bearing of the great-circle path to `other`, in degrees clockwise from north
+    /// within `[0, 360)`.
+    pub fn bearing_to(&self, other: &Position) -> f64 {
+        let lat1 = self.latitude.to_radians();
+        let lat2 = other.latitude.to_radians();
+        let dlon = (other.longitude - self.longitude).to_radians();
+
+        let y = dlon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Project this position forward by `elapsed_secs`, assuming the drone holds its heading,
+    /// speed and altitude.
+    ///
+    /// Heading is in degrees clockwise from north. The timestamp advances by `elapsed_secs`,
+    /// rounded to the nearest second.
+    pub fn dead_reckon(&self, elapsed_secs: f64) -> Position {
+        let distance_m = self.speed_mps * elapsed_secs;
+        let heading = self.heading_deg.to_radians();
+        let timestamp = (self.timestamp as f64 + elapsed_secs).round() as u64;
+
+        Position {
+            timestamp,
+            ..self.offset_by(distance_m * heading.cos(), distance_m * heading.sin())
+        }
+    }
+
+    /// This position moved `north_m` metres north and `east_m` metres east, negative values
+    /// moving south and west.
+    ///
+    /// The distance moved is assumed small relative to the earth, so it is converted to degrees on
+    /// a locally flat approximation.
+    pub fn offset_by(&self, north_m: f64, east_m: f64) -> Position {
+        let latitude = self.latitude + (north_m / EARTH_RADIUS_M).to_degrees();
+        let longitude = self.longitude
+            + (east_m / (EARTH_RADIUS_M * self.latitude.to_radians().cos())).to_degrees();
+
+        Position {
+            latitude,
+            longitude,
+            ..self.clone()
+        }
+    }
+}
+
+impl From<DronePosition> for Position {
+    fn from(pos: DronePosition) -> Self {
+        Self {
+            drone_id: pos.drone_id,
+            latitude: pos.latitude,
+            longitude: pos.longitude,
+            altitude_m: pos.altitude_m,
+            heading_deg: pos.heading_deg,
+            speed_mps: pos.speed_mps,
+            timestamp: pos.timestamp,
+        }
+    }
+}
+
+impl From<Position> for DronePosition {
+    fn from(pos: Position) -> Self {
+        Self {
+            drone_id: pos.drone_id,
+            latitude: pos.latitude,
+            longitude: pos.longitude,
+            altitude_m: pos.altitude_m,
+            heading_deg: pos.heading_deg,
+            speed_mps: pos.speed_mps,
+            timestamp: pos.timestamp,
+            // Sequencing belongs to the sender, which sets it per frame
+            sequence: 0,
+            health: None,
+        }
+    }
+}
+
+/// A shift applied by an [`EchoMachine`] to the coordinates of every position it processes.
+///
+/// The default offset is zero, echoing positions unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PositionOffset {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl PositionOffset {
+    fn apply(&self, pos: Position) -> Position {
+        Position {
+            latitude: pos.latitude + self.latitude,
+            longitude: pos.longitude + self.longitude,
+            ..pos
+        }
+    }
+}
+
+/// A capture of the full internal state of an [`EchoMachine`].
+///
+/// Created by [`EchoMachine::snapshot`] and applied with [`EchoMachine::restore`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EchoSnapshot {
+    pub latest_position: Option<Position>,
+    pub pending: bool,
+    pub history: VecDeque<Position>,
+}
+
 impl EchoMachine {
     pub fn new() -> Self {
+        Self::with_history(0)
+    }
+
+    /// Construct an [`EchoMachine`] which retains the last `capacity` positions as history.
+    ///
+    /// When the history is full the oldest position is evicted to make room for the newest.
+    pub fn with_history(capacity: usize) -> Self {
         Self {
             latest_position: None,
             pending: false,
+            history: VecDeque::with_capacity(capacity),
+            history_capacity: capacity,
+            offset: PositionOffset::default(),
+        }
+    }
+
+    /// Shift every position processed by the machine by `offset`.
+    ///
+    /// The shifted position is what is output, retained as history and returned by
+    /// [`current_position`](Self::current_position).
+    pub fn with_offset(mut self, offset: PositionOffset) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// The most recently processed position, if any.
+    pub fn current_position(&self) -> Option<&Position> {
+        self.latest_position.as_ref()
+    }
+
+    /// The retained position history, ordered from oldest to newest.
+    pub fn history(&self) -> impl Iterator<Item = &Position> {
+        self.history.iter()
+    }
+
+    /// The average of the most recent `window` positions in the history, or of the whole history
+    /// if it holds fewer.
+    ///
+    /// The drone id and timestamp are those of the newest position. Heading is averaged as a
+    /// direction, so the average of 350° and 10° is 0°. Returns `None` if `window` is zero or the
+    /// history is empty.
+    pub fn average(&self, window: usize) -> Option<Position> {
+        let newest = self.history.back()?;
+        let recent = self
+            .history
+            .range(self.history.len().saturating_sub(window)..);
+        let count = recent.len();
+        if count == 0 {
+            return None;
+        }
+
+        let mut sum = Position {
+            latitude: 0.0,
+            longitude: 0.0,
+            altitude_m: 0.0,
+            heading_deg: 0.0,
+            speed_mps: 0.0,
+            ..newest.clone()
+        };
+        let (mut heading_sin, mut heading_cos) = (0.0, 0.0);
+        for pos in recent {
+            sum.latitude += pos.latitude;
+            sum.longitude += pos.longitude;
+            sum.altitude_m += pos.altitude_m;
+            sum.speed_mps += pos.speed_mps;
+            heading_sin += pos.heading_deg.to_radians().sin();
+            heading_cos += pos.heading_deg.to_radians().cos();
+        }
+
+        let n = count as f64;
+        Some(Position {
+            latitude: sum.latitude / n,
+            longitude: sum.longitude / n,
+            altitude_m: sum.altitude_m / n,
+            heading_deg: heading_sin
+                .atan2(heading_cos)
+                .to_degrees()
+                .rem_euclid(360.0),
+            speed_mps: sum.speed_mps / n,
+            ..sum
+        })
+    }
+
+    /// Whether the most recently processed position is older than `max_age_secs` at the time
+    /// `now`, or no position has been processed at all.
+    ///
+    /// Both `now` and the position [`timestamp`](Position::timestamp) are in unix seconds. Time is
+    /// provided by the caller since the machine cannot read the clock itself.
+    pub fn is_stale(&self, now: u64, max_age_secs: u64) -> bool {
+        self.latest_position
+            .as_ref()
+            .is_none_or(|pos| now.saturating_sub(pos.timestamp) > max_age_secs)
+    }
+
+    /// Capture the current state of the machine, including any pending output.
+    pub fn snapshot(&self) -> EchoSnapshot {
+        EchoSnapshot {
+            latest_position: self.latest_position.clone(),
+            pending: self.pending,
+            history: self.history.clone(),
         }
     }
 
+    /// Restore the machine to the state captured by `snapshot`.
+    ///
+    /// The history capacity of this machine is kept, evicting the oldest snapshotted positions if
+    /// the snapshot holds more history than fits.
+    pub fn restore(&mut self, snapshot: EchoSnapshot) {
+        self.latest_position = snapshot.latest_position;
+        self.pending = snapshot.pending;
+        self.history = snapshot.history;
+
+        let excess = self.history.len().saturating_sub(self.history_capacity);
+        self.history.drain(..excess);
+    }
+
     fn update_position(&mut self, pos: Position) {
+        let pos = self.offset.apply(pos);
+
+        if self.history_capacity > 0 {
+            if self.history.len() == self.history_capacity {
+                self.history.pop_front();
+            }
+            self.history.push_back(pos.clone());
+        }
+
         self.latest_position = Some(pos);
         self.pending = true;
     }
@@ -46,10 +341,12 @@ impl Default for EchoMachine {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum EchoInput {
     Position(Position),
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum EchoOutput {
     Position(Position),
 }
@@ -67,4 +364,366 @@ impl StateMachine for EchoMachine {
     fn poll_output(&mut self) -> Option<Self::Output> {
         self.poll_position().map(EchoOutput::Position)
     }
+
+    fn reset(&mut self) {
+        self.latest_position = None;
+        self.pending = false;
+        self.history.clear();
+    }
+}
+
+impl Wait for EchoMachine {
+    fn wait_reason(&self) -> WaitReason {
+        WaitReason::Input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drone_position_round_trip() {
+        let proto = DronePosition {
+            drone_id: "drone-1".to_string(),
+            latitude: 37.7749,
+            longitude: -122.4194,
+            altitude_m: 100.5,
+            heading_deg: 270.25,
+            speed_mps: 12.75,
+            timestamp: 1_700_000_000,
+            sequence: 0,
+            health: None,
+        };
+
+        let pos = Position::from(proto.clone());
+        assert_eq!(
+            pos,
+            Position {
+                drone_id: "drone-1".to_string(),
+                latitude: 37.7749,
+                longitude: -122.4194,
+                altitude_m: 100.5,
+                heading_deg: 270.25,
+                speed_mps: 12.75,
+                timestamp: 1_700_000_000,
+            }
+        );
+        assert_eq!(DronePosition::from(pos), proto);
+    }
+
+    #[test]
+    fn test_history_evicts_oldest_at_capacity() {
+        let mut machine = EchoMachine::with_history(2);
+
+        machine.process_batch((1..=3).map(|ts| EchoInput::Position(position(ts))));
+
+        let timestamps: Vec<_> = machine.history().map(|pos| pos.timestamp).collect();
+        assert_eq!(timestamps, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_current_position_is_newest() {
+        let mut machine = EchoMachine::with_history(2);
+
+        machine.process_batch((1..=3).map(|ts| EchoInput::Position(position(ts))));
+
+        assert_eq!(machine.current_position(), Some(&position(3)));
+        assert!(matches!(
+            machine.poll_output(),
+            Some(EchoOutput::Position(pos)) if pos == position(3)
+        ));
+        assert!(machine.poll_output().is_none());
+    }
+
+    #[test]
+    fn test_no_history_by_default() {
+        let mut machine = EchoMachine::new();
+
+        machine.process_input(EchoInput::Position(position(1)));
+
+        assert_eq!(machine.history().count(), 0);
+        assert_eq!(machine.current_position(), Some(&position(1)));
+    }
+
+    #[test]
+    fn test_reset_matches_fresh_machine() {
+        let mut machine = EchoMachine::with_history(2);
+        machine.process_batch((1..=3).map(|ts| EchoInput::Position(position(ts))));
+
+        machine.reset();
+
+        assert!(machine.poll_output().is_none());
+        assert_eq!(machine, EchoMachine::with_history(2));
+    }
+
+    #[test]
+    fn test_restore_reflects_snapshot() {
+        let mut machine = EchoMachine::with_history(2);
+        machine.process_input(EchoInput::Position(position(1)));
+        let snapshot = machine.snapshot();
+
+        machine.process_input(EchoInput::Position(position(2)));
+        machine.restore(snapshot.clone());
+
+        assert_eq!(machine.snapshot(), snapshot);
+        assert_eq!(machine.current_position(), Some(&position(1)));
+        assert!(matches!(
+            machine.poll_output(),
+            Some(EchoOutput::Position(pos)) if pos == position(1)
+        ));
+        assert!(machine.poll_output().is_none());
+    }
+
+    #[test]
+    fn test_default_offset_is_identity() {
+        let mut machine = EchoMachine::new();
+
+        machine.process_input(EchoInput::Position(position(1)));
+
+        assert_eq!(
+            machine.poll_output(),
+            Some(EchoOutput::Position(position(1)))
+        );
+    }
+
+    #[test]
+    fn test_offset_shifts_position() {
+        let mut machine = EchoMachine::with_history(1).with_offset(PositionOffset {
+            latitude: 0.5,
+            longitude: -0.25,
+        });
+
+        machine.process_input(EchoInput::Position(position(1)));
+
+        let expected = Position {
+            latitude: 37.7749 + 0.5,
+            longitude: -122.4194 - 0.25,
+            ..position(1)
+        };
+        assert_eq!(machine.history().next(), Some(&expected));
+        assert_eq!(machine.poll_output(), Some(EchoOutput::Position(expected)));
+    }
+
+    #[test]
+    fn test_is_stale_without_position() {
+        let machine = EchoMachine::new();
+
+        assert!(machine.is_stale(0, u64::MAX));
+    }
+
+    #[test]
+    fn test_is_stale_fresh_position() {
+        let mut machine = EchoMachine::new();
+        machine.process_input(EchoInput::Position(position(100)));
+
+        assert!(!machine.is_stale(100, 10));
+        assert!(!machine.is_stale(110, 10));
+        // A position timestamped after `now` is not stale
+        assert!(!machine.is_stale(90, 10));
+    }
+
+    #[test]
+    fn test_is_stale_old_position() {
+        let mut machine = EchoMachine::new();
+        machine.process_input(EchoInput::Position(position(100)));
+
+        assert!(machine.is_stale(111, 10));
+    }
+
+    fn moving(heading_deg: f64, speed_mps: f64) -> Position {
+        Position {
+            heading_deg,
+            speed_mps,
+            ..position(100)
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_interpolate_endpoints() {
+        let from = moving(10.0, 5.0);
+        let to = Position {
+            latitude: 38.0,
+            longitude: -122.0,
+            altitude_m: 200.0,
+            timestamp: 110,
+            ..moving(30.0, 15.0)
+        };
+
+        assert_eq!(from.interpolate(&to, 0.0), from);
+        assert_eq!(from.interpolate(&to, 1.0), to);
+    }
+
+    #[test]
+    fn test_interpolate_midpoint() {
+        let from = moving(10.0, 5.0);
+        let to = Position {
+            latitude: 38.0,
+            longitude: -122.0,
+            altitude_m: 200.0,
+            timestamp: 110,
+            ..moving(30.0, 15.0)
+        };
+
+        let mid = from.interpolate(&to, 0.5);
+
+        assert_close(mid.latitude, (37.7749 + 38.0) / 2.0);
+        assert_close(mid.longitude, (-122.4194 - 122.0) / 2.0);
+        assert_close(mid.altitude_m, 150.0);
+        assert_close(mid.heading_deg, 20.0);
+        assert_close(mid.speed_mps, 10.0);
+        assert_eq!(mid.timestamp, 105);
+    }
+
+    #[test]
+    fn test_interpolate_heading_wraps_north() {
+        let mid = moving(350.0, 0.0).interpolate(&moving(10.0, 0.0), 0.5);
+
+        assert_close(mid.heading_deg, 0.0);
+    }
+
+    #[test]
+    fn test_dead_reckon_moves_east() {
+        let start = moving(90.0, 10.0);
+
+        let next = start.dead_reckon(10.0);
+
+        // 100m due east at this latitude
+        let expected_longitude = start.longitude
+            + (100.0 / (EARTH_RADIUS_M * start.latitude.to_radians().cos())).to_degrees();
+        assert_close(next.latitude, start.latitude);
+        assert_close(next.longitude, expected_longitude);
+        assert_eq!(next.altitude_m, start.altitude_m);
+        assert_eq!(next.timestamp, 110);
+    }
+
+    #[test]
+    fn test_dead_reckon_stationary() {
+        let start = moving(45.0, 0.0);
+
+        let next = start.dead_reckon(30.0);
+
+        assert_eq!(next.latitude, start.latitude);
+        assert_eq!(next.longitude, start.longitude);
+        assert_eq!(next.timestamp, 130);
+    }
+
+    #[test]
+    fn test_distance_one_degree_of_latitude() {
+        let distance = position_at(0.0, 0.0).distance_to(&position_at(1.0, 0.0));
+
+        // One degree of arc on a sphere of the mean earth radius
+        assert!((distance - 111_194.93).abs() < 1.0, "got {distance}");
+    }
+
+    #[test]
+    fn test_distance_between_cities() {
+        // San Francisco to Los Angeles, roughly 559km
+        let distance =
+            position_at(37.7749, -122.4194).distance_to(&position_at(34.0522, -118.2437));
+
+        assert!((distance - 559_120.0).abs() < 1_000.0, "got {distance}");
+    }
+
+    #[test]
+    fn test_distance_to_self_is_zero() {
+        let pos = position_at(37.7749, -122.4194);
+
+        assert_eq!(pos.distance_to(&pos), 0.0);
+    }
+
+    #[test]
+    fn test_bearing_cardinal_directions() {
+        let origin = position_at(0.0, 0.0);
+
+        assert!((origin.bearing_to(&position_at(1.0, 0.0)) - 0.0).abs() < 1e-9);
+        assert!((origin.bearing_to(&position_at(0.0, 1.0)) - 90.0).abs() < 1e-9);
+        assert!((origin.bearing_to(&position_at(-1.0, 0.0)) - 180.0).abs() < 1e-9);
+        assert!((origin.bearing_to(&position_at(0.0, -1.0)) - 270.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bearing_between_cities() {
+        // San Francisco to Los Angeles heads south east
+        let bearing = position_at(37.7749, -122.4194).bearing_to(&position_at(34.0522, -118.2437));
+
+        assert!((bearing - 136.5).abs() < 0.5, "got {bearing}");
+    }
+
+    fn sample(timestamp: u64, latitude: f64, altitude_m: f64, heading_deg: f64) -> EchoInput {
+        EchoInput::Position(Position {
+            drone_id: format!("drone-{timestamp}"),
+            latitude,
+            altitude_m,
+            heading_deg,
+            speed_mps: timestamp as f64,
+            ..position(timestamp)
+        })
+    }
+
+    #[test]
+    fn test_average_over_window() {
+        let mut machine = EchoMachine::with_history(5);
+        machine.process_batch([
+            sample(1, 10.0, 1000.0, 90.0),
+            sample(2, 37.0, 100.0, 350.0),
+            sample(3, 38.0, 110.0, 0.0),
+            sample(4, 39.0, 120.0, 10.0),
+        ]);
+
+        let avg = machine.average(3).unwrap();
+
+        assert_eq!(avg.drone_id, "drone-4");
+        assert_eq!(avg.timestamp, 4);
+        assert!((avg.latitude - 38.0).abs() < 1e-9);
+        assert!((avg.longitude - -122.4194).abs() < 1e-9);
+        assert!((avg.altitude_m - 110.0).abs() < 1e-9);
+        assert!((avg.speed_mps - 3.0).abs() < 1e-9);
+        // 350°, 0° and 10° average to north rather than 120°
+        assert!(avg.heading_deg.min(360.0 - avg.heading_deg) < 1e-9);
+    }
+
+    #[test]
+    fn test_average_window_larger_than_history() {
+        let mut machine = EchoMachine::with_history(5);
+        machine.process_batch([sample(1, 10.0, 100.0, 0.0), sample(2, 20.0, 200.0, 90.0)]);
+
+        let avg = machine.average(10).unwrap();
+
+        assert!((avg.latitude - 15.0).abs() < 1e-9);
+        assert!((avg.altitude_m - 150.0).abs() < 1e-9);
+        assert!((avg.heading_deg - 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_average_without_history() {
+        let mut machine = EchoMachine::new();
+        assert!(machine.average(3).is_none());
+
+        machine.process_input(sample(1, 10.0, 100.0, 0.0));
+        // Without history capacity nothing is retained to average
+        assert!(machine.average(3).is_none());
+
+        let mut machine = EchoMachine::with_history(2);
+        machine.process_input(sample(1, 10.0, 100.0, 0.0));
+        assert!(machine.average(0).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_position_json_round_trip() {
+        let pos = position(1_700_000_000);
+
+        let json = serde_json::to_string(&pos).unwrap();
+
+        assert!(!json.contains('\n'));
+        assert_eq!(serde_json::from_str::<Position>(&json).unwrap(), pos);
+    }
 }
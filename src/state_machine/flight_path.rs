@@ -0,0 +1,313 @@
+use std::time::Duration;
+
+use super::StateMachine;
+use super::echo::Position;
+use super::wrappers::output::{Wait, WaitReason};
+
+/// Simulates a drone flying a [`Route`] at a constant speed and altitude.
+///
+/// Time only passes through [`FlightPathInput::Tick`], so the positions generated are fully
+/// determined by the route and the ticks fed in. Each tick produces the position reached by then.
+#[derive(Debug, PartialEq)]
+pub struct FlightPath {
+    route: Route,
+    speed_mps: f64,
+    // The position the route starts from, carrying the drone id and altitude
+    origin: Position,
+    elapsed_secs: f64,
+    pending: Option<Position>,
+}
+
+/// A route flown by a [`FlightPath`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    /// Fly clockwise around a circle, starting due north of its center.
+    Orbit {
+        latitude: f64,
+        longitude: f64,
+        radius_m: f64,
+    },
+    /// Fly from waypoint to waypoint in order, returning to the first after the last.
+    Waypoints(Vec<Waypoint>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Waypoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Indicates why a [`Route`] cannot be flown.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidRoute {
+    #[error("route has no waypoints")]
+    NoWaypoints,
+}
+
+impl FlightPath {
+    /// Construct a [`FlightPath`] for `drone_id` flying `route` at `speed_mps` and `altitude_m`.
+    ///
+    /// Fails if `route` is [`Route::Waypoints`] without any waypoints.
+    pub fn new(
+        drone_id: impl Into<String>,
+        route: Route,
+        speed_mps: f64,
+        altitude_m: f64,
+    ) -> Result<Self, InvalidRoute> {
+        let (latitude, longitude) = match &route {
+            Route::Orbit {
+                latitude,
+                longitude,
+                ..
+            } => (*latitude, *longitude),
+            Route::Waypoints(waypoints) => {
+                let first = waypoints.first().ok_or(InvalidRoute::NoWaypoints)?;
+                (first.latitude, first.longitude)
+            }
+        };
+
+        Ok(Self {
+            route,
+            speed_mps,
+            origin: Position {
+                drone_id: drone_id.into(),
+                latitude,
+                longitude,
+                altitude_m,
+                heading_deg: 0.0,
+                speed_mps,
+                timestamp: 0,
+            },
+            elapsed_secs: 0.0,
+            pending: None,
+        })
+    }
+
+    /// The position reached after flying for `elapsed_secs`, stamped with `timestamp`.
+    fn position_at(&self, elapsed_secs: f64, timestamp: u64) -> Position {
+        let distance_m = self.speed_mps * elapsed_secs;
+
+        let pos = match &self.route {
+            Route::Orbit { radius_m, .. } => {
+                // The center is the origin, and a radius of zero hovers over it
+                let angle = if *radius_m > 0.0 {
+                    distance_m / radius_m
+                } else {
+                    0.0
+                };
+                Position {
+                    heading_deg: (angle.to_degrees() + 90.0).rem_euclid(360.0),
+                    ..self
+                        .origin
+                        .offset_by(radius_m * angle.cos(), radius_m * angle.sin())
+                }
+            }
+            Route::Waypoints(waypoints) => self.along_waypoints(waypoints, distance_m),
+        };
+
+        Position { timestamp, ..pos }
+    }
+
+    /// The position `distance_m` along the closed loop through `waypoints`.
+    fn along_waypoints(&self, waypoints: &[Waypoint], distance_m: f64) -> Position {
+        let points: Vec<Position> = waypoints
+            .iter()
+            .map(|waypoint| Position {
+                latitude: waypoint.latitude,
+                longitude: waypoint.longitude,
+                ..self.origin.clone()
+            })
+            .collect();
+        let segments: Vec<(&Position, &Position, f64)> = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(from, to)| (from, to, from.distance_to(to)))
+            .collect();
+
+        let loop_m: f64 = segments.iter().map(|(_, _, length)| length).sum();
+        if loop_m == 0.0 {
+            return points[0].clone();
+        }
+
+        let mut remaining = distance_m.rem_euclid(loop_m);
+        for (from, to, length) in &segments {
+            if remaining < *length {
+                return Position {
+                    heading_deg: from.bearing_to(to),
+                    ..from.interpolate(to, remaining / length)
+                };
+            }
+            remaining -= length;
+        }
+
+        // Only reached through rounding at the very end of the loop
+        points[0].clone()
+    }
+
+    fn tick(&mut self, elapsed: Duration, timestamp: u64) {
+        self.elapsed_secs += elapsed.as_secs_f64();
+        self.pending = Some(self.position_at(self.elapsed_secs, timestamp));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlightPathInput {
+    /// Advance the flight by `elapsed`, stamping the resulting position with `timestamp`.
+    Tick { elapsed: Duration, timestamp: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlightPathOutput {
+    Position(Position),
+}
+
+impl StateMachine for FlightPath {
+    type Input = FlightPathInput;
+    type Output = FlightPathOutput;
+
+    fn process_input(&mut self, input: Self::Input) {
+        match input {
+            FlightPathInput::Tick { elapsed, timestamp } => self.tick(elapsed, timestamp),
+        }
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.pending.take().map(FlightPathOutput::Position)
+    }
+
+    fn reset(&mut self) {
+        self.elapsed_secs = 0.0;
+        self.pending = None;
+    }
+}
+
+impl Wait for FlightPath {
+    fn wait_reason(&self) -> WaitReason {
+        WaitReason::NextTick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::state_machine::echo::position_at;
+
+    const CENTER_LAT: f64 = 37.7749;
+    const CENTER_LON: f64 = -122.4194;
+
+    fn orbit(radius_m: f64, speed_mps: f64) -> FlightPath {
+        FlightPath::new(
+            "drone-1",
+            Route::Orbit {
+                latitude: CENTER_LAT,
+                longitude: CENTER_LON,
+                radius_m,
+            },
+            speed_mps,
+            100.0,
+        )
+        .unwrap()
+    }
+
+    fn step(machine: &mut FlightPath, secs: u64, timestamp: u64) -> Position {
+        machine.process_input(FlightPathInput::Tick {
+            elapsed: Duration::from_secs(secs),
+            timestamp,
+        });
+        let Some(FlightPathOutput::Position(pos)) = machine.poll_output() else {
+            panic!("tick should produce a position");
+        };
+        pos
+    }
+
+    #[test]
+    fn test_orbit_traces_circle_of_radius() {
+        let mut machine = orbit(200.0, 10.0);
+
+        for timestamp in 1..=130 {
+            let pos = step(&mut machine, 1, timestamp);
+            let distance = pos.distance_to(&position_at(CENTER_LAT, CENTER_LON));
+            assert!(
+                (distance - 200.0).abs() < 0.5,
+                "position at {timestamp}s is {distance}m from the center"
+            );
+            assert_eq!(pos.timestamp, timestamp);
+            assert_eq!(pos.altitude_m, 100.0);
+        }
+    }
+
+    #[test]
+    fn test_orbit_completes_lap() {
+        let radius_m = 100.0;
+        let mut machine = orbit(radius_m, 2.0 * PI * radius_m / 100.0);
+
+        // A quarter lap clockwise from north is due east, heading south
+        let quarter = step(&mut machine, 25, 25);
+        assert!((position_at(CENTER_LAT, CENTER_LON).bearing_to(&quarter) - 90.0).abs() < 0.01);
+        assert!((quarter.heading_deg - 180.0).abs() < 1e-9);
+
+        let lap = step(&mut machine, 75, 100);
+        let start = position_at(CENTER_LAT, CENTER_LON).offset_by(radius_m, 0.0);
+        assert!(lap.distance_to(&start) < 0.01);
+    }
+
+    #[test]
+    fn test_waypoints_loop_back_to_start() {
+        let start = Waypoint {
+            latitude: CENTER_LAT,
+            longitude: CENTER_LON,
+        };
+        let north = position_at(CENTER_LAT, CENTER_LON).offset_by(100.0, 0.0);
+        let mut machine = FlightPath::new(
+            "drone-1",
+            Route::Waypoints(vec![
+                start,
+                Waypoint {
+                    latitude: north.latitude,
+                    longitude: north.longitude,
+                },
+            ]),
+            10.0,
+            100.0,
+        )
+        .unwrap();
+
+        let halfway = step(&mut machine, 5, 5);
+        assert!((halfway.distance_to(&position_at(CENTER_LAT, CENTER_LON)) - 50.0).abs() < 0.01);
+        assert!(halfway.heading_deg.abs() < 1e-6);
+
+        // Returning south towards the first waypoint
+        let returning = step(&mut machine, 10, 15);
+        assert!((returning.distance_to(&position_at(CENTER_LAT, CENTER_LON)) - 50.0).abs() < 0.01);
+        assert!((returning.heading_deg - 180.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_waypoints_must_not_be_empty() {
+        let result = FlightPath::new("drone-1", Route::Waypoints(vec![]), 10.0, 100.0);
+
+        assert_eq!(result, Err(InvalidRoute::NoWaypoints));
+    }
+
+    #[test]
+    fn test_no_output_without_tick() {
+        let mut machine = orbit(200.0, 10.0);
+
+        assert!(machine.poll_output().is_none());
+    }
+
+    #[test]
+    fn test_same_ticks_same_positions() {
+        let mut first = orbit(200.0, 10.0);
+        let mut second = orbit(200.0, 10.0);
+
+        for timestamp in 1..=10 {
+            assert_eq!(
+                step(&mut first, 1, timestamp),
+                step(&mut second, 1, timestamp)
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use super::StateMachine;
+use super::echo::Position;
+
+/// Detects when a drone arrives at its goto target.
+///
+/// The machine only reacts to the positions it is fed, so arrival is decided entirely by the
+/// injected telemetry. Once the target is reached it is cleared, so each target is reported as
+/// reached at most once.
+#[derive(Debug, Default, PartialEq)]
+pub struct GotoMachine {
+    target: Option<GotoTarget>,
+    reached: Option<Position>,
+}
+
+/// A point a drone has been sent to, and how close counts as arriving.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GotoTarget {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_m: f64,
+}
+
+impl GotoTarget {
+    /// Whether `pos` is within the radius of the target, ignoring altitude.
+    pub fn contains(&self, pos: &Position) -> bool {
+        let target = Position {
+            latitude: self.latitude,
+            longitude: self.longitude,
+            ..pos.clone()
+        };
+        pos.distance_to(&target) <= self.radius_m
+    }
+}
+
+impl GotoMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The target not yet reached, if any.
+    pub fn target(&self) -> Option<&GotoTarget> {
+        self.target.as_ref()
+    }
+
+    /// Replace the current target, discarding any unpolled arrival at the previous one.
+    fn set_target(&mut self, target: GotoTarget) {
+        self.target = Some(target);
+        self.reached = None;
+    }
+
+    fn update_position(&mut self, pos: Position) {
+        if self.target.is_some_and(|target| target.contains(&pos)) {
+            self.target = None;
+            self.reached = Some(pos);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GotoInput {
+    SetTarget {
+        latitude: f64,
+        longitude: f64,
+        radius_m: f64,
+    },
+    Position(Position),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GotoOutput {
+    /// The target was reached, at the contained position.
+    Reached(Position),
+}
+
+impl StateMachine for GotoMachine {
+    type Input = GotoInput;
+    type Output = GotoOutput;
+
+    fn process_input(&mut self, input: Self::Input) {
+        match input {
+            GotoInput::SetTarget {
+                latitude,
+                longitude,
+                radius_m,
+            } => self.set_target(GotoTarget {
+                latitude,
+                longitude,
+                radius_m,
+            }),
+            GotoInput::Position(pos) => self.update_position(pos),
+        }
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.reached.take().map(GotoOutput::Reached)
+    }
+
+    fn reset(&mut self) {
+        self.target = None;
+        self.reached = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine::echo::position_at;
+
+    fn set_target(radius_m: f64) -> GotoInput {
+        GotoInput::SetTarget {
+            latitude: 37.7749,
+            longitude: -122.4194,
+            radius_m,
+        }
+    }
+
+    #[test]
+    fn test_no_output_without_target() {
+        let mut machine = GotoMachine::new();
+
+        machine.process_input(GotoInput::Position(position_at(37.7749, -122.4194)));
+
+        assert!(machine.poll_output().is_none());
+    }
+
+    #[test]
+    fn test_far_positions_do_not_reach() {
+        let mut machine = GotoMachine::new();
+        machine.process_input(set_target(10.0));
+
+        // Roughly 1.1km and 111m north of the target
+        machine.process_input(GotoInput::Position(position_at(37.7849, -122.4194)));
+        machine.process_input(GotoInput::Position(position_at(37.7759, -122.4194)));
+
+        assert!(machine.poll_output().is_none());
+        assert!(machine.target().is_some());
+    }
+
+    #[test]
+    fn test_near_position_reaches_once() {
+        let mut machine = GotoMachine::new();
+        machine.process_input(set_target(10.0));
+        machine.process_input(GotoInput::Position(position_at(37.7849, -122.4194)));
+
+        // Roughly 5.5m north of the target
+        let near = position_at(37.77495, -122.4194);
+        machine.process_input(GotoInput::Position(near.clone()));
+        machine.process_input(GotoInput::Position(near.clone()));
+
+        assert_eq!(machine.poll_output(), Some(GotoOutput::Reached(near)));
+        assert!(machine.poll_output().is_none());
+        assert!(machine.target().is_none());
+    }
+
+    #[test]
+    fn test_new_target_discards_unpolled_arrival() {
+        let mut machine = GotoMachine::new();
+        machine.process_input(set_target(10.0));
+        machine.process_input(GotoInput::Position(position_at(37.7749, -122.4194)));
+
+        machine.process_input(GotoInput::SetTarget {
+            latitude: 38.0,
+            longitude: -122.0,
+            radius_m: 10.0,
+        });
+
+        assert!(machine.poll_output().is_none());
+    }
+
+    #[test]
+    fn test_reset_matches_fresh_machine() {
+        let mut machine = GotoMachine::new();
+        machine.process_input(set_target(10.0));
+        machine.process_input(GotoInput::Position(position_at(37.7749, -122.4194)));
+
+        machine.reset();
+
+        assert!(machine.poll_output().is_none());
+        assert_eq!(machine, GotoMachine::new());
+    }
+}
@@ -0,0 +1,214 @@
+use super::StateMachine;
+use crate::drone_proto::{self, DroneHealth};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct HealthMachine {
+    latest_health: Option<Health>,
+    pending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Health {
+    pub drone_id: String,
+    pub battery_percent: f64,
+    pub status: HealthStatus,
+    pub timestamp: u64,
+}
+
+/// The overall condition of a drone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HealthStatus {
+    /// The drone did not report a status, or reported one that is not known.
+    #[default]
+    Unknown,
+    Nominal,
+    Degraded,
+    Critical,
+}
+
+impl From<drone_proto::HealthStatus> for HealthStatus {
+    fn from(status: drone_proto::HealthStatus) -> Self {
+        match status {
+            drone_proto::HealthStatus::Unspecified => HealthStatus::Unknown,
+            drone_proto::HealthStatus::Nominal => HealthStatus::Nominal,
+            drone_proto::HealthStatus::Degraded => HealthStatus::Degraded,
+            drone_proto::HealthStatus::Critical => HealthStatus::Critical,
+        }
+    }
+}
+
+impl From<HealthStatus> for drone_proto::HealthStatus {
+    fn from(status: HealthStatus) -> Self {
+        match status {
+            HealthStatus::Unknown => drone_proto::HealthStatus::Unspecified,
+            HealthStatus::Nominal => drone_proto::HealthStatus::Nominal,
+            HealthStatus::Degraded => drone_proto::HealthStatus::Degraded,
+            HealthStatus::Critical => drone_proto::HealthStatus::Critical,
+        }
+    }
+}
+
+impl From<DroneHealth> for Health {
+    fn from(health: DroneHealth) -> Self {
+        // Statuses added to the proto after this build are reported as unknown
+        let status = drone_proto::HealthStatus::try_from(health.status)
+            .map(HealthStatus::from)
+            .unwrap_or_default();
+
+        Self {
+            drone_id: health.drone_id,
+            battery_percent: health.battery_percent,
+            status,
+            timestamp: health.timestamp,
+        }
+    }
+}
+
+impl From<Health> for DroneHealth {
+    fn from(health: Health) -> Self {
+        Self {
+            drone_id: health.drone_id,
+            battery_percent: health.battery_percent,
+            status: drone_proto::HealthStatus::from(health.status).into(),
+            timestamp: health.timestamp,
+        }
+    }
+}
+
+impl HealthMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The most recently processed health report, if any.
+    pub fn current_health(&self) -> Option<&Health> {
+        self.latest_health.as_ref()
+    }
+
+    fn update_health(&mut self, health: Health) {
+        self.latest_health = Some(health);
+        self.pending = true;
+    }
+
+    fn poll_health(&mut self) -> Option<Health> {
+        if self.pending {
+            self.pending = false;
+            self.latest_health.clone()
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthInput {
+    Health(Health),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthOutput {
+    Health(Health),
+}
+
+impl StateMachine for HealthMachine {
+    type Input = HealthInput;
+    type Output = HealthOutput;
+
+    fn process_input(&mut self, input: Self::Input) {
+        match input {
+            HealthInput::Health(health) => self.update_health(health),
+        }
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.poll_health().map(HealthOutput::Health)
+    }
+
+    fn reset(&mut self) {
+        self.latest_health = None;
+        self.pending = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(timestamp: u64) -> Health {
+        Health {
+            drone_id: "drone-1".to_string(),
+            battery_percent: 87.5,
+            status: HealthStatus::Nominal,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_drone_health_round_trip() {
+        let proto = DroneHealth {
+            drone_id: "drone-1".to_string(),
+            battery_percent: 42.25,
+            status: drone_proto::HealthStatus::Degraded.into(),
+            timestamp: 1_700_000_000,
+        };
+
+        let health = Health::from(proto.clone());
+        assert_eq!(
+            health,
+            Health {
+                drone_id: "drone-1".to_string(),
+                battery_percent: 42.25,
+                status: HealthStatus::Degraded,
+                timestamp: 1_700_000_000,
+            }
+        );
+        assert_eq!(DroneHealth::from(health), proto);
+    }
+
+    #[test]
+    fn test_unrecognized_status_is_unknown() {
+        let proto = DroneHealth {
+            status: 99,
+            ..DroneHealth::default()
+        };
+
+        assert_eq!(Health::from(proto).status, HealthStatus::Unknown);
+    }
+
+    #[test]
+    fn test_update_then_poll() {
+        let mut machine = HealthMachine::new();
+
+        machine.process_input(HealthInput::Health(health(1)));
+
+        assert_eq!(machine.current_health(), Some(&health(1)));
+        assert_eq!(machine.poll_output(), Some(HealthOutput::Health(health(1))));
+        assert!(machine.poll_output().is_none());
+        // The latest report is still visible once consumed
+        assert_eq!(machine.current_health(), Some(&health(1)));
+    }
+
+    #[test]
+    fn test_newer_report_overwrites_pending() {
+        let mut machine = HealthMachine::new();
+
+        machine.process_batch((1..=3).map(|ts| HealthInput::Health(health(ts))));
+
+        assert_eq!(machine.current_health(), Some(&health(3)));
+        assert_eq!(machine.poll_output(), Some(HealthOutput::Health(health(3))));
+        assert!(machine.poll_output().is_none());
+    }
+
+    #[test]
+    fn test_reset_matches_fresh_machine() {
+        let mut machine = HealthMachine::new();
+        machine.process_input(HealthInput::Health(health(1)));
+
+        machine.reset();
+
+        assert!(machine.poll_output().is_none());
+        assert_eq!(machine, HealthMachine::new());
+    }
+}
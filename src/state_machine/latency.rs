@@ -0,0 +1,187 @@
+use std::collections::VecDeque;
+
+use super::StateMachine;
+
+/// Tracks end-to-end latency over a rolling window of observations.
+///
+/// Both timestamps of an observation are provided by the caller, since the machine cannot read the
+/// clock itself. They may be in any unit as long as it is consistent, and the statistics are in
+/// the same unit. After each observation the statistics over the window are output.
+#[derive(Debug, PartialEq)]
+pub struct LatencyMachine {
+    window: VecDeque<u64>,
+    window_size: usize,
+    pending: bool,
+}
+
+/// Latency statistics over the observations in a [`LatencyMachine`]'s window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyStats {
+    pub min: u64,
+    pub avg: f64,
+    pub max: u64,
+    /// The number of observations the statistics cover.
+    pub samples: usize,
+}
+
+impl LatencyMachine {
+    /// The number of observations retained by [`new`](Self::new).
+    pub const DEFAULT_WINDOW: usize = 100;
+
+    pub fn new() -> Self {
+        Self::with_window(Self::DEFAULT_WINDOW)
+    }
+
+    /// Construct a [`LatencyMachine`] computing statistics over the last `size` observations.
+    ///
+    /// A `size` of zero is treated as one.
+    pub fn with_window(size: usize) -> Self {
+        let size = size.max(1);
+        Self {
+            window: VecDeque::with_capacity(size),
+            window_size: size,
+            pending: false,
+        }
+    }
+
+    /// The statistics over the current window, if anything has been observed.
+    pub fn stats(&self) -> Option<LatencyStats> {
+        let min = *self.window.iter().min()?;
+        let max = *self.window.iter().max()?;
+        let total: u64 = self.window.iter().sum();
+
+        Some(LatencyStats {
+            min,
+            avg: total as f64 / self.window.len() as f64,
+            max,
+            samples: self.window.len(),
+        })
+    }
+
+    fn observe(&mut self, sent_ts: u64, recv_ts: u64) {
+        if self.window.len() == self.window_size {
+            self.window.pop_front();
+        }
+        // Clock skew between sender and receiver can put receipt before sending
+        self.window.push_back(recv_ts.saturating_sub(sent_ts));
+        self.pending = true;
+    }
+}
+
+impl Default for LatencyMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LatencyInput {
+    Observed { sent_ts: u64, recv_ts: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LatencyOutput {
+    Stats(LatencyStats),
+}
+
+impl StateMachine for LatencyMachine {
+    type Input = LatencyInput;
+    type Output = LatencyOutput;
+
+    fn process_input(&mut self, input: Self::Input) {
+        match input {
+            LatencyInput::Observed { sent_ts, recv_ts } => self.observe(sent_ts, recv_ts),
+        }
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        if !self.pending {
+            return None;
+        }
+        self.pending = false;
+        self.stats().map(LatencyOutput::Stats)
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.pending = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observed(sent_ts: u64, latency: u64) -> LatencyInput {
+        LatencyInput::Observed {
+            sent_ts,
+            recv_ts: sent_ts + latency,
+        }
+    }
+
+    #[test]
+    fn test_no_output_without_observations() {
+        let mut machine = LatencyMachine::new();
+
+        assert!(machine.poll_output().is_none());
+        assert!(machine.stats().is_none());
+    }
+
+    #[test]
+    fn test_stats_over_observations() {
+        let mut machine = LatencyMachine::new();
+
+        machine.process_batch([observed(1000, 20), observed(2000, 50), observed(3000, 35)]);
+
+        assert_eq!(
+            machine.poll_output(),
+            Some(LatencyOutput::Stats(LatencyStats {
+                min: 20,
+                avg: 35.0,
+                max: 50,
+                samples: 3,
+            }))
+        );
+        assert!(machine.poll_output().is_none());
+    }
+
+    #[test]
+    fn test_window_drops_oldest() {
+        let mut machine = LatencyMachine::with_window(2);
+
+        machine.process_batch([observed(1000, 100), observed(2000, 10), observed(3000, 30)]);
+
+        assert_eq!(
+            machine.stats(),
+            Some(LatencyStats {
+                min: 10,
+                avg: 20.0,
+                max: 30,
+                samples: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_receipt_before_sending_is_zero() {
+        let mut machine = LatencyMachine::new();
+
+        machine.process_input(LatencyInput::Observed {
+            sent_ts: 1000,
+            recv_ts: 990,
+        });
+
+        assert_eq!(machine.stats().map(|stats| stats.max), Some(0));
+    }
+
+    #[test]
+    fn test_reset_matches_fresh_machine() {
+        let mut machine = LatencyMachine::with_window(2);
+        machine.process_input(observed(1000, 20));
+
+        machine.reset();
+
+        assert!(machine.poll_output().is_none());
+        assert_eq!(machine, LatencyMachine::with_window(2));
+    }
+}
@@ -1,4 +1,13 @@
+pub mod combinators;
 pub mod echo;
+pub mod flight_path;
+pub mod goto;
+pub mod health;
+pub mod latency;
+pub mod replay;
+pub mod runner;
+#[cfg(any(test, feature = "testing"))]
+pub mod testkit;
 pub mod wrappers;
 
 /// The [`StateMachine`] trait provides calling semantics and indicates the upholding of invariants
@@ -242,4 +251,169 @@ pub trait StateMachine {
     /// The implementor of this trait provides the dispatch mapping from the polling methods of
     /// the state machine to the unified [`Output`](StateMachine::Output) type of this trait.
     fn poll_output(&mut self) -> Option<Self::Output>;
+
+    /// Reset the state machine back to the state it was in when first constructed.
+    ///
+    /// Any pending output is discarded, so [`poll_output`](StateMachine::poll_output) returns
+    /// `None` until further input is processed. Configuration provided at construction is kept.
+    fn reset(&mut self);
+
+    /// Process every input yielded by `inputs` into the state machine, in iteration order.
+    ///
+    /// This is equivalent to calling [`process_input`](StateMachine::process_input) for each item
+    /// and is provided for ergonomically feeding a group of inputs at once. Determinism is preserved
+    /// since the inputs are processed in the order produced by the iterator.
+    fn process_batch<I: IntoIterator<Item = Self::Input>>(&mut self, inputs: I)
+    where
+        Self: Sized,
+    {
+        for input in inputs {
+            self.process_input(input);
+        }
+    }
+
+    /// Drain all currently available output from the state machine.
+    ///
+    /// Returns a [`DrainOutput`] iterator which repeatedly [polls](StateMachine::poll_output) the
+    /// state machine until no output is present. The iterator holds the `&mut` borrow of the state
+    /// machine for its entire lifetime, so no input can be processed while it is being drained.
+    fn drain_output(&mut self) -> DrainOutput<'_, Self>
+    where
+        Self: Sized,
+    {
+        DrainOutput { machine: self }
+    }
+}
+
+/// An iterator over the available output of a [`StateMachine`].
+///
+/// Created by [`StateMachine::drain_output`]; see its documentation for more.
+#[derive(Debug)]
+pub struct DrainOutput<'a, S> {
+    machine: &'a mut S,
+}
+
+impl<S: StateMachine> Iterator for DrainOutput<'_, S> {
+    type Item = S::Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.machine.poll_output()
+    }
+}
+
+/// A minimal FIFO machine that outputs its inputs in the order they were processed, shared by
+/// tests as a fixture.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct QueueMachine<T> {
+    queue: std::collections::VecDeque<T>,
+}
+
+#[cfg(test)]
+impl<T> Default for QueueMachine<T> {
+    fn default() -> Self {
+        Self {
+            queue: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl<T> StateMachine for QueueMachine<T> {
+    type Input = T;
+    type Output = T;
+
+    fn process_input(&mut self, input: Self::Input) {
+        self.queue.push_back(input);
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.queue.pop_front()
+    }
+
+    fn reset(&mut self) {
+        self.queue.clear();
+    }
+}
+
+/// Outputs each input offset by a value fixed at construction.
+///
+/// Machines constructed with different offsets emulate a machine with hidden impure state.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct OffsetMachine {
+    offset: u64,
+    pending: Option<u64>,
+}
+
+#[cfg(test)]
+impl OffsetMachine {
+    pub(crate) fn new(offset: u64) -> Self {
+        Self {
+            offset,
+            pending: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl StateMachine for OffsetMachine {
+    type Input = u64;
+    type Output = u64;
+
+    fn process_input(&mut self, input: Self::Input) {
+        self.pending = Some(self.offset + input);
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.pending.take()
+    }
+
+    fn reset(&mut self) {
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_batch_preserves_order() {
+        let mut machine = QueueMachine::default();
+
+        machine.process_batch([1, 2, 3]);
+
+        assert_eq!(machine.poll_output(), Some(1));
+        assert_eq!(machine.poll_output(), Some(2));
+        assert_eq!(machine.poll_output(), Some(3));
+        assert_eq!(machine.poll_output(), None);
+    }
+
+    #[test]
+    fn test_drain_output_matches_manual_polling() {
+        let mut drained = QueueMachine::default();
+        let mut polled = QueueMachine::default();
+        drained.process_batch([4, 5, 6]);
+        polled.process_batch([4, 5, 6]);
+
+        let mut manual = Vec::new();
+        while let Some(output) = polled.poll_output() {
+            manual.push(output);
+        }
+
+        assert_eq!(drained.drain_output().collect::<Vec<_>>(), manual);
+        assert_eq!(drained.drain_output().next(), None);
+    }
+
+    #[test]
+    fn test_reset_discards_pending_output() {
+        let mut machine = QueueMachine::default();
+        machine.process_batch([1, 2, 3]);
+
+        machine.reset();
+
+        assert_eq!(machine.poll_output(), None);
+        assert!(machine.queue.is_empty());
+    }
 }
@@ -0,0 +1,91 @@
+//! Utilities for verifying that a [`StateMachine`] upholds its determinism invariants.
+
+use std::fmt::Debug;
+
+use super::StateMachine;
+
+/// Feed each of `inputs` into `machine`, fully draining the output after every input.
+///
+/// Returns the recorded output in the order it was produced, interleaved as it would be observed
+/// by a consumer polling after each input.
+pub fn replay<S: StateMachine>(machine: &mut S, inputs: Vec<S::Input>) -> Vec<S::Output> {
+    let mut outputs = Vec::new();
+
+    for input in inputs {
+        machine.process_input(input);
+        outputs.extend(machine.drain_output());
+    }
+
+    outputs
+}
+
+/// [Replay](replay) `inputs` against two machines constructed by `make`, asserting that both
+/// produce identical output.
+///
+/// Returns the recorded output of the first replay.
+///
+/// # Panics
+/// Panics if the output of the two replays diverges, indicating that the machine depends on
+/// something other than its input.
+pub fn replay_deterministic<S: StateMachine>(
+    mut make: impl FnMut() -> S,
+    inputs: Vec<S::Input>,
+) -> Vec<S::Output>
+where
+    S::Input: Clone,
+    S::Output: PartialEq + Debug,
+{
+    let first = replay(&mut make(), inputs.clone());
+    let second = replay(&mut make(), inputs);
+
+    assert_eq!(first, second, "state machine replay is not deterministic");
+
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine::OffsetMachine;
+    use crate::state_machine::echo::{EchoInput, EchoMachine, EchoOutput, position};
+
+    #[test]
+    fn test_replay_records_output_per_input() {
+        let inputs = (1..=3)
+            .map(|ts| EchoInput::Position(position(ts)))
+            .collect();
+
+        let outputs = replay(&mut EchoMachine::new(), inputs);
+
+        let expected: Vec<_> = (1..=3)
+            .map(|ts| EchoOutput::Position(position(ts)))
+            .collect();
+        assert_eq!(outputs, expected);
+    }
+
+    #[test]
+    fn test_replay_deterministic() {
+        let inputs: Vec<_> = (1..=3)
+            .map(|ts| EchoInput::Position(position(ts)))
+            .collect();
+
+        let outputs = replay_deterministic(|| EchoMachine::with_history(2), inputs);
+
+        assert_eq!(outputs.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "not deterministic")]
+    fn test_replay_deterministic_detects_divergence() {
+        let mut constructed = 0;
+
+        // Each construction uses a different offset, emulating a machine with hidden impure state
+        replay_deterministic(
+            || {
+                constructed += 1;
+                OffsetMachine::new(constructed)
+            },
+            vec![1, 2],
+        );
+    }
+}
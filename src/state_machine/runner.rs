@@ -0,0 +1,108 @@
+use tokio::sync::mpsc;
+
+use super::StateMachine;
+
+/// Drives a [`StateMachine`] by pumping input from and output to channels.
+///
+/// The [`Runner`] owns all of the impure concerns of operating the machine (channels and async
+/// scheduling), leaving the machine itself pure. Each input received is
+/// [processed](StateMachine::process_input) and then all available output is
+/// [drained](StateMachine::drain_output) to the output channel before the next input is received.
+#[derive(Debug)]
+pub struct Runner<S: StateMachine> {
+    machine: S,
+    inputs: mpsc::Receiver<S::Input>,
+    outputs: mpsc::Sender<S::Output>,
+}
+
+impl<S: StateMachine> Runner<S> {
+    pub fn new(
+        machine: S,
+        inputs: mpsc::Receiver<S::Input>,
+        outputs: mpsc::Sender<S::Output>,
+    ) -> Self {
+        Self {
+            machine,
+            inputs,
+            outputs,
+        }
+    }
+
+    /// The machine being driven.
+    pub fn machine(&self) -> &S {
+        &self.machine
+    }
+
+    /// Receive and process a single input, forwarding all resulting output.
+    ///
+    /// Returns `false` once the input channel is closed or the output receiver has been dropped,
+    /// after which the runner can make no further progress.
+    pub async fn step(&mut self) -> bool {
+        let Some(input) = self.inputs.recv().await else {
+            return false;
+        };
+
+        self.machine.process_input(input);
+
+        for output in self.machine.drain_output() {
+            if self.outputs.send(output).await.is_err() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// [Step](Runner::step) until the input or output channel closes, returning the machine.
+    pub async fn run(mut self) -> S {
+        while self.step().await {}
+        self.machine
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine::echo::{EchoInput, EchoMachine, EchoOutput, position};
+
+    #[tokio::test]
+    async fn test_step_round_trip() {
+        let (input_tx, input_rx) = mpsc::channel(4);
+        let (output_tx, mut output_rx) = mpsc::channel(4);
+        let mut runner = Runner::new(EchoMachine::new(), input_rx, output_tx);
+
+        input_tx
+            .send(EchoInput::Position(position(1)))
+            .await
+            .unwrap();
+        assert!(runner.step().await);
+
+        let EchoOutput::Position(pos) = output_rx.recv().await.unwrap();
+        assert_eq!(pos, position(1));
+        assert!(output_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_when_inputs_close() {
+        let (input_tx, input_rx) = mpsc::channel(4);
+        let (output_tx, mut output_rx) = mpsc::channel(4);
+        let runner = Runner::new(EchoMachine::with_history(4), input_rx, output_tx);
+
+        for ts in 1..=3 {
+            input_tx
+                .send(EchoInput::Position(position(ts)))
+                .await
+                .unwrap();
+        }
+        drop(input_tx);
+
+        let machine = runner.run().await;
+
+        let mut timestamps = Vec::new();
+        while let Some(EchoOutput::Position(pos)) = output_rx.recv().await {
+            timestamps.push(pos.timestamp);
+        }
+        assert_eq!(timestamps, vec![1, 2, 3]);
+        assert_eq!(machine.history().count(), 3);
+    }
+}
@@ -0,0 +1,122 @@
+//! Assertions checking that a [`StateMachine`] upholds the calling semantics expected of it.
+//!
+//! These are intended for the tests of state machine implementations, saving each from repeating
+//! the same conformance checks. Outside this crate, they require the `testing` feature.
+
+use std::fmt::Debug;
+
+use super::StateMachine;
+use super::replay::{replay, replay_deterministic};
+
+/// Assert that two machines made by `build` produce identical output when fed `inputs`.
+///
+/// Each input is processed in turn and all available output drained after it, as by
+/// [`replay_deterministic`].
+///
+/// # Panics
+/// If the outputs of the machines differ.
+pub fn assert_deterministic<S>(build: impl Fn() -> S, inputs: Vec<S::Input>)
+where
+    S: StateMachine,
+    S::Input: Clone,
+    S::Output: PartialEq + Debug,
+{
+    replay_deterministic(build, inputs);
+}
+
+/// Assert that a machine made by `build` has no output when idle: before any input is processed,
+/// and once the output of `inputs` has been drained.
+///
+/// # Panics
+/// If the machine has output while idle.
+pub fn assert_poll_empty_when_idle<S>(build: impl Fn() -> S, inputs: Vec<S::Input>)
+where
+    S: StateMachine,
+    S::Input: Clone,
+    S::Output: PartialEq + Debug,
+{
+    let mut machine = build();
+    assert_eq!(
+        machine.poll_output(),
+        None,
+        "fresh machine has output before any input"
+    );
+
+    replay(&mut machine, inputs);
+    assert_eq!(
+        machine.poll_output(),
+        None,
+        "machine has output after being drained"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine::echo::{EchoInput, EchoMachine, Position, position};
+    use crate::state_machine::goto::{GotoInput, GotoMachine};
+    use crate::state_machine::{OffsetMachine, QueueMachine};
+
+    fn echo_inputs() -> Vec<EchoInput> {
+        (1..=5)
+            .map(|ts| {
+                EchoInput::Position(Position {
+                    latitude: 37.7749 + ts as f64 * 0.001,
+                    ..position(ts)
+                })
+            })
+            .collect()
+    }
+
+    fn goto_inputs() -> Vec<GotoInput> {
+        vec![
+            GotoInput::SetTarget {
+                latitude: 37.7749,
+                longitude: -122.4194,
+                radius_m: 10.0,
+            },
+            GotoInput::Position(Position {
+                latitude: 37.7849,
+                ..position(1)
+            }),
+            GotoInput::Position(position(2)),
+            GotoInput::Position(position(3)),
+        ]
+    }
+
+    #[test]
+    fn test_echo_machine_conforms() {
+        assert_deterministic(|| EchoMachine::with_history(3), echo_inputs());
+        assert_poll_empty_when_idle(|| EchoMachine::with_history(3), echo_inputs());
+    }
+
+    #[test]
+    fn test_goto_machine_conforms() {
+        assert_deterministic(GotoMachine::new, goto_inputs());
+        assert_poll_empty_when_idle(GotoMachine::new, goto_inputs());
+    }
+
+    #[test]
+    #[should_panic(expected = "not deterministic")]
+    fn test_detects_divergence() {
+        let constructed = std::cell::Cell::new(0);
+        let build = || {
+            constructed.set(constructed.get() + 1);
+            OffsetMachine::new(constructed.get())
+        };
+
+        assert_deterministic(build, vec![1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "before any input")]
+    fn test_detects_output_when_fresh() {
+        let build = || {
+            let mut machine = QueueMachine::default();
+            machine.process_input(0);
+            machine
+        };
+
+        assert_poll_empty_when_idle(build, vec![]);
+    }
+}
@@ -1 +1,4 @@
+pub mod rate_limited;
+pub mod seeded_rng;
+pub mod sequenced;
 pub mod system;
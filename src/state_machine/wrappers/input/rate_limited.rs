@@ -0,0 +1,128 @@
+use crate::state_machine::StateMachine;
+
+/// The input of a [`RateLimited`] state machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitedInput<I> {
+    /// Refill the bucket by the configured refill amount.
+    Tick,
+    /// An input to pass to the wrapped state machine if the bucket has a token available.
+    Input(I),
+}
+
+/// A [`StateMachine`] wrapper rate limiting the inputs passed to the wrapped state machine `S`
+/// with a token bucket.
+///
+/// Each admitted input consumes one token from the bucket, and inputs arriving while the bucket
+/// is empty are dropped. The bucket starts full and is refilled by injected
+/// [`Tick`](RateLimitedInput::Tick) inputs rather than the system clock, so which inputs are
+/// admitted is fully determined by the order of ticks and inputs.
+#[derive(Debug)]
+pub struct RateLimited<S> {
+    machine: S,
+    capacity: u32,
+    refill: u32,
+    tokens: u32,
+    dropped: u64,
+}
+
+impl<S> RateLimited<S> {
+    /// Wrap `machine` with a full bucket holding up to `capacity` tokens, refilled by `refill`
+    /// tokens on each tick.
+    pub fn new(machine: S, capacity: u32, refill: u32) -> Self {
+        Self {
+            machine,
+            capacity,
+            refill,
+            tokens: capacity,
+            dropped: 0,
+        }
+    }
+
+    /// The number of tokens currently available.
+    pub fn tokens(&self) -> u32 {
+        self.tokens
+    }
+
+    /// The number of inputs dropped because the bucket was empty.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// The wrapped state machine.
+    pub fn machine(&self) -> &S {
+        &self.machine
+    }
+}
+
+impl<S: StateMachine> StateMachine for RateLimited<S> {
+    type Input = RateLimitedInput<S::Input>;
+    type Output = S::Output;
+
+    fn process_input(&mut self, input: Self::Input) {
+        match input {
+            RateLimitedInput::Tick => {
+                self.tokens = self.tokens.saturating_add(self.refill).min(self.capacity);
+            }
+            RateLimitedInput::Input(input) if self.tokens > 0 => {
+                self.tokens -= 1;
+                self.machine.process_input(input);
+            }
+            RateLimitedInput::Input(_) => self.dropped += 1,
+        }
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.machine.poll_output()
+    }
+
+    fn reset(&mut self) {
+        self.machine.reset();
+        self.tokens = self.capacity;
+        self.dropped = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine::QueueMachine;
+    use RateLimitedInput::{Input, Tick};
+
+    #[test]
+    fn test_admits_inputs_while_tokens_remain() {
+        let mut machine = RateLimited::new(QueueMachine::default(), 2, 1);
+
+        machine.process_batch([
+            Input('a'),
+            Input('b'),
+            Input('c'),
+            Tick,
+            Input('d'),
+            Input('e'),
+            // Refills are capped at the capacity of the bucket
+            Tick,
+            Tick,
+            Tick,
+            Input('f'),
+            Input('g'),
+            Input('h'),
+        ]);
+
+        let admitted: String = machine.drain_output().collect();
+        assert_eq!(admitted, "abdfg");
+        assert_eq!(machine.dropped(), 3);
+        assert_eq!(machine.tokens(), 0);
+    }
+
+    #[test]
+    fn test_reset_refills_bucket() {
+        let mut machine = RateLimited::new(QueueMachine::default(), 1, 1);
+        machine.process_batch([Input('a'), Input('b')]);
+
+        machine.reset();
+
+        assert_eq!(machine.tokens(), 1);
+        assert_eq!(machine.dropped(), 0);
+        assert!(machine.poll_output().is_none());
+    }
+}
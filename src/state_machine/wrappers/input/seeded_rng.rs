@@ -0,0 +1,233 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use super::system::{Seed, SystemInput};
+use crate::state_machine::StateMachine;
+
+/// A xoshiro256** pseudo-random number generator.
+///
+/// The sequence generated is fully determined by the [`Seed`] it is constructed from, so it may be
+/// used inside a state machine as long as the seed is provided via input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prng {
+    state: [u64; 4],
+}
+
+impl Prng {
+    pub fn from_seed(Seed(seed): Seed) -> Self {
+        let mut state = [0; 4];
+        for (word, bytes) in state.iter_mut().zip(seed.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().expect("chunks are 8 bytes"));
+        }
+
+        // An all zero state only ever generates zero, so it is expanded from a fixed value instead
+        if state == [0; 4] {
+            let mut x = 0u64;
+            for word in &mut state {
+                x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                let mut z = x;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                *word = z ^ (z >> 31);
+            }
+        }
+
+        Self { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    /// A value uniformly distributed within `range`.
+    ///
+    /// # Panics
+    /// If `range` is empty.
+    pub fn next_range(&mut self, range: Range<u64>) -> u64 {
+        assert!(
+            !range.is_empty(),
+            "cannot generate a value in an empty range"
+        );
+        let span = range.end - range.start;
+
+        // Reject the values above the largest multiple of the span to avoid modulo bias
+        let rejected = (u64::MAX % span + 1) % span;
+        loop {
+            let value = self.next_u64();
+            if value <= u64::MAX - rejected {
+                return range.start + value % span;
+            }
+        }
+    }
+}
+
+/// A state machine generating deterministic pseudo-random values once seeded.
+///
+/// The seed is provided once via [`SystemInput::System`], typically by a runner generating a
+/// [`Seed`] from system entropy. Each [`SystemInput::Input`] tick after that outputs the next
+/// value of the sequence, so recording the seed is enough to replay every value generated. Ticks
+/// before seeding, and seeds after the first, are ignored until the machine is reset.
+#[derive(Debug, Default, PartialEq)]
+pub struct SeededRng {
+    rng: Option<Prng>,
+    pending: VecDeque<u64>,
+}
+
+impl SeededRng {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_seeded(&self) -> bool {
+        self.rng.is_some()
+    }
+
+    /// The next value of the sequence, or `None` if not yet seeded.
+    pub fn next_u64(&mut self) -> Option<u64> {
+        self.rng.as_mut().map(Prng::next_u64)
+    }
+
+    /// The next value of the sequence within `range`, or `None` if not yet seeded.
+    ///
+    /// # Panics
+    /// If `range` is empty.
+    pub fn next_range(&mut self, range: Range<u64>) -> Option<u64> {
+        self.rng.as_mut().map(|rng| rng.next_range(range))
+    }
+
+    fn seed(&mut self, seed: Seed) {
+        if self.rng.is_none() {
+            self.rng = Some(Prng::from_seed(seed));
+        }
+    }
+
+    fn tick(&mut self) {
+        if let Some(value) = self.next_u64() {
+            self.pending.push_back(value);
+        }
+    }
+}
+
+impl StateMachine for SeededRng {
+    type Input = SystemInput<(), Seed>;
+    type Output = u64;
+
+    fn process_input(&mut self, input: Self::Input) {
+        match input {
+            SystemInput::System(seed) => self.seed(seed),
+            SystemInput::Input(()) => self.tick(),
+        }
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.pending.pop_front()
+    }
+
+    fn reset(&mut self) {
+        self.rng = None;
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded(byte: u8) -> SeededRng {
+        let mut machine = SeededRng::new();
+        machine.process_input(SystemInput::System(Seed([byte; 32])));
+        machine
+    }
+
+    fn ticks(n: usize) -> impl Iterator<Item = SystemInput<(), Seed>> {
+        (0..n).map(|_| SystemInput::Input(()))
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut first = seeded(7);
+        let mut second = seeded(7);
+
+        first.process_batch(ticks(16));
+        second.process_batch(ticks(16));
+
+        let values: Vec<_> = first.drain_output().collect();
+        assert_eq!(values.len(), 16);
+        assert_eq!(values, second.drain_output().collect::<Vec<_>>());
+        assert_eq!(first.next_range(10..20), second.next_range(10..20));
+    }
+
+    #[test]
+    fn test_different_seeds_differ() {
+        let mut first = seeded(1);
+        let mut second = seeded(2);
+
+        first.process_batch(ticks(4));
+        second.process_batch(ticks(4));
+
+        assert_ne!(
+            first.drain_output().collect::<Vec<_>>(),
+            second.drain_output().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_zero_seed_is_not_degenerate() {
+        let mut machine = seeded(0);
+
+        machine.process_batch(ticks(4));
+
+        assert!(machine.drain_output().any(|value| value != 0));
+    }
+
+    #[test]
+    fn test_ticks_before_seed_are_ignored() {
+        let mut machine = SeededRng::new();
+
+        machine.process_batch(ticks(2));
+
+        assert!(machine.poll_output().is_none());
+        assert_eq!(machine.next_u64(), None);
+    }
+
+    #[test]
+    fn test_later_seeds_are_ignored() {
+        let mut reseeded = seeded(1);
+        reseeded.process_input(SystemInput::System(Seed([2; 32])));
+
+        assert_eq!(reseeded.next_u64(), seeded(1).next_u64());
+    }
+
+    #[test]
+    fn test_next_range_within_bounds() {
+        let mut machine = seeded(3);
+
+        for _ in 0..1000 {
+            let value = machine.next_range(5..8).unwrap();
+            assert!((5..8).contains(&value), "got {value}");
+        }
+        assert_eq!(machine.next_range(4..5), Some(4));
+    }
+
+    #[test]
+    fn test_reset_matches_fresh_machine() {
+        let mut machine = seeded(1);
+        machine.process_batch(ticks(2));
+
+        machine.reset();
+
+        assert!(machine.poll_output().is_none());
+        assert_eq!(machine, SeededRng::new());
+    }
+}
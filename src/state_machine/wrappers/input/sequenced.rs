@@ -0,0 +1,116 @@
+use crate::state_machine::StateMachine;
+
+/// An input stamped with its sequence number by [`Sequenced`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequencedInput<I> {
+    /// The position of this input among all inputs processed, starting at zero.
+    pub seq: u64,
+    pub input: I,
+}
+
+/// A [`StateMachine`] wrapper stamping each input with a monotonically increasing sequence number
+/// before passing it to the wrapped state machine `S`.
+///
+/// The sequence numbers are counted by the wrapper itself rather than read from the system, so
+/// they are deterministic and can be used to correlate inputs across replays.
+#[derive(Debug, Default)]
+pub struct Sequenced<S> {
+    machine: S,
+    next_seq: u64,
+}
+
+impl<S> Sequenced<S> {
+    /// Wrap `machine`, numbering inputs from zero.
+    pub fn new(machine: S) -> Self {
+        Self {
+            machine,
+            next_seq: 0,
+        }
+    }
+
+    /// The sequence number the next processed input will be stamped with.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// The wrapped state machine.
+    pub fn machine(&self) -> &S {
+        &self.machine
+    }
+}
+
+impl<S, I> StateMachine for Sequenced<S>
+where
+    S: StateMachine<Input = SequencedInput<I>>,
+{
+    type Input = I;
+    type Output = S::Output;
+
+    fn process_input(&mut self, input: Self::Input) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.machine.process_input(SequencedInput { seq, input });
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.machine.poll_output()
+    }
+
+    fn reset(&mut self) {
+        self.machine.reset();
+        self.next_seq = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    /// Outputs the sequence number and value of each input it processes.
+    #[derive(Debug, Default)]
+    struct RecordingMachine {
+        processed: VecDeque<(u64, char)>,
+    }
+
+    impl StateMachine for RecordingMachine {
+        type Input = SequencedInput<char>;
+        type Output = (u64, char);
+
+        fn process_input(&mut self, input: Self::Input) {
+            self.processed.push_back((input.seq, input.input));
+        }
+
+        fn poll_output(&mut self) -> Option<Self::Output> {
+            self.processed.pop_front()
+        }
+
+        fn reset(&mut self) {
+            self.processed.clear();
+        }
+    }
+
+    #[test]
+    fn test_sequence_numbers_increment() {
+        let mut machine = Sequenced::new(RecordingMachine::default());
+
+        machine.process_batch(['a', 'b', 'c']);
+
+        let outputs: Vec<_> = machine.drain_output().collect();
+        assert_eq!(outputs, vec![(0, 'a'), (1, 'b'), (2, 'c')]);
+        assert_eq!(machine.next_seq(), 3);
+    }
+
+    #[test]
+    fn test_reset_restarts_sequence() {
+        let mut machine = Sequenced::new(RecordingMachine::default());
+        machine.process_batch(['a', 'b']);
+
+        machine.reset();
+        machine.process_input('c');
+
+        assert_eq!(machine.poll_output(), Some((0, 'c')));
+        assert_eq!(machine.poll_output(), None);
+    }
+}
@@ -27,9 +27,78 @@ impl SystemResource for std::time::Instant {
     }
 }
 
+/// The wall-clock time in whole seconds since the unix epoch, as used by telemetry timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnixSeconds(pub u64);
+
+impl SystemResource for UnixSeconds {
+    fn generate() -> Self {
+        let since_epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock should not be set before the unix epoch");
+        Self(since_epoch.as_secs())
+    }
+}
+
+/// A 256-bit random seed drawn from the operating system's entropy source.
+///
+/// State machines must not access system entropy themselves, so this is intended to be provided
+/// via [`SystemInput::System`] and used to deterministically seed a PRNG inside the machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Seed(pub [u8; 32]);
+
+impl SystemResource for Seed {
+    fn generate() -> Self {
+        let mut seed = [0; 32];
+        getrandom::fill(&mut seed).expect("OS random number generator should be available");
+        Self(seed)
+    }
+}
+
 /// A [`StateMachine`](super::StateMachine) input wrapper for providing [`SystemResource`] to the
 /// state machine.
 pub enum SystemInput<I, S> {
     Input(I),
     System(S),
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn test_seed_generate_differs() {
+        assert_ne!(Seed::generate(), Seed::generate());
+    }
+
+    #[test]
+    fn test_tuple_composes_instant_and_seed() {
+        let before = Instant::now();
+
+        let (instant, seed) = <(Instant, Seed)>::generate();
+
+        assert!(instant >= before);
+        assert_ne!(seed, Seed::generate());
+    }
+
+    #[test]
+    fn test_unix_seconds_is_current() {
+        let UnixSeconds(now) = UnixSeconds::generate();
+
+        // Between 2023-11-14 and 2100-01-01
+        assert!((1_700_000_000..4_102_444_800).contains(&now), "got {now}");
+    }
+
+    #[test]
+    fn test_tuple_composes_instant_and_unix_seconds() {
+        let before = Instant::now();
+        let UnixSeconds(earliest) = UnixSeconds::generate();
+
+        let (instant, UnixSeconds(now)) = <(Instant, UnixSeconds)>::generate();
+
+        assert!(instant >= before);
+        assert!(now >= earliest);
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::state_machine::StateMachine;
+
+/// A [`StateMachine`] wrapper counting the inputs processed and outputs produced by the wrapped
+/// state machine `S`.
+///
+/// The counters are a permitted side effect: they are only ever written and are never read by
+/// the wrapped state machine, so its logic is unaffected. They are kept across
+/// [`reset`](StateMachine::reset) since they measure the work done by the wrapper over its
+/// lifetime rather than the state of the machine.
+#[derive(Debug, Default)]
+pub struct Metered<S> {
+    machine: S,
+    inputs: AtomicU64,
+    outputs: AtomicU64,
+}
+
+impl<S> Metered<S> {
+    /// Wrap `machine`, starting both counters at zero.
+    pub fn new(machine: S) -> Self {
+        Self {
+            machine,
+            inputs: AtomicU64::new(0),
+            outputs: AtomicU64::new(0),
+        }
+    }
+
+    /// The number of inputs processed and outputs produced, in that order.
+    pub fn counts(&self) -> (u64, u64) {
+        (
+            self.inputs.load(Ordering::Relaxed),
+            self.outputs.load(Ordering::Relaxed),
+        )
+    }
+
+    /// The wrapped state machine.
+    pub fn machine(&self) -> &S {
+        &self.machine
+    }
+
+    /// Unwrap the state machine, discarding the counters.
+    pub fn into_inner(self) -> S {
+        self.machine
+    }
+}
+
+impl<S: StateMachine> StateMachine for Metered<S> {
+    type Input = S::Input;
+    type Output = S::Output;
+
+    fn process_input(&mut self, input: Self::Input) {
+        self.inputs.fetch_add(1, Ordering::Relaxed);
+        self.machine.process_input(input);
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        let output = self.machine.poll_output();
+        if output.is_some() {
+            self.outputs.fetch_add(1, Ordering::Relaxed);
+        }
+        output
+    }
+
+    fn reset(&mut self) {
+        self.machine.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine::echo::{EchoInput, EchoMachine, position};
+
+    #[test]
+    fn test_counts_inputs_and_outputs() {
+        let mut machine = Metered::new(EchoMachine::new());
+        assert_eq!(machine.counts(), (0, 0));
+
+        // The echo machine only outputs the latest of the positions processed since the last poll
+        machine.process_batch((1..=3).map(|ts| EchoInput::Position(position(ts))));
+        assert_eq!(machine.drain_output().count(), 1);
+        assert_eq!(machine.counts(), (3, 1));
+
+        machine.process_input(EchoInput::Position(position(4)));
+        assert_eq!(machine.drain_output().count(), 1);
+        assert_eq!(machine.counts(), (4, 2));
+    }
+
+    #[test]
+    fn test_reset_keeps_counts() {
+        let mut machine = Metered::new(EchoMachine::new());
+        machine.process_input(EchoInput::Position(position(1)));
+
+        machine.reset();
+
+        assert!(machine.poll_output().is_none());
+        assert_eq!(machine.machine(), &EchoMachine::new());
+        assert_eq!(machine.counts(), (1, 0));
+    }
+}
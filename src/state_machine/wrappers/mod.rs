@@ -5,4 +5,5 @@
 //! to be deterministically provided via input.
 
 pub mod input;
+pub mod metered;
 pub mod output;
@@ -1,5 +1,115 @@
+use crate::state_machine::StateMachine;
+
 /// The output wrapper uses `Result<T, E>` to be able to provide an additional "wait value" when
 /// output isn't present.
 ///
 /// Note that state machines still may return `None` if no wait value is applicable.
 pub type WrappedOutput<T, E> = Result<T, E>;
+
+/// What a state machine with no output is waiting on before it can produce more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitReason {
+    /// Output is produced as time passes, so the runner should provide the next tick.
+    NextTick,
+    /// Output is only produced in response to input, so the runner should wait for more.
+    Input,
+}
+
+/// A [`StateMachine`] able to report what it is waiting on when it has no output.
+///
+/// The reason must be derived only from the state of the machine, like the output itself.
+pub trait Wait: StateMachine {
+    /// What the machine is waiting on, assuming it currently has no output.
+    fn wait_reason(&self) -> WaitReason;
+}
+
+/// A [`StateMachine`] wrapper polling the wrapped state machine `S` for either output or the
+/// [`WaitReason`] it has none, for a runner to interpret.
+#[derive(Debug, Default)]
+pub struct WaitFor<S> {
+    machine: S,
+}
+
+impl<S> WaitFor<S> {
+    pub fn new(machine: S) -> Self {
+        Self { machine }
+    }
+
+    /// The wrapped state machine.
+    pub fn machine(&self) -> &S {
+        &self.machine
+    }
+
+    /// Unwrap the state machine.
+    pub fn into_inner(self) -> S {
+        self.machine
+    }
+}
+
+impl<S: Wait> WaitFor<S> {
+    /// Poll the wrapped machine for output, or what it is waiting on if it has none.
+    pub fn poll_wrapped_output(&mut self) -> WrappedOutput<S::Output, WaitReason> {
+        self.machine
+            .poll_output()
+            .ok_or_else(|| self.machine.wait_reason())
+    }
+}
+
+impl<S: StateMachine> StateMachine for WaitFor<S> {
+    type Input = S::Input;
+    type Output = S::Output;
+
+    fn process_input(&mut self, input: Self::Input) {
+        self.machine.process_input(input);
+    }
+
+    fn poll_output(&mut self) -> Option<Self::Output> {
+        self.machine.poll_output()
+    }
+
+    fn reset(&mut self) {
+        self.machine.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::state_machine::echo::{EchoInput, EchoMachine, EchoOutput, position};
+    use crate::state_machine::flight_path::{FlightPath, FlightPathInput, Route};
+
+    #[test]
+    fn test_echo_waits_for_input() {
+        let mut machine = WaitFor::new(EchoMachine::new());
+        assert_eq!(machine.poll_wrapped_output(), Err(WaitReason::Input));
+
+        machine.process_input(EchoInput::Position(position(1)));
+
+        assert_eq!(
+            machine.poll_wrapped_output(),
+            Ok(EchoOutput::Position(position(1)))
+        );
+        assert_eq!(machine.poll_wrapped_output(), Err(WaitReason::Input));
+    }
+
+    #[test]
+    fn test_flight_path_waits_for_tick() {
+        let route = Route::Orbit {
+            latitude: 37.7749,
+            longitude: -122.4194,
+            radius_m: 200.0,
+        };
+        let mut machine = WaitFor::new(FlightPath::new("drone-1", route, 10.0, 100.0).unwrap());
+        assert_eq!(machine.poll_wrapped_output(), Err(WaitReason::NextTick));
+
+        machine.process_input(FlightPathInput::Tick {
+            elapsed: Duration::from_secs(1),
+            timestamp: 1,
+        });
+
+        assert!(machine.poll_wrapped_output().is_ok());
+        assert_eq!(machine.poll_wrapped_output(), Err(WaitReason::NextTick));
+    }
+}
@@ -0,0 +1,498 @@
+//! Helpers for subscribing to broadcasts announced over MoQ.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use futures::Stream;
+use futures::stream::{self, StreamExt};
+use moq_lite::{BroadcastConsumer, OriginConsumer};
+use tokio::time::Instant;
+use tracing::debug;
+
+/// Indicates why a broadcast could not be subscribed to.
+#[derive(Debug, thiserror::Error)]
+pub enum SubscribeError {
+    #[error("broadcast {path} was not announced within {timeout:?}")]
+    Timeout { path: String, timeout: Duration },
+    #[error("origin closed before broadcast {path} was announced")]
+    Closed { path: String },
+    #[error("consumer is not authorized to watch {prefix}")]
+    Unauthorized { prefix: String },
+}
+
+/// A broadcast (un)announced under one of the roots watched by [`multi_root`].
+#[derive(Clone)]
+pub struct RootAnnouncement {
+    /// The watched prefix the broadcast was announced under.
+    pub prefix: String,
+    /// The path of the broadcast relative to `prefix`.
+    pub path: String,
+    /// The announced broadcast, or `None` if it was unannounced.
+    pub broadcast: Option<BroadcastConsumer>,
+}
+
+/// Wait for the broadcast at `path` to be announced on `consumer`, for at most `timeout`.
+///
+/// Announcements of other paths are skipped, as is `path` being unannounced, so a publisher which
+/// starts after this is called, or restarts while waiting, is still found. A broadcast already
+/// announced when this is called is returned immediately.
+pub async fn subscribe_broadcast_with_timeout(
+    consumer: &OriginConsumer,
+    path: &str,
+    timeout: Duration,
+) -> Result<BroadcastConsumer, SubscribeError> {
+    // A fresh consumer replays the broadcasts which are already announced
+    let mut announcements = consumer.consume();
+
+    let wait_fut = async {
+        loop {
+            match announcements.announced().await {
+                Some((announced, Some(broadcast))) if announced.as_str() == path => {
+                    debug!(path = %path, "Found broadcast");
+                    return Ok(broadcast);
+                }
+                Some(_) => {
+                    // Not our path, or our path being unannounced, keep waiting
+                    continue;
+                }
+                None => {
+                    return Err(SubscribeError::Closed {
+                        path: path.to_string(),
+                    });
+                }
+            }
+        }
+    };
+
+    tokio::time::timeout(timeout, wait_fut)
+        .await
+        .map_err(|_| SubscribeError::Timeout {
+            path: path.to_string(),
+            timeout,
+        })?
+}
+
+/// Watch the announcements under each of `prefixes` on `consumer` as a single stream, each tagged
+/// with the prefix it was announced under.
+///
+/// Broadcasts already announced are replayed first, as with a fresh consumer. The stream ends once
+/// the announcements of every prefix have ended. Prefixes should not overlap, otherwise broadcasts
+/// under both are announced once for each.
+pub fn multi_root(
+    consumer: &OriginConsumer,
+    prefixes: &[&str],
+) -> Result<impl Stream<Item = RootAnnouncement> + Send + Unpin + use<>, SubscribeError> {
+    let roots = prefixes
+        .iter()
+        .map(|prefix| {
+            let root = consumer
+                .with_root(*prefix)
+                .ok_or_else(|| SubscribeError::Unauthorized {
+                    prefix: prefix.to_string(),
+                })?;
+            let prefix = prefix.to_string();
+
+            Ok(stream::unfold(root, move |mut root| {
+                let prefix = prefix.clone();
+                async move {
+                    let (path, broadcast) = root.announced().await?;
+                    let announcement = RootAnnouncement {
+                        prefix,
+                        path: path.as_str().to_string(),
+                        broadcast,
+                    };
+                    Some((announcement, root))
+                }
+            })
+            .boxed())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(stream::select_all(roots))
+}
+
+/// Suppresses repeated and flapping announcements of the same path within a window.
+///
+/// - An announcement of a path already announced within the window is suppressed.
+/// - An unannouncement is held back for the window. If the path is announced again before the
+///   window passes, the unannouncement is dropped and the new announcement is reported, so callers
+///   replace the broadcast they hold without seeing the path go away.
+///
+/// Time is provided by the caller, so the output is determined only by the announcements and the
+/// times they are observed at. See [`debounce_announcements`] for driving it from a consumer.
+#[derive(Debug)]
+pub struct AnnouncementDebouncer {
+    window: Duration,
+    // Paths reported as announced, and when they were last reported
+    announced: HashMap<String, Instant>,
+    // Paths with a held back unannouncement, and when it is released
+    unannouncing: HashMap<String, Instant>,
+}
+
+impl AnnouncementDebouncer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            announced: HashMap::new(),
+            unannouncing: HashMap::new(),
+        }
+    }
+
+    /// Observe `path` being announced with `broadcast`, or unannounced if `None`, at `now`.
+    ///
+    /// Returns the announcement if it should be reported now.
+    pub fn observe<T>(
+        &mut self,
+        path: String,
+        broadcast: Option<T>,
+        now: Instant,
+    ) -> Option<(String, Option<T>)> {
+        match broadcast {
+            Some(broadcast) => {
+                if self.unannouncing.remove(&path).is_some() {
+                    // The previous broadcast is gone, so the replacement must be reported
+                    debug!(path = %path, "Broadcast replaced within debounce window");
+                    self.announced.insert(path.clone(), now);
+                    return Some((path, Some(broadcast)));
+                }
+                if let Some(reported) = self.announced.get(&path)
+                    && now.duration_since(*reported) < self.window
+                {
+                    debug!(path = %path, "Suppressed duplicate announcement");
+                    return None;
+                }
+
+                self.announced.insert(path.clone(), now);
+                Some((path, Some(broadcast)))
+            }
+            None => {
+                // Unannouncements of paths never reported are not reported either
+                if self.announced.contains_key(&path) {
+                    self.unannouncing
+                        .entry(path)
+                        .or_insert_with(|| now + self.window);
+                }
+                None
+            }
+        }
+    }
+
+    /// When the earliest held back unannouncement is released, if any.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.unannouncing.values().min().copied()
+    }
+
+    /// Release the unannouncements held back until `now` or earlier, in the order they are due.
+    pub fn expire<T>(&mut self, now: Instant) -> Vec<(String, Option<T>)> {
+        let mut due: Vec<(Instant, String)> = self
+            .unannouncing
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(path, deadline)| (*deadline, path.clone()))
+            .collect();
+        due.sort();
+
+        due.into_iter()
+            .map(|(_, path)| {
+                self.unannouncing.remove(&path);
+                self.announced.remove(&path);
+                (path, None)
+            })
+            .collect()
+    }
+}
+
+/// The announcements of `consumer`, debounced over `window` by an [`AnnouncementDebouncer`].
+///
+/// Paths are relative to the root of `consumer`. Unannouncements still held back when the origin
+/// closes are dropped, as the stream ends with it.
+pub fn debounce_announcements(
+    mut consumer: OriginConsumer,
+    window: Duration,
+) -> impl Stream<Item = (String, Option<BroadcastConsumer>)> {
+    async_stream::stream! {
+        let mut debouncer = AnnouncementDebouncer::new(window);
+
+        loop {
+            let deadline = debouncer.next_deadline();
+            tokio::select! {
+                announced = consumer.announced() => {
+                    let Some((path, broadcast)) = announced else {
+                        break;
+                    };
+                    let path = path.as_str().to_string();
+                    if let Some(announcement) = debouncer.observe(path, broadcast, Instant::now()) {
+                        yield announcement;
+                    }
+                }
+                () = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)),
+                    if deadline.is_some() =>
+                {
+                    for announcement in debouncer.expire(Instant::now()) {
+                        yield announcement;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moq_lite::{Broadcast, Origin};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_returns_target_after_unrelated_announcement() {
+        let origin = Origin::produce();
+        let consumer = origin.consumer;
+
+        let waiter = tokio::spawn(async move {
+            subscribe_broadcast_with_timeout(&consumer, "control/drone-1", Duration::from_secs(5))
+                .await
+        });
+        tokio::task::yield_now().await;
+
+        let unrelated = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("control/drone-2", unrelated.consumer);
+        let target = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("control/drone-1", target.consumer.clone());
+
+        let broadcast = waiter.await.unwrap().unwrap();
+        assert!(broadcast.is_clone(&target.consumer));
+    }
+
+    #[tokio::test]
+    async fn test_returns_already_announced_broadcast() {
+        let origin = Origin::produce();
+        let target = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("control/drone-1", target.consumer.clone());
+
+        let broadcast = subscribe_broadcast_with_timeout(
+            &origin.consumer,
+            "control/drone-1",
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(broadcast.is_clone(&target.consumer));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_times_out_without_announcement() {
+        let origin = Origin::produce();
+        let unrelated = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("control/drone-2", unrelated.consumer);
+
+        let result = subscribe_broadcast_with_timeout(
+            &origin.consumer,
+            "control/drone-1",
+            Duration::from_secs(5),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(SubscribeError::Timeout { path, timeout })
+                if path == "control/drone-1" && timeout == Duration::from_secs(5)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_multi_root_tags_each_prefix() {
+        let origin = Origin::produce();
+        let mut announcements = multi_root(&origin.consumer, &["drone", "control"]).unwrap();
+
+        let drone = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("drone/drone-1", drone.consumer.clone());
+        let unrelated = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("telemetry/drone-1", unrelated.consumer);
+        let control = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("control/drone-1", control.consumer.clone());
+
+        let mut seen = Vec::new();
+        for _ in 0..2 {
+            let announcement = tokio::time::timeout(Duration::from_secs(5), announcements.next())
+                .await
+                .unwrap()
+                .unwrap();
+            let broadcast = announcement.broadcast.unwrap();
+            let expected = match announcement.prefix.as_str() {
+                "drone" => &drone.consumer,
+                "control" => &control.consumer,
+                prefix => panic!("announced under unwatched prefix {prefix}"),
+            };
+            assert!(broadcast.is_clone(expected));
+            seen.push((announcement.prefix, announcement.path));
+        }
+        seen.sort();
+
+        assert_eq!(
+            seen,
+            vec![
+                ("control".to_string(), "drone-1".to_string()),
+                ("drone".to_string(), "drone-1".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multi_root_reports_unannounced() {
+        let origin = Origin::produce();
+        let drone = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("drone/drone-1", drone.consumer);
+        let mut announcements = multi_root(&origin.consumer, &["drone", "control"]).unwrap();
+
+        let announced = announcements.next().await.unwrap();
+        assert!(announced.broadcast.is_some());
+
+        drop(drone.producer);
+
+        let unannounced = announcements.next().await.unwrap();
+        assert_eq!(unannounced.prefix, "drone");
+        assert_eq!(unannounced.path, "drone-1");
+        assert!(unannounced.broadcast.is_none());
+    }
+
+    const WINDOW: Duration = Duration::from_secs(2);
+
+    fn observe(
+        debouncer: &mut AnnouncementDebouncer,
+        path: &str,
+        announced: bool,
+        at: Instant,
+    ) -> Option<(String, Option<()>)> {
+        debouncer.observe(path.to_string(), announced.then_some(()), at)
+    }
+
+    #[test]
+    fn test_debounce_suppresses_duplicate_announcements() {
+        let start = Instant::now();
+        let mut debouncer = AnnouncementDebouncer::new(WINDOW);
+
+        let reported: Vec<_> = [0, 500, 1500]
+            .into_iter()
+            .filter_map(|ms| {
+                observe(
+                    &mut debouncer,
+                    "drone-1",
+                    true,
+                    start + Duration::from_millis(ms),
+                )
+            })
+            .collect();
+        assert_eq!(reported, vec![("drone-1".to_string(), Some(()))]);
+
+        // Other paths are debounced independently, and the window restarts once it has passed
+        assert!(observe(&mut debouncer, "drone-2", true, start).is_some());
+        assert!(observe(&mut debouncer, "drone-1", true, start + WINDOW).is_some());
+    }
+
+    #[test]
+    fn test_debounce_suppresses_flapping_unannouncements() {
+        let start = Instant::now();
+        let mut debouncer = AnnouncementDebouncer::new(WINDOW);
+        assert!(observe(&mut debouncer, "drone-1", true, start).is_some());
+
+        // Each replacement broadcast is reported, but the path is never reported as gone
+        for ms in [3000, 3200, 3400, 3600] {
+            let at = start + Duration::from_millis(ms);
+            assert!(observe(&mut debouncer, "drone-1", false, at).is_none());
+            assert_eq!(
+                observe(&mut debouncer, "drone-1", true, at),
+                Some(("drone-1".to_string(), Some(())))
+            );
+        }
+
+        assert_eq!(debouncer.next_deadline(), None);
+        assert!(debouncer.expire::<()>(start + 3 * WINDOW).is_empty());
+    }
+
+    #[test]
+    fn test_debounce_releases_unannouncement_after_window() {
+        let start = Instant::now();
+        let mut debouncer = AnnouncementDebouncer::new(WINDOW);
+        observe(&mut debouncer, "drone-1", true, start);
+        observe(&mut debouncer, "drone-2", true, start);
+
+        let gone = start + Duration::from_secs(1);
+        assert!(observe(&mut debouncer, "drone-2", false, gone).is_none());
+        assert!(
+            observe(
+                &mut debouncer,
+                "drone-1",
+                false,
+                gone + Duration::from_millis(1)
+            )
+            .is_none()
+        );
+        // Unannouncing a path never announced is ignored
+        assert!(observe(&mut debouncer, "drone-3", false, gone).is_none());
+
+        assert!(debouncer.expire::<()>(gone + WINDOW / 2).is_empty());
+        assert_eq!(debouncer.next_deadline(), Some(gone + WINDOW));
+        assert_eq!(
+            debouncer.expire::<()>(gone + 2 * WINDOW),
+            vec![("drone-2".to_string(), None), ("drone-1".to_string(), None)]
+        );
+
+        // Once released, a new announcement is reported straight away
+        assert!(observe(&mut debouncer, "drone-1", true, gone + 2 * WINDOW).is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_debounce_announcements_stream() {
+        let origin = Origin::produce();
+        let mut announcements = Box::pin(debounce_announcements(
+            origin.consumer.with_root("drone").unwrap(),
+            WINDOW,
+        ));
+
+        let first = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("drone/drone-1", first.consumer.clone());
+        let (path, broadcast) = announcements.next().await.unwrap();
+        assert_eq!(path, "drone-1");
+        assert!(broadcast.unwrap().is_clone(&first.consumer));
+
+        // Flapping within the window reports only the replacement broadcast
+        drop(first.producer);
+        let second = Broadcast::produce();
+        origin
+            .producer
+            .publish_broadcast("drone/drone-1", second.consumer.clone());
+        let (path, broadcast) = announcements.next().await.unwrap();
+        assert_eq!(path, "drone-1");
+        assert!(broadcast.unwrap().is_clone(&second.consumer));
+        assert!(
+            tokio::time::timeout(3 * WINDOW, announcements.next())
+                .await
+                .is_err()
+        );
+
+        // Going away for good is reported once the window passes
+        drop(second.producer);
+        let started = Instant::now();
+        let (path, broadcast) = announcements.next().await.unwrap();
+        assert_eq!(path, "drone-1");
+        assert!(broadcast.is_none());
+        assert!(started.elapsed() >= WINDOW);
+    }
+}
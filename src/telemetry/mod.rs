@@ -0,0 +1,215 @@
+//! Helpers for publishing drone telemetry over MoQ.
+
+use std::ops::Range;
+
+use moq_lite::TrackProducer;
+
+use crate::codec::{FrameCodec, ProstCodec};
+use crate::drone_proto::DronePosition;
+use crate::state_machine::echo::Position;
+
+/// Publishes [`Position`]s as frames on a MoQ track.
+///
+/// Positions are converted to [`DronePosition`] and encoded with the codec `C`, the protobuf wire
+/// format by default. Each position is written as its own frame, numbered with the next
+/// [`sequence`](DronePosition::sequence) starting from zero.
+pub struct TelemetryPublisher<C = ProstCodec> {
+    track: TrackProducer,
+    codec: C,
+    dedup: bool,
+    last: Option<Position>,
+    next_sequence: u64,
+}
+
+impl TelemetryPublisher {
+    /// Create a publisher writing protobuf encoded positions to `track`.
+    pub fn new(track: TrackProducer) -> Self {
+        Self::with_codec(track, ProstCodec)
+    }
+}
+
+impl<C: FrameCodec<DronePosition>> TelemetryPublisher<C> {
+    /// Create a publisher writing positions encoded with `codec` to `track`.
+    pub fn with_codec(track: TrackProducer, codec: C) -> Self {
+        Self {
+            track,
+            codec,
+            dedup: false,
+            last: None,
+            next_sequence: 0,
+        }
+    }
+
+    /// Skip publishing a position identical to the previously published position.
+    ///
+    /// Disabled by default.
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Publish `pos`, returning whether a frame was written.
+    ///
+    /// No frame is written if deduplication is enabled and `pos` is identical to the previously
+    /// published position.
+    pub fn publish(&mut self, pos: Position) -> bool {
+        if self.dedup && self.last.as_ref() == Some(&pos) {
+            return false;
+        }
+
+        let frame = self.codec.encode(&DronePosition {
+            sequence: self.next_sequence,
+            ..DronePosition::from(pos.clone())
+        });
+        self.track.write_frame(frame);
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+
+        if self.dedup {
+            self.last = Some(pos);
+        }
+        true
+    }
+}
+
+/// Detects positions dropped between a sender and receiver from their
+/// [`sequence`](DronePosition::sequence) numbers.
+///
+/// The first sequence observed is taken as the start, so a receiver joining mid-stream does not
+/// report everything before it as missing. A sequence at or below the last one observed is a
+/// duplicate, reordering or sender restart, and restarts detection from it, as does the sequence
+/// wrapping around after [`u64::MAX`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GapDetector {
+    last: Option<u64>,
+}
+
+impl GapDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Observe the next received `sequence`, returning the range of sequences missed before it.
+    pub fn observe(&mut self, sequence: u64) -> Option<Range<u64>> {
+        // After the largest sequence the sender wraps around, which is treated as a restart
+        let expected = self.last.and_then(|last| last.checked_add(1));
+        self.last = Some(sequence);
+
+        expected
+            .filter(|&expected| sequence > expected)
+            .map(|expected| expected..sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+    use moq_lite::{Track, TrackConsumer};
+
+    use super::*;
+    use crate::state_machine::echo::position;
+
+    /// Read the frame of the next group, if one has been written.
+    async fn next_frame(consumer: &mut TrackConsumer) -> Option<Position> {
+        next_proto(consumer).await.map(Position::from)
+    }
+
+    async fn next_proto(consumer: &mut TrackConsumer) -> Option<DronePosition> {
+        let mut group = consumer.next_group().now_or_never()?.unwrap()?;
+        let frame = group.read_frame().await.unwrap().unwrap();
+        Some(ProstCodec.decode(&frame).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_publish_writes_frames() {
+        let track = Track::new("primary").produce();
+        let mut consumer = track.consumer;
+        let mut publisher = TelemetryPublisher::new(track.producer);
+
+        for timestamp in [1, 1, 2] {
+            assert!(publisher.publish(position(timestamp)));
+            assert_eq!(next_frame(&mut consumer).await, Some(position(timestamp)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_suppresses_duplicates() {
+        let track = Track::new("primary").produce();
+        let mut consumer = track.consumer;
+        let mut publisher = TelemetryPublisher::new(track.producer).with_dedup(true);
+
+        assert!(publisher.publish(position(1)));
+        assert_eq!(next_frame(&mut consumer).await, Some(position(1)));
+
+        assert!(!publisher.publish(position(1)));
+        assert_eq!(next_frame(&mut consumer).await, None);
+
+        assert!(publisher.publish(position(2)));
+        assert_eq!(next_frame(&mut consumer).await, Some(position(2)));
+    }
+
+    #[tokio::test]
+    async fn test_publish_numbers_frames() {
+        let track = Track::new("primary").produce();
+        let mut consumer = track.consumer;
+        let mut publisher = TelemetryPublisher::new(track.producer).with_dedup(true);
+
+        for (timestamp, sequence) in [(1, 0), (2, 1)] {
+            publisher.publish(position(timestamp));
+            assert_eq!(next_proto(&mut consumer).await.unwrap().sequence, sequence);
+        }
+
+        // Suppressed duplicates do not use up a sequence
+        assert!(!publisher.publish(position(2)));
+        publisher.publish(position(3));
+        assert_eq!(next_proto(&mut consumer).await.unwrap().sequence, 2);
+    }
+
+    #[test]
+    fn test_gap_detector_reports_missing_sequence() {
+        let mut detector = GapDetector::new();
+
+        assert_eq!(detector.observe(0), None);
+        assert_eq!(detector.observe(1), None);
+        assert_eq!(detector.observe(3), Some(2..3));
+        assert_eq!(detector.observe(4), None);
+    }
+
+    #[test]
+    fn test_gap_detector_reports_missing_range() {
+        let mut detector = GapDetector::new();
+
+        detector.observe(5);
+
+        assert_eq!(detector.observe(9), Some(6..9));
+    }
+
+    #[test]
+    fn test_gap_detector_starts_from_first_observed() {
+        let mut detector = GapDetector::new();
+
+        assert_eq!(detector.observe(100), None);
+        assert_eq!(detector.observe(101), None);
+    }
+
+    #[test]
+    fn test_gap_detector_restarts_after_regression() {
+        let mut detector = GapDetector::new();
+        detector.observe(10);
+
+        // A duplicate or restarted sender is not a gap
+        assert_eq!(detector.observe(10), None);
+        assert_eq!(detector.observe(0), None);
+        assert_eq!(detector.observe(2), Some(1..2));
+    }
+
+    #[test]
+    fn test_gap_detector_restarts_after_wrapping() {
+        let mut detector = GapDetector::new();
+        assert_eq!(detector.observe(u64::MAX - 2), None);
+
+        assert_eq!(detector.observe(u64::MAX), Some(u64::MAX - 1..u64::MAX));
+        assert_eq!(detector.observe(u64::MAX), None);
+        assert_eq!(detector.observe(0), None);
+        assert_eq!(detector.observe(1), None);
+    }
+}
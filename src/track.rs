@@ -0,0 +1,92 @@
+//! Helpers for writing to MoQ tracks.
+
+use futures::FutureExt;
+use moq_lite::TrackProducer;
+
+/// Indicates that a frame was not written because the track has no consumers.
+#[derive(Debug, thiserror::Error)]
+#[error("track {track} has no consumers")]
+pub struct TrackClosed {
+    /// The name of the track.
+    pub track: String,
+}
+
+/// Writes frames to a MoQ track, reporting when no one is listening.
+///
+/// [`TrackProducer::write_frame`] succeeds whether or not the track has consumers, so a producer
+/// can keep encoding frames long after every subscriber has gone away. A `TrackSink` checks for
+/// consumers before each write, letting the caller stop instead.
+pub struct TrackSink {
+    track: TrackProducer,
+}
+
+impl TrackSink {
+    pub fn new(track: TrackProducer) -> Self {
+        Self { track }
+    }
+
+    /// Whether the track has no consumers.
+    ///
+    /// A track which has never been consumed is also closed.
+    pub fn is_closed(&self) -> bool {
+        self.track.unused().now_or_never().is_some()
+    }
+
+    /// Write `frame` to the track as its own group.
+    ///
+    /// Nothing is written if the track has no consumers.
+    pub fn write_frame(&mut self, frame: Vec<u8>) -> Result<(), TrackClosed> {
+        if self.is_closed() {
+            return Err(TrackClosed {
+                track: self.track.info.name.clone(),
+            });
+        }
+
+        self.track.write_frame(frame);
+        Ok(())
+    }
+
+    /// Unwrap the underlying track producer.
+    pub fn into_inner(self) -> TrackProducer {
+        self.track
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moq_lite::Track;
+
+    use super::*;
+
+    #[test]
+    fn test_write_frame_succeeds_with_consumer() {
+        let track = Track::new("primary").produce();
+        let mut consumer = track.consumer;
+        let mut sink = TrackSink::new(track.producer);
+
+        assert!(!sink.is_closed());
+        sink.write_frame(b"hello".to_vec()).unwrap();
+
+        let mut group = consumer
+            .next_group()
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let frame = group.read_frame().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(frame.as_ref(), b"hello");
+    }
+
+    #[test]
+    fn test_write_frame_reports_closed_after_consumer_dropped() {
+        let track = Track::new("primary").produce();
+        let mut sink = TrackSink::new(track.producer);
+        sink.write_frame(b"hello".to_vec()).unwrap();
+
+        drop(track.consumer);
+
+        assert!(sink.is_closed());
+        let err = sink.write_frame(b"hello".to_vec()).unwrap_err();
+        assert_eq!(err.track, "primary");
+    }
+}
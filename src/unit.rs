@@ -5,15 +5,38 @@ use std::sync::Arc;
 /// a drone, dock, and potentially other hardware.
 ///
 /// Currently this can be any generic string and is notably also externally editable.
+///
+/// Since unit ids are used as broadcast path segments, a valid id is non-empty and contains
+/// neither `/` nor whitespace. Use [`try_new`](UnitId::try_new) to enforce this.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct UnitId(Arc<str>);
 
 impl UnitId {
     /// Create a new [`UnitId`] from any type that can be converted into an `Arc<str>`.
+    ///
+    /// The id is not validated, so this can produce an invalid id. Prefer
+    /// [`try_new`](UnitId::try_new) for ids from untrusted sources.
     pub fn new(id: impl Into<Arc<str>>) -> Self {
         Self(id.into())
     }
 
+    /// Create a new [`UnitId`], validating that it is usable as a broadcast path segment.
+    pub fn try_new(id: impl Into<Arc<str>>) -> Result<Self, InvalidUnitId> {
+        let id = id.into();
+
+        if id.is_empty() {
+            return Err(InvalidUnitId::Empty);
+        }
+        if id.contains('/') {
+            return Err(InvalidUnitId::ContainsSlash { id: id.to_string() });
+        }
+        if id.contains(char::is_whitespace) {
+            return Err(InvalidUnitId::ContainsWhitespace { id: id.to_string() });
+        }
+
+        Ok(Self(id))
+    }
+
     /// Returns the underlying string slice.
     pub fn as_str(&self) -> &str {
         &self.0
@@ -26,6 +49,7 @@ impl Display for UnitId {
     }
 }
 
+/// The `From` conversions are unvalidated like [`UnitId::new`].
 impl From<String> for UnitId {
     fn from(s: String) -> Self {
         Self(s.into())
@@ -37,3 +61,55 @@ impl From<&str> for UnitId {
         Self(s.into())
     }
 }
+
+/// Indicates why a string is not a valid [`UnitId`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidUnitId {
+    #[error("unit id must not be empty")]
+    Empty,
+    #[error("unit id {id:?} must not contain '/'")]
+    ContainsSlash { id: String },
+    #[error("unit id {id:?} must not contain whitespace")]
+    ContainsWhitespace { id: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_new_valid() {
+        let unit_id = UnitId::try_new("drone-1").unwrap();
+        assert_eq!(unit_id, UnitId::from("drone-1"));
+    }
+
+    #[test]
+    fn test_try_new_rejects_empty() {
+        assert_eq!(UnitId::try_new(""), Err(InvalidUnitId::Empty));
+    }
+
+    #[test]
+    fn test_try_new_rejects_slash() {
+        let err = UnitId::try_new("fleet/drone-1").unwrap_err();
+        assert_eq!(
+            err,
+            InvalidUnitId::ContainsSlash {
+                id: "fleet/drone-1".to_string()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "unit id \"fleet/drone-1\" must not contain '/'"
+        );
+    }
+
+    #[test]
+    fn test_try_new_rejects_whitespace() {
+        for id in ["drone 1", " drone-1", "drone-1\n", "drone\t1"] {
+            assert_eq!(
+                UnitId::try_new(id),
+                Err(InvalidUnitId::ContainsWhitespace { id: id.to_string() })
+            );
+        }
+    }
+}
@@ -1,34 +1,55 @@
-use std::sync::Mutex;
-
 use crate::state_machine::{
     StateMachine,
     echo::{EchoInput, EchoMachine, EchoOutput, Position},
+    health::{Health, HealthInput, HealthMachine, HealthOutput},
 };
 
+/// The state of a single unit.
+///
+/// The context owns its state machines outright and is only changed through `&mut self`, so the
+/// machines stay pure. Shared contexts are changed through
+/// [`UnitMap::with_unit_mut`](crate::unit_map::UnitMap::with_unit_mut), and read through
+/// [`UnitRef::view`](crate::unit_map::unit_ref::UnitRef::view).
 #[derive(Debug)]
 pub struct UnitContext {
-    echo: Mutex<EchoMachine>,
+    echo: EchoMachine,
+    health: HealthMachine,
 }
 
 impl UnitContext {
     pub fn new() -> Self {
         Self {
-            echo: Mutex::new(EchoMachine::new()),
+            echo: EchoMachine::new(),
+            health: HealthMachine::new(),
         }
     }
 
     // TODO: Make a view type instead of passing through to the state machine here
-    pub fn update_position(&self, pos: Position) {
-        let mut machine = self.echo.lock().expect("telemetry machine lock poisoned");
-        machine.process_input(EchoInput::Position(pos));
+    pub fn update_position(&mut self, pos: Position) {
+        self.echo.process_input(EchoInput::Position(pos));
     }
 
-    pub fn poll_position(&self) -> Option<Position> {
-        let mut machine = self.echo.lock().expect("telemetry machine lock poisoned");
-        machine.poll_output().map(|out| match out {
+    pub fn poll_position(&mut self) -> Option<Position> {
+        self.echo.poll_output().map(|out| match out {
             EchoOutput::Position(pos) => pos,
         })
     }
+
+    /// The latest position, without consuming the pending update returned by
+    /// [`poll_position`](Self::poll_position).
+    pub fn peek_position(&self) -> Option<Position> {
+        self.echo.current_position().cloned()
+    }
+
+    pub fn update_health(&mut self, health: Health) {
+        self.health.process_input(HealthInput::Health(health));
+    }
+
+    pub fn poll_health(&mut self) -> Option<Health> {
+        self.health.poll_output().map(|out| match out {
+            HealthOutput::Health(health) => health,
+        })
+    }
 }
 
 impl Default for UnitContext {
@@ -36,3 +57,78 @@ impl Default for UnitContext {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine::echo::position;
+    use crate::state_machine::health::HealthStatus;
+    use crate::unit::UnitId;
+    use crate::unit_map::UnitMap;
+
+    #[test]
+    fn test_peek_position_does_not_consume_pending() {
+        let mut ctx = UnitContext::new();
+        assert_eq!(ctx.peek_position(), None);
+
+        ctx.update_position(position(1));
+        assert_eq!(ctx.peek_position(), Some(position(1)));
+        assert_eq!(ctx.peek_position(), Some(position(1)));
+        assert_eq!(ctx.poll_position(), Some(position(1)));
+
+        // The latest position is still visible once consumed
+        assert_eq!(ctx.poll_position(), None);
+        assert_eq!(ctx.peek_position(), Some(position(1)));
+    }
+
+    #[test]
+    fn test_pending_position_is_replaced_by_newer() {
+        let mut ctx = UnitContext::new();
+
+        ctx.update_position(position(1));
+        ctx.update_position(position(2));
+        assert_eq!(ctx.poll_position(), Some(position(2)));
+        assert_eq!(ctx.poll_position(), None);
+    }
+
+    #[test]
+    fn test_shared_context_is_updated_while_referenced() {
+        let map = UnitMap::new();
+        let unit_id = UnitId::from("drone-1");
+        map.insert_unit(unit_id.clone(), UnitContext::new())
+            .unwrap();
+
+        // An outstanding reference does not block updates through the map
+        let held = map.get_unit(&unit_id).unwrap();
+        map.with_unit_mut(&unit_id, |ctx| ctx.update_position(position(1)))
+            .unwrap();
+
+        assert_eq!(
+            held.view(|ctx| ctx.peek_position()).unwrap(),
+            Some(position(1))
+        );
+        assert_eq!(
+            map.with_unit_mut(&unit_id, |ctx| ctx.poll_position())
+                .unwrap(),
+            Some(position(1))
+        );
+    }
+
+    #[test]
+    fn test_health_is_polled_independently_of_position() {
+        let mut ctx = UnitContext::new();
+        let health = Health {
+            drone_id: "drone-1".to_string(),
+            battery_percent: 64.0,
+            status: HealthStatus::Nominal,
+            timestamp: 1,
+        };
+
+        ctx.update_position(position(1));
+        ctx.update_health(health.clone());
+
+        assert_eq!(ctx.poll_health(), Some(health));
+        assert_eq!(ctx.poll_health(), None);
+        assert_eq!(ctx.poll_position(), Some(position(1)));
+    }
+}
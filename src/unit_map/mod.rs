@@ -1,5 +1,6 @@
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
+use crate::metrics::UnitMapMetrics;
 pub use crate::unit::UnitId;
 use dashmap::{DashMap, Entry};
 
@@ -11,16 +12,28 @@ use self::{
 pub mod error;
 pub mod unit_ref;
 
+/// The units of a [`UnitMap`], shared with the [`UnitRef`]s looking them up.
+type EntityMap<T> = DashMap<UnitId, UnitEntity<T>, ahash::RandomState>;
+
+/// A unit context owned by its [`UnitMap`].
+#[derive(Debug)]
+struct UnitEntity<T> {
+    unit_context: T,
+    /// Dropped along with the entity, invalidating every [`UnitRef`] obtained for it.
+    liveness: Arc<()>,
+}
+
 /// A map of units identified by a [`UnitId`] and their associated context `T`.
 ///
 /// When a unit is added to the map it is turned into a shared resource for which only references
 /// can be obtained through the [`UnitRef`] interface.
 ///
-/// Direct access to the strong reference is not allowed in order to prevent long lived upgrades
-/// undermining lifecycle control from the [`UnitMap`].
+/// The map owns each unit context outright and a [`UnitRef`] looks it up through the map, so
+/// outstanding references neither extend the lifetime of a unit nor prevent
+/// [`with_unit_mut`](Self::with_unit_mut) from mutating it.
 #[derive(Debug)]
 pub struct UnitMap<T> {
-    entity_map: DashMap<UnitId, Arc<T>, ahash::RandomState>,
+    entity_map: Arc<EntityMap<T>>,
 }
 
 impl<T> UnitMap<T> {
@@ -37,12 +50,25 @@ impl<T> UnitMap<T> {
             }),
 
             Entry::Vacant(slot) => {
-                slot.insert(Arc::new(unit_context));
+                slot.insert(UnitEntity::new(unit_context));
                 Ok(())
             }
         }
     }
 
+    /// Lend the unit context for the provided `unit_id`, creating it with `make` if not present.
+    ///
+    /// The lookup and insertion happen atomically, so `make` is called at most once per unit even
+    /// when multiple callers race to create the same unit.
+    pub fn get_or_insert_unit(&self, unit_id: UnitId, make: impl FnOnce() -> T) -> UnitRef<T> {
+        let entity = self
+            .entity_map
+            .entry(unit_id.clone())
+            .or_insert_with(|| UnitEntity::new(make()));
+
+        self.unit_ref(unit_id, &entity)
+    }
+
     /// Remove the unit entity for the provided `unit_id`.
     pub fn remove_unit(&self, unit_id: &UnitId) -> Result<(), UnitNotFound> {
         self.entity_map
@@ -59,19 +85,220 @@ impl<T> UnitMap<T> {
     /// If the unit is present returns a [`UnitRef`] containing the unit context `T`.
     pub fn get_unit(&self, unit_id: &UnitId) -> Result<UnitRef<T>, UnitNotFound> {
         self.entity_map
-            .view(unit_id, |_, entity| {
-                UnitRef::new(unit_id.clone(), Arc::downgrade(entity))
+            .view(unit_id, |_, entity| self.unit_ref(unit_id.clone(), entity))
+            .ok_or_else(|| UnitNotFound {
+                unit_id: unit_id.clone(),
+            })
+    }
+
+    /// Scoped shared access via `view_fn` to the unit context for the provided `unit_id`.
+    ///
+    /// The shard of the map containing the unit is read locked for the duration of `view_fn`, so
+    /// `view_fn` must not write to this map as doing so may deadlock.
+    pub fn with_unit<R>(
+        &self,
+        unit_id: &UnitId,
+        view_fn: impl FnOnce(&T) -> R,
+    ) -> Result<R, UnitNotFound> {
+        self.entity_map
+            .view(unit_id, |_, entity| view_fn(&entity.unit_context))
+            .ok_or_else(|| UnitNotFound {
+                unit_id: unit_id.clone(),
             })
+    }
+
+    /// Scoped mutable access via `mut_fn` to the unit context for the provided `unit_id`.
+    ///
+    /// The shard of the map containing the unit is write locked for the duration of `mut_fn`, so
+    /// `mut_fn` must not call back into this map, including through a [`UnitRef`], as doing so may
+    /// deadlock.
+    pub fn with_unit_mut<R>(
+        &self,
+        unit_id: &UnitId,
+        mut_fn: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, UnitNotFound> {
+        self.entity_map
+            .get_mut(unit_id)
+            .map(|mut entity| mut_fn(&mut entity.unit_context))
             .ok_or_else(|| UnitNotFound {
                 unit_id: unit_id.clone(),
             })
     }
+
+    /// The number of units currently tracked by the map.
+    pub fn unit_count(&self) -> usize {
+        self.entity_map.len()
+    }
+
+    /// Check whether a unit is tracked for the provided `unit_id`.
+    pub fn contains_unit(&self, unit_id: &UnitId) -> bool {
+        self.entity_map.contains_key(unit_id)
+    }
+
+    /// Snapshot the counts of the map for export.
+    pub fn metrics(&self) -> UnitMapMetrics {
+        UnitMapMetrics {
+            connected_units: self.unit_count(),
+        }
+    }
+
+    fn unit_ref(&self, unit_id: UnitId, entity: &UnitEntity<T>) -> UnitRef<T> {
+        UnitRef::new(
+            unit_id,
+            Arc::downgrade(&self.entity_map),
+            Arc::downgrade(&entity.liveness),
+        )
+    }
 }
 
 impl<T> Default for UnitMap<T> {
     fn default() -> Self {
         Self {
-            entity_map: DashMap::default(),
+            entity_map: Arc::default(),
+        }
+    }
+}
+
+impl<T> UnitEntity<T> {
+    fn new(unit_context: T) -> Self {
+        Self {
+            unit_context,
+            liveness: Arc::new(()),
+        }
+    }
+
+    /// Whether `liveness` was obtained from this entity, rather than one since replaced.
+    fn is_alive_as(&self, liveness: &Weak<()>) -> bool {
+        std::ptr::eq(Arc::as_ptr(&self.liveness), liveness.as_ptr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_empty_map() {
+        let map = UnitMap::<()>::new();
+
+        assert_eq!(map.unit_count(), 0);
+        assert!(!map.contains_unit(&UnitId::from("drone-1")));
+    }
+
+    #[test]
+    fn test_populated_map() {
+        let map = UnitMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        map.insert_unit(unit_id.clone(), ()).unwrap();
+        map.insert_unit(UnitId::from("drone-2"), ()).unwrap();
+
+        assert_eq!(map.unit_count(), 2);
+        assert!(map.contains_unit(&unit_id));
+        assert!(!map.contains_unit(&UnitId::from("drone-3")));
+    }
+
+    #[test]
+    fn test_counts_after_removal() {
+        let map = UnitMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        map.insert_unit(unit_id.clone(), ()).unwrap();
+        map.remove_unit(&unit_id).unwrap();
+
+        assert_eq!(map.unit_count(), 0);
+        assert!(!map.contains_unit(&unit_id));
+    }
+
+    #[test]
+    fn test_get_or_insert_unit_creates_once() {
+        let map = Arc::new(UnitMap::new());
+        let created = Arc::new(AtomicUsize::new(0));
+        let unit_id = UnitId::from("drone-1");
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let map = Arc::clone(&map);
+                let created = Arc::clone(&created);
+                let unit_id = unit_id.clone();
+                thread::spawn(move || {
+                    map.get_or_insert_unit(unit_id, || created.fetch_add(1, Ordering::SeqCst))
+                        .view(|_| ())
+                        .unwrap();
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+        assert_eq!(map.unit_count(), 1);
+    }
+
+    #[test]
+    fn test_get_or_insert_unit_returns_existing() {
+        let map = UnitMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        map.insert_unit(unit_id.clone(), 1).unwrap();
+
+        let unit_ref = map.get_or_insert_unit(unit_id, || 2);
+        assert_eq!(unit_ref.view(|value| *value).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_with_unit_mut() {
+        let map = UnitMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        map.insert_unit(unit_id.clone(), 0_u32).unwrap();
+
+        for _ in 0..3 {
+            map.with_unit_mut(&unit_id, |count| *count += 1).unwrap();
         }
+
+        let count = map
+            .get_unit(&unit_id)
+            .unwrap()
+            .view(|count| *count)
+            .unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_with_unit() {
+        let map = UnitMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        map.insert_unit(unit_id.clone(), 7_u32).unwrap();
+
+        assert_eq!(map.with_unit(&unit_id, |value| *value).unwrap(), 7);
+        assert!(map.with_unit(&UnitId::from("drone-2"), |_| ()).is_err());
+    }
+
+    #[test]
+    fn test_with_unit_mut_not_found() {
+        let map = UnitMap::<u32>::new();
+
+        let result = map.with_unit_mut(&UnitId::from("drone-1"), |_| ());
+        assert_eq!(result.unwrap_err().unit_id, UnitId::from("drone-1"));
+    }
+
+    #[test]
+    fn test_with_unit_mut_while_referenced() {
+        let map = UnitMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        map.insert_unit(unit_id.clone(), 0_u32).unwrap();
+        let unit_ref = map.get_unit(&unit_id).unwrap();
+
+        map.with_unit_mut(&unit_id, |count| *count += 1).unwrap();
+
+        assert_eq!(unit_ref.view(|count| *count).unwrap(), 1);
     }
 }
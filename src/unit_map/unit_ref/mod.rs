@@ -1,36 +1,97 @@
-use std::{convert::Infallible, fmt, sync::Weak};
+use std::{
+    convert::Infallible,
+    fmt,
+    future::Future,
+    sync::{Arc, Weak},
+};
 
 use self::error::UnitViewInvalid;
-use super::UnitId;
+use super::{EntityMap, UnitId};
 
 pub mod error;
 
-/// A weak reference to a shared unit context that provides a scoped [`view`](Self::view).
+/// A weak reference to a unit context held by a [`UnitMap`](super::UnitMap) that provides a
+/// scoped [`view`](Self::view).
+///
+/// The unit context is looked up through the map on each access, so the reference is valid only
+/// while the unit it was obtained for remains in the map.
 pub struct UnitRef<T> {
     unit_id: UnitId,
-    weak_unit_context: Weak<T>,
+    weak_entity_map: Weak<EntityMap<T>>,
+    weak_liveness: Weak<()>,
 }
 
 impl<T> UnitRef<T> {
-    /// Construct a new [`UnitRef`] from an identifier and weak reference to a unit context.
-    pub(super) fn new(unit_id: UnitId, weak_unit_context: Weak<T>) -> UnitRef<T> {
+    /// Construct a new [`UnitRef`] from an identifier and weak references to the map holding the
+    /// unit and the liveness of the unit within it.
+    pub(super) fn new(
+        unit_id: UnitId,
+        weak_entity_map: Weak<EntityMap<T>>,
+        weak_liveness: Weak<()>,
+    ) -> UnitRef<T> {
         Self {
             unit_id,
-            weak_unit_context,
+            weak_entity_map,
+            weak_liveness,
         }
     }
 
     /// Scoped access via a `view_fn` to the `unit_context` for the unit reference.
     ///
+    /// The shard of the map containing the unit is read locked for the duration of `view_fn`, so
+    /// `view_fn` must not write to the map as doing so may deadlock.
+    ///
     /// If the unit context exists returns the value `R` computed from the `view_fn`, else
     /// returns a [`UnitViewInvalid`] error indicating unit context is no longer valid.
     pub fn view<F: FnOnce(&T) -> R, R>(&self, view_fn: F) -> Result<R, UnitViewInvalid> {
-        Weak::upgrade(&self.weak_unit_context)
-            .map(|unit_context| view_fn(&unit_context))
+        Weak::upgrade(&self.weak_entity_map)
+            .and_then(|entity_map| {
+                entity_map
+                    .view(&self.unit_id, |_, entity| {
+                        entity
+                            .is_alive_as(&self.weak_liveness)
+                            .then(|| view_fn(&entity.unit_context))
+                    })
+                    .flatten()
+            })
             .ok_or(UnitViewInvalid {
                 unit_id: self.unit_id.clone(),
             })
     }
+
+    /// Asynchronous access via a `view_fn` to the `unit_context` for the unit reference.
+    ///
+    /// Unlike the strict scoping of [`view`](Self::view), a snapshot of the unit context is cloned
+    /// and handed to `view_fn`, so the map is not locked while the returned future is pending.
+    /// The snapshot lives for as long as the future holds it, even if the unit is removed from its
+    /// owning map in the meantime, but does not observe later changes to the unit.
+    ///
+    /// If the unit context exists returns the value `R` awaited from the `view_fn`, else returns a
+    /// [`UnitViewInvalid`] error indicating unit context is no longer valid.
+    pub async fn view_async<F, Fut, R>(&self, view_fn: F) -> Result<R, UnitViewInvalid>
+    where
+        T: Clone,
+        F: FnOnce(Arc<T>) -> Fut,
+        Fut: Future<Output = R>,
+    {
+        let unit_context = self.view(|unit_context| Arc::new(unit_context.clone()))?;
+
+        Ok(view_fn(unit_context).await)
+    }
+
+    /// Check whether the unit context for this reference is still alive.
+    pub fn is_valid(&self) -> bool {
+        self.weak_liveness.strong_count() > 0
+    }
+
+    /// The number of strong references to the unit context: `1` while it is held by its map, or
+    /// `0` once it has been removed.
+    ///
+    /// This is intended for debugging and is inherently racy, as the unit may be removed at any
+    /// time.
+    pub fn strong_count(&self) -> usize {
+        self.weak_liveness.strong_count()
+    }
 }
 
 #[expect(clippy::missing_fields_in_debug, reason = "custom weak handling")]
@@ -55,7 +116,8 @@ impl<T> Clone for UnitRef<T> {
     fn clone(&self) -> Self {
         Self {
             unit_id: self.unit_id.clone(),
-            weak_unit_context: self.weak_unit_context.clone(),
+            weak_entity_map: self.weak_entity_map.clone(),
+            weak_liveness: self.weak_liveness.clone(),
         }
     }
 }
@@ -73,3 +135,49 @@ impl<T> PartialEq<UnitRef<T>> for UnitId {
         *self == other.unit_id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::unit_map::{UnitId, UnitMap};
+
+    #[test]
+    fn test_unit_ref_invalid_after_removal() {
+        let map = UnitMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        map.insert_unit(unit_id.clone(), ()).unwrap();
+        let unit_ref = map.get_unit(&unit_id).unwrap();
+        assert!(unit_ref.is_valid());
+        assert_eq!(unit_ref.strong_count(), 1);
+
+        map.remove_unit(&unit_id).unwrap();
+        assert!(!unit_ref.is_valid());
+        assert_eq!(unit_ref.strong_count(), 0);
+
+        // A unit inserted again under the same id is not reachable through the old reference
+        map.insert_unit(unit_id.clone(), ()).unwrap();
+        assert!(!unit_ref.is_valid());
+        assert!(unit_ref.view(|_| ()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_view_async() {
+        let map = UnitMap::new();
+        let unit_id = UnitId::from("drone-1");
+
+        map.insert_unit(unit_id.clone(), 42_u32).unwrap();
+        let unit_ref = map.get_unit(&unit_id).unwrap();
+
+        let value = unit_ref
+            .view_async(|context| async move {
+                tokio::task::yield_now().await;
+                *context
+            })
+            .await
+            .unwrap();
+        assert_eq!(value, 42);
+
+        map.remove_unit(&unit_id).unwrap();
+        assert!(unit_ref.view_async(|_| async {}).await.is_err());
+    }
+}